@@ -1,17 +1,146 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use salsa::DebugWithDb;
+
+/// The database holding the loaded documents and the results of every query
+/// computed from them.
+///
+/// Queries only read the database, so [`ParallelDatabase::snapshot`] can be
+/// used to run them from several threads at once, for example to generate
+/// several targets from a single loaded document. Results computed through
+/// one snapshot are reused by the others. Setting an input, such as with
+/// `InputApi::set_api`, waits until every snapshot has been dropped.
+///
+/// ```no_run
+/// use abeye::{generate_md, generate_ts, Config, Database, InputApi, ParallelDatabase};
+///
+/// let doc = serde_json::from_str(&std::fs::read_to_string("openapi.json")?)?;
+/// let db = Database::default();
+/// let api = InputApi::new(&db, doc, Config::default());
+///
+/// let ts = {
+///     let db = db.snapshot();
+///     std::thread::spawn(move || generate_ts(&*db, api))
+/// };
+/// let md = {
+///     let db = db.snapshot();
+///     std::thread::spawn(move || generate_md(&*db, api))
+/// };
+/// let (ts, md) = (ts.join().unwrap(), md.join().unwrap());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [`ParallelDatabase::snapshot`]: crate::ParallelDatabase::snapshot
 #[derive(Default)]
 #[salsa::db(crate::Jar)]
 pub struct Database {
     storage: salsa::Storage<Self>,
+    trace: Option<Arc<Mutex<QueryTrace>>>,
+}
+
+impl Database {
+    /// Creates a database recording every query it executes into `trace`,
+    /// which can also be read back with [`Database::query_trace`]. Snapshots
+    /// record into the same trace.
+    pub fn with_query_trace(trace: Arc<Mutex<QueryTrace>>) -> Database {
+        Database {
+            trace: Some(trace),
+            ..Default::default()
+        }
+    }
+
+    /// The queries executed so far, if the database was created with
+    /// [`Database::with_query_trace`].
+    pub fn query_trace(&self) -> Option<QueryTrace> {
+        let mut trace = self.trace.as_ref()?.lock().unwrap().clone();
+        trace.finish();
+        Some(trace)
+    }
 }
 
 impl salsa::Database for Database {
-    fn salsa_event(&self, _event: salsa::Event) {}
+    fn salsa_event(&self, event: salsa::Event) {
+        let Some(trace) = &self.trace else { return };
+
+        let started = match event.kind {
+            salsa::EventKind::WillExecute { database_key } => {
+                let query = format!("{:?}", database_key.debug(self));
+                tracing::debug!(target: "abeye::queries", "executing {query}");
+                Some(query)
+            }
+            salsa::EventKind::WillBlockOn { database_key, .. } => {
+                let query = format!("{:?}", database_key.debug(self));
+                tracing::debug!(target: "abeye::queries", "blocking on {query}");
+                trace.lock().unwrap().blocked.push(query);
+                None
+            }
+            _ => None,
+        };
+
+        let mut trace = trace.lock().unwrap();
+        trace.finish();
+        if let Some(query) = started {
+            trace.start(query);
+        }
+    }
 }
 
 impl salsa::ParallelDatabase for Database {
     fn snapshot(&self) -> salsa::Snapshot<Self> {
         salsa::Snapshot::new(Database {
             storage: self.storage.snapshot(),
+            trace: self.trace.clone(),
         })
     }
 }
+
+/// The queries executed by a [`Database`] created with
+/// [`Database::with_query_trace`].
+///
+/// Salsa only reports when a query starts executing, so the time of a query
+/// is measured until the next event, which leaves out the time spent in the
+/// queries it calls but may include some of its caller.
+#[derive(Debug, Default, Clone)]
+pub struct QueryTrace {
+    /// The executions of every query function, by name.
+    pub queries: BTreeMap<String, QueryStats>,
+    /// The queries, with their keys, which had to wait for another thread.
+    pub blocked: Vec<String>,
+    /// The most recently executed queries, with their keys, oldest first.
+    /// When a query cycle panics these are the queries leading up to it.
+    pub recent: VecDeque<String>,
+    current: Option<(String, Instant)>,
+}
+
+/// How often, and for how long, a query function was executed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct QueryStats {
+    /// The number of times the query was executed rather than reused.
+    pub executions: usize,
+    /// The time spent executing the query itself, summed over executions.
+    pub time: Duration,
+}
+
+impl QueryTrace {
+    const RECENT: usize = 32;
+
+    fn start(&mut self, query: String) {
+        let name = query.split('(').next().unwrap_or(&query).to_string();
+        self.queries.entry(name.clone()).or_default().executions += 1;
+        if self.recent.len() == Self::RECENT {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(query);
+        self.current = Some((name, Instant::now()));
+    }
+
+    fn finish(&mut self) {
+        if let Some((name, started)) = self.current.take() {
+            self.queries.entry(name).or_default().time += started.elapsed();
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use itertools::Itertools;
+
+use crate::{lower_api, InputApi};
+
+#[salsa::tracked]
+pub fn generate_md(db: &dyn crate::Db, api: InputApi) -> String {
+    use std::fmt::Write;
+
+    let mut buf = String::new();
+
+    let info = &api.api(db).info;
+    writeln!(buf, "# {}", info.title).unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "Version: `{}`", info.version).unwrap();
+
+    let details = api.info_details(db);
+    if !details.is_empty() {
+        writeln!(buf).unwrap();
+        // NOTE: Two trailing spaces keep consecutive detail lines (contact,
+        // license) on separate lines when rendered.
+        writeln!(
+            buf,
+            "{}",
+            details.iter().map(|line| line.trim_end()).format("  \n")
+        )
+        .unwrap();
+    }
+
+    let operations = lower_api(db, api);
+
+    writeln!(buf).unwrap();
+    writeln!(buf, "## Operations").unwrap();
+
+    for op in &operations {
+        writeln!(buf).unwrap();
+        writeln!(buf, "### `{}`", op.name(db, api)).unwrap();
+        writeln!(buf).unwrap();
+        writeln!(buf, "`{} {}`", op.method, op.path).unwrap();
+        for text in [&op.summary, &op.description].into_iter().flatten() {
+            writeln!(buf).unwrap();
+            writeln!(buf, "{}", text.trim()).unwrap();
+        }
+        if op.deprecated {
+            writeln!(buf).unwrap();
+            writeln!(buf, "> **Deprecated.**").unwrap();
+        }
+        if !op.servers.is_empty() {
+            writeln!(buf).unwrap();
+            writeln!(buf, "Served from:").unwrap();
+            writeln!(buf).unwrap();
+            for url in &op.servers {
+                writeln!(buf, "- `{url}`").unwrap();
+            }
+        }
+    }
+
+    tracing::info!("documented {} operation", operations.len());
+
+    buf
+}
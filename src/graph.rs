@@ -0,0 +1,115 @@
+use std::collections::BTreeSet;
+
+use crate::{component_types, lower_api, InputApi};
+
+/// The edges of the reference graph: from every operation to the component
+/// types it uses, and from every component type to those it references.
+struct Graph {
+    /// The name, method, and path of every operation.
+    operations: Vec<(String, &'static str, String)>,
+    types: Vec<String>,
+    /// Edges from the operation with the given name to a type.
+    operation_edges: Vec<(String, String)>,
+    type_edges: Vec<(String, String)>,
+}
+
+impl Graph {
+    fn new(db: &dyn crate::Db, api: InputApi) -> Graph {
+        let operations = lower_api(db, api);
+        let types = component_types(db, api);
+
+        Graph {
+            operations: operations
+                .iter()
+                .map(|op| (op.name(db, api), op.method, op.path.clone()))
+                .collect(),
+            types: types.iter().map(|(name, _)| name.clone()).collect(),
+            operation_edges: operations
+                .iter()
+                .flat_map(|op| {
+                    let name = op.name(db, api);
+                    op.types()
+                        .into_iter()
+                        .flat_map(|ty| ty.references(db))
+                        .collect::<BTreeSet<_>>()
+                        .into_iter()
+                        .map(move |ty| (name.clone(), ty))
+                })
+                .collect(),
+            type_edges: types
+                .iter()
+                .flat_map(|(name, ty)| {
+                    ty.references(db)
+                        .into_iter()
+                        .map(move |reference| (name.clone(), reference))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A Mermaid flowchart of the reference graph, with operations drawn as
+/// stadiums and component types as rectangles.
+#[salsa::tracked]
+pub fn generate_mermaid(db: &dyn crate::Db, api: InputApi) -> String {
+    use std::fmt::Write;
+
+    fn id(prefix: &str, name: &str) -> String {
+        let name = name.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+        format!("{prefix}_{name}")
+    }
+
+    let graph = Graph::new(db, api);
+
+    let mut buf = String::new();
+    writeln!(buf, "flowchart LR").unwrap();
+    for (name, method, path) in &graph.operations {
+        writeln!(buf, "  {}([\"{method} {path}\"])", id("op", name)).unwrap();
+    }
+    for name in &graph.types {
+        writeln!(buf, "  {}[\"{name}\"]", id("type", name)).unwrap();
+    }
+    for (from, to) in &graph.operation_edges {
+        writeln!(buf, "  {} --> {}", id("op", from), id("type", to)).unwrap();
+    }
+    for (from, to) in &graph.type_edges {
+        writeln!(buf, "  {} --> {}", id("type", from), id("type", to)).unwrap();
+    }
+
+    buf
+}
+
+/// A Graphviz DOT graph of the reference graph, with operations drawn as
+/// boxes and component types as ellipses.
+#[salsa::tracked]
+pub fn generate_dot(db: &dyn crate::Db, api: InputApi) -> String {
+    use std::fmt::Write;
+
+    // NOTE: Operations and types may share names, so operations are
+    // prefixed to keep their nodes apart.
+    fn op_id(name: &str) -> String {
+        format!("{:?}", format!("op:{name}"))
+    }
+
+    let graph = Graph::new(db, api);
+
+    let mut buf = String::new();
+    writeln!(buf, "digraph api {{").unwrap();
+    writeln!(buf, "  rankdir=LR;").unwrap();
+    for (name, method, path) in &graph.operations {
+        let label = format!("{method} {path}");
+        writeln!(buf, "  {} [shape=box, label={label:?}];", op_id(name)).unwrap();
+    }
+    for name in &graph.types {
+        writeln!(buf, "  {name:?};").unwrap();
+    }
+    for (from, to) in &graph.operation_edges {
+        writeln!(buf, "  {} -> {to:?};", op_id(from)).unwrap();
+    }
+    for (from, to) in &graph.type_edges {
+        writeln!(buf, "  {from:?} -> {to:?};").unwrap();
+    }
+    writeln!(buf, "}}").unwrap();
+
+    buf
+}
@@ -1,12 +1,37 @@
 mod db;
+mod docs;
+mod graph;
+mod lock;
+mod py;
+mod rs;
+mod stats;
+mod template;
+mod transform;
 mod ts;
 
 use camino::Utf8PathBuf;
-pub use db::Database;
-pub use ts::generate_ts;
+pub use db::{Database, QueryStats, QueryTrace};
+pub use docs::generate_md;
+pub use graph::{generate_dot, generate_mermaid};
+pub use lock::{Lock, PropertyShape, Shape, Signature};
+pub use py::generate_py;
+pub use rs::generate_rs;
+/// Re-exported for taking snapshots of a [`Database`], see its documentation.
+pub use salsa::{ParallelDatabase, Snapshot};
+pub use stats::{compatibility, media_type_stats, Compatibility, FeatureGap, MediaTypeStats};
+pub use template::{
+    generate_template, TemplateApi, TemplateBody, TemplateOperation, TemplateParam,
+    TemplateResponse, TemplateType,
+};
+pub use transform::{
+    apply_transforms, bundle_external_refs, convert_openapi_31, generic_families, merge_document,
+    sanitize_schema_names, Overlay, OverlayAction, PatchOperation, Transform,
+};
+pub use ts::{generate_ts, generate_ts_files, generate_ts_runtime, ts_size_report, SizeEntry};
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
+use heck::ToLowerCamelCase;
 use itertools::Itertools;
 use openapiv3 as oapi;
 
@@ -14,20 +39,533 @@ use openapiv3 as oapi;
 pub struct Jar(
     InputApi,
     Type,
+    Doc,
     Schema,
     generate_ts,
+    generate_ts_files,
+    ts::ts_type_module,
+    generate_md,
+    generate_mermaid,
+    generate_dot,
+    generate_py,
+    generate_rs,
+    lower_api,
     schema_by_name,
+    recursive_schema,
+    schema_generics,
     schema_ty,
     simplify_ty,
+    discriminator_tags,
+    Diagnostics,
+    lower_all,
+    check_route_enums,
 );
 
 pub trait Db: salsa::DbWithJar<Jar> {}
 
 impl<DB> Db for DB where DB: ?Sized + salsa::DbWithJar<Jar> {}
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Options of the generated output, read from the file given by `--config`
+/// with the fields named as they are here and overridden by the flags of
+/// `abeye generate`. Library users can deserialize it the same way, or
+/// assemble it with [`Config::builder`].
+///
+/// ```toml
+/// name_strategy = "operation-id"
+/// typed_errors = true
+/// constants = ["*Status", "Country"]
+///
+/// [operations."GET /users/{id}"]
+/// rename = "getUser"
+/// response_schema = "User"
+///
+/// [media_types."application/msgpack"]
+/// module = "./msgpack"
+/// encode = "encodeMsgpack"
+/// decode = "decodeMsgpack"
+///
+/// [schema_types.Decimal]
+/// ts = "Big"
+/// from = "big.js"
+///
+/// [json]
+/// module = "./json"
+/// revive = "camelCaseKeys"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
+    /// The prefix removed from paths when naming methods, without a trailing
+    /// `/`.
+    #[serde(deserialize_with = "deserialize_api_prefix")]
     pub api_prefix: Option<Utf8PathBuf>,
+    /// How the generated methods are named.
+    pub name_strategy: NameStrategy,
+    pub empty_response: EmptyResponse,
+    /// Resolve methods with the status and body of every documented response
+    /// of JSON operations, as a union discriminated by the status, instead of
+    /// rejecting those which aren't successful.
+    pub status_results: bool,
+    /// Reject requests whose response isn't successful with an `ApiError`
+    /// holding the status and parsed body, typed by the error responses
+    /// documented for each operation.
+    pub typed_errors: bool,
+    /// Per-operation overrides keyed by the uppercase method and the path
+    /// template, for example `"GET /users/{id}"`.
+    pub operations: BTreeMap<String, OperationOverride>,
+    /// Fill in query parameters with a documented default when the caller
+    /// omits them, making them optional in the generated client.
+    pub fill_query_defaults: bool,
+    /// Export a constant per operation describing its method and path.
+    pub operation_constants: bool,
+    /// Import the runtime helpers from this module specifier instead of
+    /// including them in the generated output. See `abeye runtime`.
+    pub runtime_import: Option<String>,
+    pub sse_transport: SseTransport,
+    pub runtime: Runtime,
+    /// Report the name, method, path, status, and duration of every request
+    /// to the hook registered with `setOnMetric`.
+    pub telemetry: bool,
+    /// Send the W3C `traceparent` header of the context returned by the
+    /// getter registered with `setTraceContext`. Can be overridden per
+    /// operation with the `x-abeye-trace` extension.
+    pub trace_context: bool,
+    /// Send the token returned by the `csrfToken` option in this header with
+    /// every POST, PUT, PATCH, and DELETE request.
+    pub csrf_header: Option<String>,
+    /// Share a single request between identical concurrent GET calls. Can be
+    /// overridden per operation with the `x-abeye-dedupe` extension.
+    pub dedupe: bool,
+    /// Generate `offlineQueue`, which persists POST, PUT, PATCH, and DELETE
+    /// calls made while offline and replays them once back online.
+    pub offline_queue: bool,
+    /// Export `cacheKeys`, deriving a stable key from the parameters of every
+    /// GET operation for use with client side caches.
+    pub cache_keys: bool,
+    /// Export `parseXQuery` for every GET operation with query parameters,
+    /// validating and converting `URLSearchParams` into its typed query.
+    pub query_parsers: bool,
+    /// Export `pollX` for every GET operation responding with JSON, repeating
+    /// the request on an interval and yielding every response.
+    pub poll_helpers: bool,
+    /// Export `makeX` for every component schema used as a request body which
+    /// is a tagged union, with a function per variant filling in its tag.
+    pub union_constructors: bool,
+    /// Export `createApi`, which creates a client with the same methods as
+    /// `api` using the given options, such as a `locale`, for every call.
+    pub client_factory: bool,
+    /// Export the `Paths` type mapping operation names to their path
+    /// templates, along with `PathOf` and `ExtractParams` for building
+    /// generic wrappers keyed on them.
+    pub path_types: bool,
+    /// Export `createMockApi`, creating an object like `api` whose methods
+    /// resolve with fake data instead of making requests, along with `fakes`
+    /// creating a fake value of every component type.
+    pub mock_api: bool,
+    /// Export a type guard per component type, and check JSON responses and
+    /// event stream payloads against them at runtime.
+    pub validate: bool,
+    pub ts_validators: TsValidators,
+    /// What fields of intersected objects with incompatible types become.
+    pub intersection_conflict: IntersectionConflict,
+    /// Export a client object per tag, such as `searchApi`, holding the
+    /// methods of the operations with that tag. `api` combines all of them.
+    pub tag_clients: bool,
+    /// How the methods of the `api` object are grouped.
+    pub group_by: GroupBy,
+    /// Precede every component type with a comment holding its
+    /// [`Type::content_hash`].
+    pub type_hashes: bool,
+    /// Leave a commented placeholder in the `api` object for every operation
+    /// skipped for being unsupported, and don't count those against
+    /// `--strict`.
+    pub keep_going: bool,
+    /// How types are imported alongside values from the same module.
+    pub type_imports: TypeImports,
+    /// Brand every component type with its name, so that structurally
+    /// identical types are not interchangeable.
+    pub branded: bool,
+    /// Encoders and decoders for media types other than JSON, keyed by the
+    /// media type.
+    pub media_types: BTreeMap<String, MediaTypeCodec>,
+    /// Types emitted in place of the component schemas with the given names.
+    pub schema_types: BTreeMap<String, CustomType>,
+    /// Types emitted in place of strings and numbers with the given `format`.
+    pub format_types: BTreeMap<String, CustomType>,
+    /// The enumerations whose values are exported as an array, such as
+    /// `STATUSES`, matched against the names of their component schemas.
+    /// `None` exports the values of every enumeration.
+    #[serde(rename = "constants", deserialize_with = "deserialize_globs")]
+    pub constant_schemas: Option<Vec<globset::Glob>>,
+    /// Hooks applied to every JSON body parsed or serialized by the
+    /// generated TypeScript runtime.
+    #[serde(rename = "json")]
+    pub json_hooks: Option<JsonHooks>,
+    /// Declare the families of component schemas found by
+    /// [`generic_families`], such as `PaginatedResponse_User` and
+    /// `PaginatedResponse_Post`, as a single generic type referenced as
+    /// `PaginatedResponse<User>`. Only supported by the TypeScript, Python,
+    /// and Rust targets.
+    pub generic_schemas: bool,
+    /// Options only affecting a single target, from the `[target.ts]`,
+    /// `[target.zod]`, and `[target.python]` sections.
+    pub target: TargetConfigs,
+}
+
+/// The options of each target, so that every generator only reads its own.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TargetConfigs {
+    pub ts: TsConfig,
+    pub zod: ZodConfig,
+    pub python: PyConfig,
+}
+
+/// Options of the TypeScript target.
+///
+/// ```toml
+/// [target.ts]
+/// import_extension = ".js"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TsConfig {
+    /// Appended to the specifiers of the modules importing each other with
+    /// `--split`, such as `".js"` for ES modules resolved by Node.js.
+    pub import_extension: String,
+}
+
+/// Options of the Zod schemas generated with `--ts-validators zod`.
+///
+/// ```toml
+/// [target.zod]
+/// module = "zod/v4"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ZodConfig {
+    /// The module to import `z` from instead of `zod`.
+    pub module: Option<String>,
+}
+
+/// Options of the Python target.
+///
+/// ```toml
+/// [target.python]
+/// enum_style = "enum"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PyConfig {
+    pub enum_style: PyEnumStyle,
+}
+
+/// How the Python target declares component schemas which are enumerations
+/// of strings.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PyEnumStyle {
+    /// An alias of a `Literal`, such as `Status = Literal["open", "closed"]`.
+    #[default]
+    Literal,
+    /// A subclass of `str` and `Enum`, with a member per value.
+    Enum,
+}
+
+fn deserialize_api_prefix<'de, D>(deserializer: D) -> Result<Option<Utf8PathBuf>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let prefix: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(prefix.map(|prefix| prefix.trim_end_matches('/').into()))
+}
+
+fn deserialize_globs<'de, D>(deserializer: D) -> Result<Option<Vec<globset::Glob>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let patterns: Option<Vec<String>> = serde::Deserialize::deserialize(deserializer)?;
+    patterns
+        .map(|patterns| {
+            patterns
+                .iter()
+                .map(|pattern| globset::Glob::new(pattern))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+/// Assembles a [`Config`], starting from the defaults or from a deserialized
+/// configuration.
+///
+/// ```
+/// use abeye::{Config, NameStrategy};
+///
+/// let config = Config::builder()
+///     .name_strategy(NameStrategy::OperationId)
+///     .typed_errors(true)
+///     .build();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+macro_rules! config_setters {
+    ($($field:ident: $ty:ty,)*) => {
+        impl ConfigBuilder {
+            $(
+                #[doc = concat!("Sets [`Config::", stringify!($field), "`].")]
+                pub fn $field(mut self, $field: impl Into<$ty>) -> ConfigBuilder {
+                    self.config.$field = $field.into();
+                    self
+                }
+            )*
+        }
+    };
+}
+
+config_setters! {
+    name_strategy: NameStrategy,
+    empty_response: EmptyResponse,
+    status_results: bool,
+    typed_errors: bool,
+    operations: BTreeMap<String, OperationOverride>,
+    fill_query_defaults: bool,
+    operation_constants: bool,
+    runtime_import: Option<String>,
+    sse_transport: SseTransport,
+    runtime: Runtime,
+    telemetry: bool,
+    trace_context: bool,
+    csrf_header: Option<String>,
+    dedupe: bool,
+    offline_queue: bool,
+    cache_keys: bool,
+    query_parsers: bool,
+    poll_helpers: bool,
+    union_constructors: bool,
+    client_factory: bool,
+    path_types: bool,
+    mock_api: bool,
+    validate: bool,
+    intersection_conflict: IntersectionConflict,
+    tag_clients: bool,
+    group_by: GroupBy,
+    type_hashes: bool,
+    keep_going: bool,
+    type_imports: TypeImports,
+    branded: bool,
+    media_types: BTreeMap<String, MediaTypeCodec>,
+    schema_types: BTreeMap<String, CustomType>,
+    format_types: BTreeMap<String, CustomType>,
+    constant_schemas: Option<Vec<globset::Glob>>,
+    json_hooks: Option<JsonHooks>,
+    generic_schemas: bool,
+    target: TargetConfigs,
+}
+
+impl ConfigBuilder {
+    /// Sets [`Config::api_prefix`], removing any trailing `/`.
+    pub fn api_prefix(mut self, api_prefix: impl AsRef<str>) -> ConfigBuilder {
+        self.config.api_prefix = Some(api_prefix.as_ref().trim_end_matches('/').into());
+        self
+    }
+
+    /// Sets [`Config::ts_validators`], which implies [`Config::validate`].
+    pub fn ts_validators(mut self, ts_validators: TsValidators) -> ConfigBuilder {
+        self.config.ts_validators = ts_validators;
+        self.config.validate = true;
+        self
+    }
+
+    pub fn build(self) -> Config {
+        self.config
+    }
+}
+
+impl From<Config> for ConfigBuilder {
+    fn from(config: Config) -> ConfigBuilder {
+        ConfigBuilder { config }
+    }
+}
+
+impl Config {
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    fn operation_override(&self, method: &str, path: &str) -> OperationOverride {
+        self.operations
+            .get(&format!("{method} {path}"))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Adjustments to a single operation, for fixing up documents which can't be
+/// changed at the source.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OperationOverride {
+    /// Name the generated method this instead of deriving it from the path.
+    pub rename: Option<String>,
+    /// Leave the operation out of the generated output.
+    pub skip: bool,
+    /// Use the response content of this media type when several are
+    /// documented.
+    pub response_media_type: Option<String>,
+    /// Type the response as this component schema, regardless of what is
+    /// documented.
+    pub response_schema: Option<String>,
+    /// Rename path parameters, mapping the documented name to the placeholder
+    /// used in the path template.
+    pub path_params: BTreeMap<String, String>,
+}
+
+/// A user provided encoder and decoder for a media type, imported by the
+/// generated client.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MediaTypeCodec {
+    /// The module specifier to import `encode` and `decode` from.
+    pub module: String,
+    /// The name of an export of the type `(value: unknown) => BodyInit`.
+    pub encode: String,
+    /// The name of an export of the type
+    /// `(response: Response) => Promise<unknown>`.
+    pub decode: String,
+}
+
+/// A user provided reviver and replacer for JSON bodies, imported by the
+/// generated runtime, such as for converting the case of keys or parsing
+/// decimal strings.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonHooks {
+    /// The module specifier to import `revive` and `serialize` from.
+    pub module: String,
+    /// The name of an export passed to `JSON.parse` as its reviver.
+    #[serde(default)]
+    pub revive: Option<String>,
+    /// The name of an export passed to `JSON.stringify` as its replacer.
+    #[serde(default)]
+    pub serialize: Option<String>,
+}
+
+/// A TypeScript type emitted verbatim in place of a lowered type.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomType {
+    /// The TypeScript type, for example `Big`.
+    pub ts: String,
+    /// The module to import `ts` from, which must then be a single name.
+    #[serde(default)]
+    pub from: Option<String>,
+}
+
+/// How responses without a meaningful body (no content, or an object schema
+/// without any properties) are represented in the generated client.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmptyResponse {
+    /// Resolve to an empty object typed as `Record<string, never>`.
+    #[default]
+    Record,
+    /// Resolve to `void`, discarding whatever the server sent.
+    Void,
+}
+
+/// How the generated TypeScript imports types alongside values from the same
+/// module. Either compiles with `isolatedModules` and `verbatimModuleSyntax`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TypeImports {
+    /// Mark types inline, as in `import { type User, isUser }`, which needs
+    /// TypeScript 4.5 or newer.
+    #[default]
+    Inline,
+    /// Import types with a separate `import type` statement, for tools which
+    /// don't understand inline type modifiers.
+    Separate,
+}
+
+/// What a field becomes when the objects of an intersection declare it with
+/// incompatible types, such as `number` and an object.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IntersectionConflict {
+    /// Type the field as `never`.
+    #[default]
+    Never,
+    /// Keep the intersection of the types, for example `number & { ... }`.
+    Intersect,
+    /// Fail, listing the conflicting types.
+    Error,
+}
+
+/// How the generated methods are named, unless renamed by an
+/// [`OperationOverride`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NameStrategy {
+    /// Join the segments of the path, with the API prefix removed. The method
+    /// is prepended when several operations share the path.
+    #[default]
+    Path,
+    /// Use the `operationId`, falling back to the path for operations
+    /// without one.
+    OperationId,
+}
+
+/// How the methods of the generated `api` object are grouped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupBy {
+    /// Define every method on `api` itself.
+    #[default]
+    None,
+    /// Define the methods on an object per tag, such as `api.search`, using
+    /// the first tag of each operation. Untagged operations are defined on
+    /// `api.default`.
+    Tag,
+}
+
+/// How `validate` checks values at runtime.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TsValidators {
+    /// Type guards built from the combinators of the runtime.
+    #[default]
+    Guards,
+    /// Zod schemas, exported as `UserSchema` for the type `User`. Requires
+    /// `zod` to be installed.
+    Zod,
+}
+
+/// How event streams are consumed by the generated client.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SseTransport {
+    /// Use the browser's `EventSource`. Only supports GET requests.
+    #[default]
+    EventSource,
+    /// Use `fetch` and parse the response stream, which works in any runtime
+    /// providing `fetch`, such as Deno and Cloudflare Workers.
+    Fetch,
+}
+
+/// The JavaScript runtime the generated client targets.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Runtime {
+    #[default]
+    Browser,
+    /// Node.js 18 or newer, relying on the global `fetch` and
+    /// `AbortController`. Cookies are passed explicitly through the `cookie`
+    /// option, and event streams always use the fetch transport.
+    Node,
 }
 
 #[salsa::input]
@@ -37,15 +575,88 @@ pub struct InputApi {
     pub config: Config,
 }
 
+impl InputApi {
+    /// Description, contact, and license details from the document's `info`
+    /// object, one entry per line. The title and version are left to the
+    /// caller, as each target presents them differently.
+    fn info_details(self, db: &dyn crate::Db) -> Vec<String> {
+        let info = &self.api(db).info;
+
+        let mut lines = Vec::new();
+
+        if let Some(description) = &info.description {
+            lines.extend(description.trim().lines().map(String::from));
+            lines.push(String::new());
+        }
+        if let Some(contact) = &info.contact {
+            let parts = [
+                contact.name.clone(),
+                contact.email.as_ref().map(|email| format!("<{email}>")),
+                contact.url.as_ref().map(|url| format!("({url})")),
+            ]
+            .into_iter()
+            .flatten()
+            .join(" ");
+            if !parts.is_empty() {
+                lines.push(format!("Contact: {parts}"));
+            }
+        }
+        if let Some(license) = &info.license {
+            match &license.url {
+                Some(url) => lines.push(format!("License: {} ({url})", license.name)),
+                None => lines.push(format!("License: {}", license.name)),
+            }
+        }
+
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+
+        lines
+    }
+}
+
+/// A lowered type. Types are interned, so structurally equal types compare
+/// equal.
+#[salsa::interned]
+pub struct Type {
+    pub kind: TypeKind,
+}
+
+/// The documentation of a schema, such as its `description`. Interned so that
+/// [`Property`] can hold it while staying `Copy`.
 #[salsa::interned]
-struct Type {
-    kind: TypeKind,
+pub struct Doc {
+    pub text: String,
+}
+
+impl Doc {
+    /// The documentation of the schema, unless it is a reference, whose
+    /// documentation belongs to the component it references.
+    fn of<T: std::ops::Deref<Target = oapi::Schema>>(
+        db: &dyn crate::Db,
+        schema: &oapi::ReferenceOr<T>,
+    ) -> Option<Doc> {
+        match schema {
+            oapi::ReferenceOr::Item(schema) => schema
+                .schema_data
+                .description
+                .clone()
+                .map(|text| Doc::new(db, text)),
+            oapi::ReferenceOr::Reference { .. } => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-enum TypeKind {
+pub enum TypeKind {
+    /// A named component schema.
     Reference(String),
     Object(BTreeMap<String, Property>),
+    /// An object with arbitrary keys of the first type and values of the
+    /// second, from `additionalProperties`. Keys are strings unless given by
+    /// `x-key-type` or `propertyNames`.
+    Map(Type, Type),
     Array(Type),
     Tuple(Vec<Type>),
     Or(Vec<Type>),
@@ -54,46 +665,181 @@ enum TypeKind {
     Ident(String),
     String,
     Boolean,
+    Null,
+    /// A type given by a [`CustomType`], emitted verbatim.
+    Custom(String),
+    /// An instance of the generic type declared for a family of component
+    /// schemas with [`Config::generic_schemas`], given its type arguments.
+    Generic(String, Vec<Type>),
+    /// The type parameter of a generic type declaration.
+    Parameter(String),
+    /// Any value, for schemas which don't constrain it such as `{}`, and for
+    /// those which aren't supported.
+    Unknown,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Property {
-    ty: Type,
-    optional: bool,
+pub struct Property {
+    pub ty: Type,
+    pub optional: bool,
+    /// The description of the property schema.
+    pub doc: Option<Doc>,
 }
 impl Property {
     fn required(ty: Type) -> Self {
         Property {
             ty,
             optional: false,
+            doc: None,
         }
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum RequestKind {
+pub enum RequestKind {
     Json(Type),
+    /// Encoded with the [`MediaTypeCodec`] configured for the
+    /// [`Operation::request_media_type`].
+    Custom(Type),
+    /// `multipart/form-data`, with the properties of `format: binary` typed
+    /// as files.
+    Multipart(Type),
+    /// `application/x-www-form-urlencoded`, as sent by HTML forms and OAuth
+    /// token endpoints.
+    Form(Type),
 }
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ResponseKind {
+
+impl RequestKind {
+    /// The type of the body, regardless of how it is encoded.
+    pub fn ty(self) -> Type {
+        match self {
+            RequestKind::Json(ty)
+            | RequestKind::Custom(ty)
+            | RequestKind::Multipart(ty)
+            | RequestKind::Form(ty) => ty,
+        }
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResponseKind {
+    Empty,
     Plain,
     Json(Type),
     EventStream(Type),
+    /// CSV with a header row, parsed into rows of the type.
+    Csv(Type),
+    /// Binary content such as images, resolved as a `Blob`.
+    Blob,
+    /// XML, passed through as text. Configuring a [`MediaTypeCodec`] for the
+    /// media type decodes it instead.
+    Xml,
+    /// Decoded with the [`MediaTypeCodec`] configured for the
+    /// [`Operation::response_media_type`].
+    Custom(Type),
+    /// A `3XX` response documenting a `Location` header, resolved as the URL
+    /// redirected to rather than followed.
+    Redirect,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Operation {
-    path: String,
-    query: BTreeMap<String, Type>,
-    path_params: BTreeMap<String, Type>,
-    body: Option<RequestKind>,
-    response: Option<ResponseKind>,
+pub struct QueryParam {
+    pub ty: Type,
+    /// The default value from the parameter's schema.
+    pub default: Option<serde_json::Value>,
+    /// Whether the parameter is documented as `required`.
+    pub required: bool,
+    /// How arrays and objects are written into the query string.
+    pub style: QueryStyle,
+    /// Whether array items and object properties are written as separate
+    /// parameters, defaulting to `true` for [`QueryStyle::Form`] only.
+    pub explode: bool,
+}
+
+/// The `style` of a query parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryStyle {
+    /// `a=1&a=2`, or `a=1,2` when not exploded.
+    Form,
+    /// `a=1%202`.
+    SpaceDelimited,
+    /// `a=1|2`.
+    PipeDelimited,
+    /// `a[key]=value`, for objects.
+    DeepObject,
+}
+
+impl QueryStyle {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QueryStyle::Form => "form",
+            QueryStyle::SpaceDelimited => "spaceDelimited",
+            QueryStyle::PipeDelimited => "pipeDelimited",
+            QueryStyle::DeepObject => "deepObject",
+        }
+    }
+}
+
+impl QueryParam {
+    /// Whether callers may leave out the parameter, either because it isn't
+    /// required or because its default is filled in per
+    /// [`Config::fill_query_defaults`].
+    pub fn optional(&self, config: &Config) -> bool {
+        !self.required || (config.fill_query_defaults && self.default.is_some())
+    }
+}
+
+/// A lowered operation, that is a single method on a single path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operation {
+    pub method: &'static str,
+    pub path: String,
+    pub query: BTreeMap<String, QueryParam>,
+    pub path_params: BTreeMap<String, Type>,
+    /// The header parameters by name, besides `If-Match` which sets
+    /// [`Operation::if_match`].
+    pub headers: BTreeMap<String, Type>,
+    pub body: Option<RequestKind>,
+    /// The response the client resolves with: the first successful one, or
+    /// else the `default` response.
+    pub response: Option<ResponseKind>,
+    /// Every documented response keyed by its status, such as `200`, `4XX`,
+    /// or `default`, in the order they are documented. Responses which can't
+    /// be lowered are left out.
+    pub responses: Vec<(String, ResponseKind)>,
+    /// The media type of the request body, if any.
+    pub request_media_type: Option<String>,
+    /// The media type of the response, if it has any content.
+    pub response_media_type: Option<String>,
+    /// Whether the operation is guarded by an `If-Match` header, requiring
+    /// the caller to pass the etag of the resource being modified.
+    pub if_match: bool,
+    /// Whether the operation documents a 429 response or rate limit headers,
+    /// such as `Retry-After` or `X-RateLimit-Remaining`.
+    pub rate_limited: bool,
+    /// The `operationId` of the operation, if any.
+    pub operation_id: Option<String>,
+    /// The `summary` of the operation, if any.
+    pub summary: Option<String>,
+    /// The `description` of the operation, if any.
+    pub description: Option<String>,
+    /// The tags of the operation, in the order they are listed.
+    pub tags: Vec<String>,
+    /// Whether the operation is documented as deprecated.
+    pub deprecated: bool,
+    /// The URLs of the servers of the operation, or of its path item, which
+    /// override those of the document. Empty if neither declares any.
+    pub servers: Vec<String>,
+    /// The `x-` specification extensions of the operation.
+    pub extensions: BTreeMap<String, serde_json::Value>,
 }
 
 #[salsa::tracked]
 struct Schema {
     #[return_ref]
     schema: OapiSchema,
+    /// The JSON pointer of the schema in the document, for diagnostics.
+    #[return_ref]
+    pointer: String,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -104,8 +850,8 @@ struct OapiSchema {
 impl Eq for OapiSchema {}
 
 impl Schema {
-    fn from_oapi(db: &dyn crate::Db, schema: oapi::Schema) -> Schema {
-        Schema::new(db, OapiSchema { schema })
+    fn from_oapi(db: &dyn crate::Db, schema: oapi::Schema, pointer: String) -> Schema {
+        Schema::new(db, OapiSchema { schema }, pointer)
     }
     fn kind(self, db: &dyn crate::Db) -> &oapi::SchemaKind {
         &self.schema(db).schema.schema_kind
@@ -116,12 +862,18 @@ impl Schema {
 }
 
 impl Type {
+    /// The type of values the document doesn't describe in a supported way.
+    pub fn unknown(db: &dyn crate::Db) -> Type {
+        Type::new(db, TypeKind::Unknown)
+    }
+
     pub fn constants(self, db: &dyn crate::Db) -> Option<Vec<String>> {
         match self.kind(db) {
             TypeKind::Or(options)
-                if options
-                    .iter()
-                    .all(|opt| matches!(opt.kind(db), TypeKind::Ident(_))) =>
+                if !options.is_empty()
+                    && options
+                        .iter()
+                        .all(|opt| matches!(opt.kind(db), TypeKind::Ident(_))) =>
             {
                 Some(
                     options
@@ -136,206 +888,1788 @@ impl Type {
             _ => None,
         }
     }
+
+    /// The names of all component schemas referenced by the type.
+    fn references(self, db: &dyn crate::Db) -> BTreeSet<String> {
+        match self.kind(db) {
+            TypeKind::Reference(name) => [name].into_iter().collect(),
+            TypeKind::Object(obj) => obj
+                .values()
+                .flat_map(|prop| prop.ty.references(db))
+                .collect(),
+            TypeKind::Map(keys, values) => [keys, values]
+                .into_iter()
+                .flat_map(|ty| ty.references(db))
+                .collect(),
+            TypeKind::Array(ty) => ty.references(db),
+            TypeKind::Tuple(tys)
+            | TypeKind::Or(tys)
+            | TypeKind::And(tys)
+            | TypeKind::Generic(_, tys) => tys.iter().flat_map(|ty| ty.references(db)).collect(),
+            TypeKind::Number
+            | TypeKind::Ident(_)
+            | TypeKind::String
+            | TypeKind::Boolean
+            | TypeKind::Null
+            | TypeKind::Custom(_)
+            | TypeKind::Parameter(_)
+            | TypeKind::Unknown => BTreeSet::new(),
+        }
+    }
+
+    /// The [`TypeKind::Custom`] types used by the type.
+    fn custom_types(self, db: &dyn crate::Db) -> BTreeSet<String> {
+        match self.kind(db) {
+            TypeKind::Custom(ts) => [ts].into_iter().collect(),
+            TypeKind::Object(obj) => obj
+                .values()
+                .flat_map(|prop| prop.ty.custom_types(db))
+                .collect(),
+            TypeKind::Map(keys, values) => [keys, values]
+                .into_iter()
+                .flat_map(|ty| ty.custom_types(db))
+                .collect(),
+            TypeKind::Array(ty) => ty.custom_types(db),
+            TypeKind::Tuple(tys)
+            | TypeKind::Or(tys)
+            | TypeKind::And(tys)
+            | TypeKind::Generic(_, tys) => tys.iter().flat_map(|ty| ty.custom_types(db)).collect(),
+            TypeKind::Reference(_)
+            | TypeKind::Number
+            | TypeKind::Ident(_)
+            | TypeKind::String
+            | TypeKind::Boolean
+            | TypeKind::Null
+            | TypeKind::Parameter(_)
+            | TypeKind::Unknown => BTreeSet::new(),
+        }
+    }
+
+    /// Follows references to component schemas until reaching a type which
+    /// is not a reference, or a reference back to a schema already followed.
+    fn resolve(self, db: &dyn crate::Db, api: InputApi) -> Type {
+        let mut ty = self;
+        let mut followed = BTreeSet::new();
+        while let TypeKind::Reference(name) = ty.kind(db) {
+            if !followed.insert(name.clone()) {
+                break;
+            }
+            match lower_schema(db, api, &name) {
+                Some(next) => ty = next,
+                None => break,
+            }
+        }
+        ty
+    }
+
+    /// The type with every occurrence of `from` replaced by `to`.
+    fn substitute(self, db: &dyn crate::Db, from: Type, to: Type) -> Type {
+        if self == from {
+            return to;
+        }
+        let all = |types: Vec<Type>| -> Vec<Type> {
+            types
+                .into_iter()
+                .map(|ty| ty.substitute(db, from, to))
+                .collect()
+        };
+        let kind = match self.kind(db) {
+            TypeKind::Object(properties) => TypeKind::Object(
+                properties
+                    .into_iter()
+                    .map(|(name, prop)| {
+                        let ty = prop.ty.substitute(db, from, to);
+                        (name, Property { ty, ..prop })
+                    })
+                    .collect(),
+            ),
+            TypeKind::Map(keys, values) => TypeKind::Map(
+                keys.substitute(db, from, to),
+                values.substitute(db, from, to),
+            ),
+            TypeKind::Array(items) => TypeKind::Array(items.substitute(db, from, to)),
+            TypeKind::Tuple(types) => TypeKind::Tuple(all(types)),
+            TypeKind::Or(types) => TypeKind::Or(all(types)),
+            TypeKind::And(types) => TypeKind::And(all(types)),
+            TypeKind::Generic(name, types) => TypeKind::Generic(name, all(types)),
+            TypeKind::Reference(_)
+            | TypeKind::Number
+            | TypeKind::Ident(_)
+            | TypeKind::String
+            | TypeKind::Boolean
+            | TypeKind::Null
+            | TypeKind::Custom(_)
+            | TypeKind::Parameter(_)
+            | TypeKind::Unknown => return self,
+        };
+        Type::new(db, kind)
+    }
+
+    /// Whether the type is the component schema `name` itself, or a union or
+    /// intersection including it, looking through references to other
+    /// component schemas. TypeScript rejects such types as circular, unlike
+    /// those referencing themselves from within objects or arrays.
+    fn is_circular(self, db: &dyn crate::Db, api: InputApi, name: &str) -> bool {
+        let mut followed = BTreeSet::new();
+        let mut pending = vec![self];
+        while let Some(ty) = pending.pop() {
+            match ty.kind(db) {
+                TypeKind::Reference(other) if other == name => return true,
+                TypeKind::Reference(other) => {
+                    if api.config(db).schema_types.contains_key(&other)
+                        || !followed.insert(other.clone())
+                    {
+                        continue;
+                    }
+                    if let Some(schema) = schema_by_name(db, api, other) {
+                        let pointer = format!("/components/schemas/{}", escape_pointer(&other));
+                        pending.push(simplify_ty(db, api, schema_ty(db, api, schema), pointer));
+                    }
+                }
+                TypeKind::Or(types) | TypeKind::And(types) => pending.extend(types),
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Whether the type is an object without any properties, looking through
+    /// references to component schemas. Free-form objects are lowered to
+    /// maps, so an empty object admits no additional properties either.
+    fn is_empty_object(self, db: &dyn crate::Db, api: InputApi) -> bool {
+        matches!(self.resolve(db, api).kind(db), TypeKind::Object(obj) if obj.is_empty())
+    }
+
+    /// The values of an enumeration of strings, leaving out `null`. `None`
+    /// if the type isn't such an enumeration.
+    fn enum_values(self, db: &dyn crate::Db) -> Option<BTreeSet<String>> {
+        match self.kind(db) {
+            TypeKind::Ident(value) => Some([value].into()),
+            TypeKind::Or(options) => options
+                .iter()
+                .filter(|option| option.kind(db) != TypeKind::Null)
+                .map(|option| match option.kind(db) {
+                    TypeKind::Ident(value) => Some(value),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// The properties of an object, looking through references and the parts
+    /// of intersections. `None` if any part isn't an object.
+    fn object_properties(
+        self,
+        db: &dyn crate::Db,
+        api: InputApi,
+    ) -> Option<BTreeMap<String, Property>> {
+        match self.resolve(db, api).kind(db) {
+            TypeKind::Object(obj) => Some(obj),
+            TypeKind::And(parts) => parts.iter().try_fold(BTreeMap::new(), |mut obj, part| {
+                obj.extend(part.object_properties(db, api)?);
+                Some(obj)
+            }),
+            _ => None,
+        }
+    }
+
+    /// The union as told apart by a required property holding a different
+    /// string literal in every option, such as the property of a
+    /// `discriminator`. `None` if the type isn't such a union.
+    fn tagged_union(self, db: &dyn crate::Db, api: InputApi) -> Option<TaggedUnion> {
+        let TypeKind::Or(options) = self.resolve(db, api).kind(db) else {
+            return None;
+        };
+        let options = options
+            .iter()
+            .map(|option| Some((*option, option.object_properties(db, api)?)))
+            .collect::<Option<Vec<_>>>()?;
+        let tag = |obj: &BTreeMap<String, Property>, property: &str| match obj.get(property) {
+            Some(prop) if !prop.optional => match prop.ty.kind(db) {
+                TypeKind::Ident(tag) => Some(tag),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let (_, first) = options.first()?;
+        first.keys().find_map(|property| {
+            let variants = options
+                .iter()
+                .map(|(option, obj)| {
+                    Some(UnionVariant {
+                        tag: tag(obj, property)?,
+                        ty: *option,
+                        fields: obj.len() - 1,
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?;
+            if !variants.iter().map(|variant| &variant.tag).all_unique() {
+                return None;
+            }
+            Some(TaggedUnion {
+                property: property.clone(),
+                variants,
+            })
+        })
+    }
+}
+
+/// A union of objects told apart by the string literal of a property, see
+/// [`Type::tagged_union`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TaggedUnion {
+    /// The name of the property holding the tag.
+    property: String,
+    variants: Vec<UnionVariant>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UnionVariant {
+    tag: String,
+    /// The option of the union.
+    ty: Type,
+    /// The number of properties of the option besides the tag.
+    fields: usize,
 }
 
 fn resolve_schema(
     db: &dyn crate::Db,
     api: InputApi,
     schema: &oapi::ReferenceOr<oapi::Schema>,
-) -> Schema {
+    pointer: String,
+) -> Option<Schema> {
     match schema {
         oapi::ReferenceOr::Reference { reference } => {
-            schema_by_name(db, api, reference.clone()).unwrap()
+            let schema = schema_by_name(db, api, reference.clone());
+            if schema.is_none() {
+                report(db, pointer, format!("unresolved reference {reference:?}"));
+            }
+            schema
         }
-        oapi::ReferenceOr::Item(schema) => Schema::from_oapi(db, schema.clone()),
+        oapi::ReferenceOr::Item(schema) => Some(Schema::from_oapi(db, schema.clone(), pointer)),
     }
 }
-fn resolve_schema_ty(
-    db: &dyn crate::Db,
-    api: InputApi,
-    schema: &oapi::ReferenceOr<oapi::Schema>,
-) -> Type {
-    schema_ty(db, api, resolve_schema(db, api, schema))
-}
 fn shallow_schema_ty(
     db: &dyn crate::Db,
     api: InputApi,
     schema: &oapi::ReferenceOr<oapi::Schema>,
+    pointer: String,
 ) -> Type {
     match schema {
         oapi::ReferenceOr::Reference { reference } => {
             if let Some(name) = reference.strip_prefix("#/components/schemas/") {
-                if name.contains('_') {
-                    resolve_schema_ty(db, api, schema)
+                if let Some(custom) = api.config(db).schema_types.get(name) {
+                    Type::new(db, TypeKind::Custom(custom.ts.clone()))
+                } else if let Some((generic, argument)) = generic_instance(db, api, name) {
+                    let argument = ty_by_name(db, api, format!("#/components/schemas/{argument}"));
+                    Type::new(db, TypeKind::Generic(generic, vec![argument]))
+                } else if name.contains('_') && !recursive_schema(db, api, name.to_string()) {
+                    resolve_schema(db, api, schema, pointer)
+                        .map(|schema| schema_ty(db, api, schema))
+                        .unwrap_or_else(|| Type::unknown(db))
                 } else {
                     Type::new(db, TypeKind::Reference(name.to_string()))
                 }
             } else {
-                todo!()
+                report(
+                    db,
+                    pointer,
+                    format!("only component schemas can be referenced, not {reference:?}"),
+                );
+                Type::unknown(db)
             }
         }
         oapi::ReferenceOr::Item(schema) => {
-            schema_ty(db, api, Schema::from_oapi(db, schema.clone()))
+            schema_ty(db, api, Schema::from_oapi(db, schema.clone(), pointer))
         }
     }
 }
 
-fn ty_by_name(db: &dyn crate::Db, api: InputApi, name: String) -> Type {
-    shallow_schema_ty(db, api, &oapi::ReferenceOr::Reference { reference: name })
-}
-
-fn operation(
-    db: &dyn crate::Db,
-    api: InputApi,
-    path: String,
-    operation: &oapi::Operation,
-) -> Operation {
-    let mut path_params = BTreeMap::new();
-    let mut query = BTreeMap::new();
-
-    for param in &operation.parameters {
-        match param {
-            oapi::ReferenceOr::Reference { .. } => todo!(),
-            oapi::ReferenceOr::Item(param) => match param {
-                oapi::Parameter::Query { parameter_data, .. } => {
-                    let ty = match &parameter_data.format {
-                        oapi::ParameterSchemaOrContent::Schema(schema) => {
-                            shallow_schema_ty(db, api, schema)
-                        }
-                        oapi::ParameterSchemaOrContent::Content(_) => todo!(),
-                    };
-
-                    query.insert(parameter_data.name.clone(), ty);
-                }
-                oapi::Parameter::Header { .. } => todo!(),
-                oapi::Parameter::Path { parameter_data, .. } => {
-                    let ty = match &parameter_data.format {
-                        oapi::ParameterSchemaOrContent::Schema(schema) => {
-                            shallow_schema_ty(db, api, schema)
-                        }
-                        oapi::ParameterSchemaOrContent::Content(_) => todo!(),
-                    };
+/// The type of the keys of a map schema, given by its `x-key-type` extension
+/// as either a schema or the name of a type such as `integer`. Keys are
+/// strings if it is left out or isn't a string, number, or enumeration.
+fn map_keys(db: &dyn crate::Db, api: InputApi, data: &oapi::SchemaData, pointer: &str) -> Type {
+    let string = Type::new(db, TypeKind::String);
+    let Some(value) = data.extensions.get("x-key-type") else {
+        return string;
+    };
+    let pointer = format!("{pointer}/x-key-type");
 
-                    path_params.insert(parameter_data.name.clone(), ty);
-                }
-                oapi::Parameter::Cookie { .. } => todo!(),
-            },
-        }
-    }
-    let body = if let Some(body) = &operation.request_body {
-        match body {
-            oapi::ReferenceOr::Reference { .. } => todo!(),
-            oapi::ReferenceOr::Item(body) => {
-                assert_eq!(body.content.len(), 1);
-
-                let (media_type, value) = body.content.iter().next().unwrap();
-                let ty = if let Some(schema) = &value.schema {
-                    let ty = simplify_ty(db, shallow_schema_ty(db, api, schema));
-                    let ts = ty.ts(db);
-                    tracing::debug!(?media_type, ty=?ts, "request");
-                    ty
-                } else {
-                    todo!()
-                };
-                match media_type.as_str() {
-                    "application/json" => Some(RequestKind::Json(ty)),
-                    _ => todo!("unhandled request media type: {media_type:?}"),
-                }
-            }
+    let schema = match value {
+        serde_json::Value::String(ty) => serde_json::json!({ "type": ty }),
+        value => value.clone(),
+    };
+    let keys = match serde_json::from_value(schema) {
+        Ok(schema) => shallow_schema_ty(db, api, &schema, pointer.clone()),
+        Err(err) => {
+            report(
+                db,
+                pointer,
+                format!("invalid key type: {err}, assuming string"),
+            );
+            return string;
         }
-    } else {
-        None
     };
-
-    if !path_params.is_empty() {
-        for (path_param, ty) in &path_params {
-            let ty = ty.ts(db);
-            tracing::debug!(?path_param, ?ty);
+    match keys.kind(db) {
+        TypeKind::String | TypeKind::Number | TypeKind::Ident(_) | TypeKind::Reference(_) => keys,
+        TypeKind::Or(options)
+            if options
+                .iter()
+                .all(|option| matches!(option.kind(db), TypeKind::Ident(_))) =>
+        {
+            keys
         }
-    }
-    if !query.is_empty() {
-        for (query_param, ty) in &query {
-            let ty = ty.ts(db);
-            tracing::debug!(?query_param, ?ty);
+        _ => {
+            report(
+                db,
+                pointer,
+                "map keys must be strings, numbers, or enumerations, assuming string",
+            );
+            string
         }
     }
+}
 
-    let mut response = None;
+fn ty_by_name(db: &dyn crate::Db, api: InputApi, name: String) -> Type {
+    let pointer = name.trim_start_matches('#').to_string();
+    shallow_schema_ty(
+        db,
+        api,
+        &oapi::ReferenceOr::Reference { reference: name },
+        pointer,
+    )
+}
 
-    for (status, res) in &operation.responses.responses {
-        response = match res {
-            oapi::ReferenceOr::Reference { .. } => todo!(),
-            oapi::ReferenceOr::Item(response) => {
-                for (media_type, value) in &response.content {
-                    if let Some(schema) = &value.schema {
-                        let ty = simplify_ty(db, shallow_schema_ty(db, api, schema)).ts(db);
-                        tracing::debug!(?status, ?media_type, ?ty, "response");
-                    }
-                }
+/// A construct of the document which was left out of the output, or which
+/// isn't supported and was typed as `unknown` instead.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// The JSON pointer of the construct in the document.
+    pub pointer: String,
+    pub message: String,
+}
 
-                assert_eq!(response.content.len(), 1);
+/// What became of the construct a [`Diagnostic`] is about.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum DiagnosticKind {
+    /// The construct isn't supported, and was left out of the output.
+    Skipped,
+    /// The construct was left out on purpose, by an override in the
+    /// configuration or the naming of a component schema.
+    Excluded,
+    /// The construct isn't supported, and was typed loosely or ignored.
+    Unsupported,
+    /// The construct contradicts another part of the document, which the
+    /// output follows as documented.
+    Inconsistent,
+}
 
-                let (media_type, value) = response.content.iter().next().unwrap();
-                let ty = if let Some(schema) = &value.schema {
-                    let ty = simplify_ty(db, shallow_schema_ty(db, api, schema));
-                    let ts = ty.ts(db);
-                    tracing::debug!(?status, ?media_type, ty=?ts, "response");
-                    ty
-                } else {
-                    todo!()
-                };
-                match media_type.as_str() {
-                    "text/plain" | "text/plain; charset=utf-8" => {
-                        assert_eq!(ty, Type::new(db, TypeKind::String));
-                        Some(ResponseKind::Plain)
-                    }
-                    "application/json" => Some(ResponseKind::Json(ty)),
-                    "text/event-stream" => Some(ResponseKind::EventStream(ty)),
-                    _ => todo!("unhandled request media type: {media_type:?}"),
-                }
-            }
-        };
+impl DiagnosticKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiagnosticKind::Skipped => "skipped",
+            DiagnosticKind::Excluded => "excluded",
+            DiagnosticKind::Unsupported => "unsupported",
+            DiagnosticKind::Inconsistent => "inconsistent",
+        }
     }
+}
 
-    Operation {
-        path,
-        query,
-        path_params,
-        body,
-        response,
-    }
+#[salsa::accumulator]
+pub struct Diagnostics(Diagnostic);
+
+/// Records a [`DiagnosticKind::Unsupported`] diagnostic for the query being
+/// computed.
+fn report(db: &dyn crate::Db, pointer: impl Into<String>, message: impl Into<String>) {
+    push_diagnostic(db, DiagnosticKind::Unsupported, pointer, message);
 }
 
-#[salsa::tracked]
-fn schema_by_name(db: &dyn crate::Db, api: InputApi, name: String) -> Option<Schema> {
-    tracing::debug!(?name, "schema_by_name");
+/// Records a [`DiagnosticKind::Skipped`] diagnostic for the query being
+/// computed.
+fn report_skipped(db: &dyn crate::Db, pointer: impl Into<String>, message: impl Into<String>) {
+    push_diagnostic(db, DiagnosticKind::Skipped, pointer, message);
+}
 
-    if let Some(name) = name.strip_prefix("#/components/schemas/") {
-        schema_by_name(db, api, name.to_string())
-    } else {
-        match api.api(db).components.as_ref()?.schemas.get(&name)? {
-            oapi::ReferenceOr::Reference { reference } => {
-                todo!("reference to: {reference}")
-            }
-            oapi::ReferenceOr::Item(schema) => Some(Schema::from_oapi(db, schema.clone())),
-        }
-    }
+/// Records a [`DiagnosticKind::Excluded`] diagnostic for the query being
+/// computed.
+fn report_excluded(db: &dyn crate::Db, pointer: impl Into<String>, message: impl Into<String>) {
+    push_diagnostic(db, DiagnosticKind::Excluded, pointer, message);
 }
 
-#[salsa::tracked]
-fn schema_ty(db: &dyn crate::Db, api: InputApi, schema: Schema) -> Type {
-    match schema.kind(db) {
-        oapi::SchemaKind::Type(ty) => match ty {
-            oapi::Type::String(str) => {
-                if str.enumeration.is_empty() {
-                    Type::new(db, TypeKind::String)
+/// Records a [`DiagnosticKind::Inconsistent`] diagnostic for the query being
+/// computed.
+fn report_inconsistent(db: &dyn crate::Db, pointer: impl Into<String>, message: impl Into<String>) {
+    push_diagnostic(db, DiagnosticKind::Inconsistent, pointer, message);
+}
+
+fn push_diagnostic(
+    db: &dyn crate::Db,
+    kind: DiagnosticKind,
+    pointer: impl Into<String>,
+    message: impl Into<String>,
+) {
+    Diagnostics::push(
+        db,
+        Diagnostic {
+            kind,
+            pointer: pointer.into(),
+            message: message.into(),
+        },
+    );
+}
+
+/// Escapes a reference token of a JSON pointer.
+fn escape_pointer(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Lowers every operation and component type, collecting the diagnostics
+/// reported while doing so.
+#[salsa::tracked]
+fn lower_all(db: &dyn crate::Db, api: InputApi) {
+    lower_api(db, api);
+    component_types(db, api);
+    check_route_enums(db, api);
+}
+
+/// Reports enumerations used as path parameters whose values don't match the
+/// paths written out per value alongside them, such as `/reports/{kind}`
+/// next to `/reports/daily` and `/reports/weekly`, where `kind` is one of
+/// `daily`, `weekly`, or `monthly`. Only paths of the same method which
+/// differ in just that segment are compared, and parameters without any such
+/// paths aren't checked.
+#[salsa::tracked]
+fn check_route_enums(db: &dyn crate::Db, api: InputApi) {
+    let operations = lower_api(db, api);
+    for op in &operations {
+        let segments = op.path.split('/').collect_vec();
+        for (index, segment) in segments.iter().enumerate() {
+            let Some(name) = segment
+                .strip_prefix('{')
+                .and_then(|segment| segment.strip_suffix('}'))
+            else {
+                continue;
+            };
+            let Some(values) = op
+                .path_params
+                .get(name)
+                .and_then(|ty| ty.resolve(db, api).enum_values(db))
+            else {
+                continue;
+            };
+
+            let routes = operations
+                .iter()
+                .filter(|other| other.method == op.method)
+                .filter_map(|other| {
+                    let other_segments = other.path.split('/').collect_vec();
+                    if other_segments.len() != segments.len() {
+                        return None;
+                    }
+                    let sibling = other_segments
+                        .iter()
+                        .zip(&segments)
+                        .enumerate()
+                        .all(|(i, (a, b))| i == index || a == b);
+                    let literal = other_segments[index];
+                    (sibling && !literal.starts_with('{')).then_some(literal.to_string())
+                })
+                .collect::<BTreeSet<_>>();
+            if routes.is_empty() {
+                continue;
+            }
+
+            let pointer = format!(
+                "/paths/{}/{}",
+                escape_pointer(&op.path),
+                op.method.to_ascii_lowercase()
+            );
+            let route = |value: &str| {
+                let path = segments
+                    .iter()
+                    .enumerate()
+                    .map(|(i, segment)| if i == index { value } else { *segment })
+                    .join("/");
+                format!("{} {path}", op.method)
+            };
+            for value in values.difference(&routes) {
+                report_inconsistent(
+                    db,
+                    &pointer,
+                    format!(
+                        "path parameter {name:?} can be {value:?}, but there is no `{}` like the other values",
+                        route(value)
+                    ),
+                );
+            }
+            for value in routes.difference(&values) {
+                report_inconsistent(
+                    db,
+                    &pointer,
+                    format!(
+                        "`{}` is written out like the values of path parameter {name:?}, but {value:?} isn't one of them",
+                        route(value)
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Every construct of the document which was left out of the output, or
+/// which is unsupported and was typed as `unknown` instead, in the order they
+/// were encountered.
+///
+/// This is what's missing from the generated clients, and is printed as a
+/// table by the CLI.
+pub fn diagnostics(db: &dyn crate::Db, api: InputApi) -> Vec<Diagnostic> {
+    lower_all::accumulated::<Diagnostics>(db, api)
+}
+
+/// The operations skipped for being unsupported, as `METHOD /path` (or just
+/// the path, when the whole path item was skipped) with the diagnostic
+/// explaining why.
+pub fn skipped_operations(db: &dyn crate::Db, api: InputApi) -> Vec<(String, Diagnostic)> {
+    let unescape = |token: &str| token.replace("~1", "/").replace("~0", "~");
+    diagnostics(db, api)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.kind == DiagnosticKind::Skipped)
+        .filter_map(|diagnostic| {
+            let mut tokens = diagnostic.pointer.strip_prefix("/paths/")?.split('/');
+            let path = unescape(tokens.next()?);
+            let operation = match tokens.next() {
+                Some(method) => format!("{} {path}", method.to_ascii_uppercase()),
+                None => path,
+            };
+            Some((operation, diagnostic))
+        })
+        .collect()
+}
+
+/// Lowers every operation of every path in the document, in the order they
+/// appear.
+///
+/// This is the input to every target, and can be used by tools wanting to
+/// inspect the API without generating code.
+#[salsa::tracked]
+pub fn lower_api(db: &dyn crate::Db, api: InputApi) -> Vec<Operation> {
+    let config = api.config(db);
+
+    api.api(db)
+        .paths
+        .paths
+        .iter()
+        .flat_map(|(path, item)| match item {
+            oapi::ReferenceOr::Reference { reference } => {
+                report_skipped(
+                    db,
+                    format!("/paths/{}", escape_pointer(path)),
+                    format!("references to path items are not supported, skipping {reference:?}"),
+                );
+                Vec::new()
+            }
+            oapi::ReferenceOr::Item(path_item) => {
+                let span = tracing::debug_span!("endpoint", path);
+                let _enter = span.enter();
+
+                let gen_op = |method: &'static str, op: &Option<oapi::Operation>| {
+                    let op = op.as_ref()?;
+                    if config.operation_override(method, path).skip {
+                        report_excluded(
+                            db,
+                            format!(
+                                "/paths/{}/{}",
+                                escape_pointer(path),
+                                method.to_ascii_lowercase()
+                            ),
+                            "skipped by an override in the configuration",
+                        );
+                        return None;
+                    }
+                    operation(db, api, method, path.clone(), path_item, op)
+                };
+                [
+                    gen_op("DELETE", &path_item.delete),
+                    gen_op("GET", &path_item.get),
+                    gen_op("PUT", &path_item.put),
+                    gen_op("POST", &path_item.post),
+                    gen_op("HEAD", &path_item.head),
+                    gen_op("TRACE", &path_item.trace),
+                    gen_op("PATCH", &path_item.patch),
+                ]
+                .into_iter()
+                .flatten()
+                .collect_vec()
+            }
+        })
+        .collect()
+}
+
+/// The location and name identifying a parameter among those of an operation.
+fn parameter_key(param: &oapi::Parameter) -> (&'static str, &str) {
+    match param {
+        oapi::Parameter::Query { parameter_data, .. } => ("query", &parameter_data.name),
+        oapi::Parameter::Header { parameter_data, .. } => ("header", &parameter_data.name),
+        oapi::Parameter::Path { parameter_data, .. } => ("path", &parameter_data.name),
+        oapi::Parameter::Cookie { parameter_data, .. } => ("cookie", &parameter_data.name),
+    }
+}
+
+/// Resolves a reference to a component, such as
+/// `#/components/parameters/Limit`, by looking up its name among the
+/// components of `section` with `lookup`. Components which are themselves
+/// references are followed. Unresolved references, and references to other
+/// sections, are reported at `pointer`.
+fn resolve_component<'a, T>(
+    db: &dyn crate::Db,
+    item: &'a oapi::ReferenceOr<T>,
+    section: &str,
+    lookup: impl Fn(&str) -> Option<&'a oapi::ReferenceOr<T>>,
+    pointer: &str,
+) -> Option<&'a T> {
+    let prefix = format!("#/components/{section}/");
+    let mut item = item;
+    // NOTE: The number of references followed is bounded, so cycles end.
+    for _ in 0..32 {
+        let reference = match item {
+            oapi::ReferenceOr::Item(item) => return Some(item),
+            oapi::ReferenceOr::Reference { reference } => reference,
+        };
+        let Some(name) = reference.strip_prefix(&prefix) else {
+            report(
+                db,
+                pointer,
+                format!("only components in {section:?} can be referenced here, not {reference:?}"),
+            );
+            return None;
+        };
+        match lookup(&name.replace("~1", "/").replace("~0", "~")) {
+            Some(resolved) => item = resolved,
+            None => {
+                report(db, pointer, format!("unresolved reference {reference:?}"));
+                return None;
+            }
+        }
+    }
+    report(
+        db,
+        pointer,
+        "references nested too deeply, possibly a cycle",
+    );
+    None
+}
+
+fn operation(
+    db: &dyn crate::Db,
+    api: InputApi,
+    method: &'static str,
+    path: String,
+    path_item: &oapi::PathItem,
+    operation: &oapi::Operation,
+) -> Option<Operation> {
+    let overrides = api.config(db).operation_override(method, &path);
+    let components = api.api(db).components.as_ref();
+    let op_pointer = format!(
+        "/paths/{}/{}",
+        escape_pointer(&path),
+        method.to_ascii_lowercase()
+    );
+
+    let mut path_params = BTreeMap::new();
+    let mut query = BTreeMap::new();
+    let mut headers = BTreeMap::new();
+    let mut if_match = false;
+
+    let path_pointer = format!("/paths/{}", escape_pointer(&path));
+    let mut resolved = Vec::new();
+    for (base, parameters) in [
+        (&path_pointer, &path_item.parameters),
+        (&op_pointer, &operation.parameters),
+    ] {
+        for (i, param) in parameters.iter().enumerate() {
+            let pointer = format!("{base}/parameters/{i}");
+            if let Some(param) = resolve_component(
+                db,
+                param,
+                "parameters",
+                |name| components?.parameters.get(name),
+                &pointer,
+            ) {
+                resolved.push((base == &op_pointer, pointer, param));
+            }
+        }
+    }
+    // NOTE: Parameters of the path item are shared by all of its operations,
+    // unless the operation has its own with the same name and location.
+    let parameters = resolved.iter().filter(|(own, _, param)| {
+        *own || !resolved
+            .iter()
+            .any(|(other, _, op_param)| *other && parameter_key(op_param) == parameter_key(param))
+    });
+    for (_, pointer, param) in parameters {
+        let param_ty = |format: &oapi::ParameterSchemaOrContent| match format {
+            oapi::ParameterSchemaOrContent::Schema(schema) => {
+                shallow_schema_ty(db, api, schema, format!("{pointer}/schema"))
+            }
+            oapi::ParameterSchemaOrContent::Content(_) => {
+                report(
+                    db,
+                    format!("{pointer}/content"),
+                    "parameters with content are not supported, typing as unknown",
+                );
+                Type::unknown(db)
+            }
+        };
+        match param {
+            oapi::Parameter::Query {
+                parameter_data,
+                style,
+                ..
+            } => {
+                let ty = param_ty(&parameter_data.format);
+                let style = match style {
+                    oapi::QueryStyle::Form => QueryStyle::Form,
+                    oapi::QueryStyle::SpaceDelimited => QueryStyle::SpaceDelimited,
+                    oapi::QueryStyle::PipeDelimited => QueryStyle::PipeDelimited,
+                    oapi::QueryStyle::DeepObject => QueryStyle::DeepObject,
+                };
+                let default = match &parameter_data.format {
+                    oapi::ParameterSchemaOrContent::Schema(schema) => {
+                        resolve_schema(db, api, schema, format!("{pointer}/schema"))
+                            .and_then(|schema| schema.data(db).default.clone())
+                    }
+                    oapi::ParameterSchemaOrContent::Content(_) => None,
+                };
+
+                query.insert(
+                    parameter_data.name.clone(),
+                    QueryParam {
+                        ty,
+                        default,
+                        required: parameter_data.required,
+                        style,
+                        explode: parameter_data.explode.unwrap_or(style == QueryStyle::Form),
+                    },
+                );
+            }
+            oapi::Parameter::Header { parameter_data, .. }
+                if parameter_data.name.eq_ignore_ascii_case("If-Match") =>
+            {
+                if_match = true;
+            }
+            // NOTE: These are described by the request body, the responses,
+            // and the security schemes, so their parameters are ignored.
+            oapi::Parameter::Header { parameter_data, .. }
+                if ["Accept", "Content-Type", "Authorization"]
+                    .iter()
+                    .any(|name| parameter_data.name.eq_ignore_ascii_case(name)) => {}
+            oapi::Parameter::Header { parameter_data, .. } => {
+                let ty = param_ty(&parameter_data.format);
+                headers.insert(parameter_data.name.clone(), ty);
+            }
+            oapi::Parameter::Path { parameter_data, .. } => {
+                let ty = param_ty(&parameter_data.format);
+
+                let name = overrides
+                    .path_params
+                    .get(&parameter_data.name)
+                    .unwrap_or(&parameter_data.name);
+                path_params.insert(name.clone(), ty);
+            }
+            oapi::Parameter::Cookie { parameter_data, .. } => report(
+                db,
+                pointer,
+                format!(
+                    "cookie parameters are not supported, leaving out {:?}",
+                    parameter_data.name
+                ),
+            ),
+        }
+    }
+    let media_types = api.config(db).media_types;
+
+    let mut request_media_type = None;
+    let body = if let Some(body) = &operation.request_body {
+        let pointer = format!("{op_pointer}/requestBody");
+        let body = resolve_component(
+            db,
+            body,
+            "requestBodies",
+            |name| components?.request_bodies.get(name),
+            &pointer,
+        )?;
+
+        // NOTE: JSON is preferred when several media types are documented.
+        let content = match body.content.get_key_value("application/json") {
+            Some(content) => Some(content),
+            None if body.content.len() == 1 => body.content.iter().next(),
+            None => None,
+        };
+        let Some((media_type, value)) = content else {
+            report_skipped(
+                db,
+                format!("{pointer}/content"),
+                "request bodies must have exactly one media type, or JSON among them, skipping operation",
+            );
+            return None;
+        };
+        if !request_media_type_supported(&api.config(db), media_type) {
+            report_skipped(
+                db,
+                format!("{pointer}/content/{}", escape_pointer(media_type)),
+                format!("unsupported request media type {media_type:?}, skipping operation"),
+            );
+            return None;
+        }
+        let ty = if let Some(schema) = &value.schema {
+            let schema_pointer = format!("{pointer}/content/{}/schema", escape_pointer(media_type));
+            let ty = if media_type == "multipart/form-data" {
+                multipart_ty(db, api, schema, schema_pointer)
+            } else {
+                let ty = shallow_schema_ty(db, api, schema, schema_pointer.clone());
+                simplify_ty(db, api, ty, schema_pointer)
+            };
+            let ts = ty.ts(db);
+            tracing::debug!(?media_type, ty=?ts, "request");
+            ty
+        } else {
+            report(
+                db,
+                format!("{pointer}/content/{}", escape_pointer(media_type)),
+                "request body without a schema, typing as unknown",
+            );
+            Type::unknown(db)
+        };
+        request_media_type = Some(media_type.clone());
+        match media_type.as_str() {
+            "application/json" => Some(RequestKind::Json(ty)),
+            "multipart/form-data" => Some(RequestKind::Multipart(ty)),
+            "application/x-www-form-urlencoded" => Some(RequestKind::Form(ty)),
+            _ if media_types.contains_key(media_type) => Some(RequestKind::Custom(ty)),
+            _ => unreachable!("unsupported request media type: {media_type:?}"),
+        }
+    } else {
+        None
+    };
+
+    let placeholders = path_placeholders(&path);
+    for placeholder in &placeholders {
+        if !path_params.contains_key(*placeholder) {
+            report(
+                db,
+                &op_pointer,
+                format!("path template placeholder {placeholder:?} has no matching parameter, assuming string"),
+            );
+            path_params.insert(placeholder.to_string(), Type::new(db, TypeKind::String));
+        }
+    }
+    path_params.retain(|name, _| {
+        let used = placeholders.contains(&name.as_str());
+        if !used {
+            report(
+                db,
+                &op_pointer,
+                format!(
+                    "path parameter {name:?} does not appear in the path template, ignoring it"
+                ),
+            );
+        }
+        used
+    });
+
+    if !path_params.is_empty() {
+        for (path_param, ty) in &path_params {
+            let ty = ty.ts(db);
+            tracing::debug!(?path_param, ?ty);
+        }
+    }
+    if !query.is_empty() {
+        for (query_param, param) in &query {
+            let ty = param.ty.ts(db);
+            tracing::debug!(?query_param, ?ty);
+        }
+    }
+
+    let rate_limited = operation
+        .responses
+        .responses
+        .iter()
+        .any(|(status, res)| match res {
+            _ if *status == oapi::StatusCode::Code(429) => true,
+            oapi::ReferenceOr::Reference { .. } => false,
+            oapi::ReferenceOr::Item(response) => response
+                .headers
+                .keys()
+                .any(|name| is_rate_limit_header(name)),
+        });
+
+    // NOTE: Failed preconditions and rate limiting are reported as errors by
+    // the client, so their bodies must not be taken for a response.
+    let documented = operation
+        .responses
+        .responses
+        .iter()
+        .filter(|(status, _)| {
+            !(if_match && **status == oapi::StatusCode::Code(412))
+                && **status != oapi::StatusCode::Code(429)
+        })
+        .map(|(status, res)| (status.to_string(), res))
+        .chain(
+            operation
+                .responses
+                .default
+                .iter()
+                .map(|res| ("default".to_string(), res)),
+        )
+        .collect_vec();
+    // NOTE: The first successful response is the one resolved by the client,
+    // falling back to the first redirect and then the `default` response.
+    let primary = documented
+        .iter()
+        .position(|(status, _)| status.starts_with('2'))
+        .or_else(|| {
+            documented
+                .iter()
+                .position(|(status, _)| status.starts_with('3'))
+        })
+        .or_else(|| {
+            documented
+                .iter()
+                .position(|(status, _)| status == "default")
+        });
+
+    let mut response = None;
+    let mut response_media_type = None;
+    let mut responses = Vec::new();
+    for (index, (status, res)) in documented.iter().enumerate() {
+        let pointer = format!("{op_pointer}/responses/{status}");
+        let Some(res) = resolve_component(
+            db,
+            res,
+            "responses",
+            |name| components?.responses.get(name),
+            &pointer,
+        ) else {
+            continue;
+        };
+
+        let is_primary = primary == Some(index);
+        match lower_response(db, api, &overrides, &pointer, status, res, is_primary) {
+            Ok((kind, media_type)) => {
+                tracing::debug!(%status, ?kind, "response");
+                if is_primary {
+                    response = Some(kind);
+                    response_media_type = media_type;
+                }
+                responses.push((status.clone(), kind));
+            }
+            Err((pointer, message)) if is_primary => {
+                report_skipped(db, pointer, format!("{message}, skipping operation"));
+                return None;
+            }
+            Err((pointer, message)) => {
+                report(db, pointer, format!("{message}, leaving the response out"));
+            }
+        }
+    }
+
+    let response = match response {
+        Some(response) => response,
+        None => {
+            report(
+                db,
+                format!("{op_pointer}/responses"),
+                "operation without responses, assuming an empty response",
+            );
+            ResponseKind::Empty
+        }
+    };
+    if matches!(
+        body,
+        Some(RequestKind::Custom(_) | RequestKind::Multipart(_) | RequestKind::Form(_))
+    ) && matches!(
+        response,
+        ResponseKind::EventStream(_) | ResponseKind::Csv(_) | ResponseKind::Redirect
+    ) {
+        report_skipped(
+            db,
+            format!("{op_pointer}/requestBody"),
+            "event stream, CSV, and redirect responses are not supported with a custom request media type, skipping operation",
+        );
+        return None;
+    }
+
+    Some(Operation {
+        method,
+        path,
+        query,
+        path_params,
+        headers,
+        body,
+        response: Some(response),
+        responses,
+        request_media_type,
+        response_media_type,
+        if_match,
+        rate_limited,
+        operation_id: operation.operation_id.clone(),
+        summary: operation.summary.clone(),
+        description: operation.description.clone(),
+        tags: operation.tags.clone(),
+        deprecated: operation.deprecated,
+        servers: match &operation.servers {
+            servers if !servers.is_empty() => servers,
+            _ => &path_item.servers,
+        }
+        .iter()
+        .map(|server| server.url.clone())
+        .collect(),
+        extensions: operation
+            .extensions
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+    })
+}
+
+/// Lowers a single documented response, along with its media type if it has
+/// any content. The [`OperationOverride::response_schema`] only applies to
+/// the `primary` response, which the client resolves with. Redirects
+/// documenting a `Location` header are lowered as [`ResponseKind::Redirect`]
+/// regardless of their content.
+///
+/// Unsupported responses are returned as the pointer and description of the
+/// problem, to be reported by the caller.
+fn lower_response(
+    db: &dyn crate::Db,
+    api: InputApi,
+    overrides: &OperationOverride,
+    pointer: &str,
+    status: &str,
+    response: &oapi::Response,
+    primary: bool,
+) -> Result<(ResponseKind, Option<String>), (String, String)> {
+    if status.starts_with('3')
+        && response
+            .headers
+            .keys()
+            .any(|name| name.eq_ignore_ascii_case("location"))
+    {
+        return Ok((ResponseKind::Redirect, None));
+    }
+    if response.content.is_empty() {
+        return Ok((ResponseKind::Empty, None));
+    }
+
+    let content = match overrides
+        .response_media_type
+        .as_ref()
+        .and_then(|media_type| response.content.get_key_value(media_type))
+    {
+        Some(content) => Some(content),
+        // NOTE: JSON is preferred when several media types are documented,
+        // such as JSON alongside XML.
+        None => match response.content.get_key_value("application/json") {
+            Some(content) => Some(content),
+            None if response.content.len() == 1 => response.content.iter().next(),
+            None => None,
+        },
+    };
+    let Some((media_type, value)) = content else {
+        return Err((
+            format!("{pointer}/content"),
+            "responses must have exactly one media type, or JSON among them, unless one is configured".to_string(),
+        ));
+    };
+    let content_pointer = format!("{pointer}/content/{}", escape_pointer(media_type));
+    let config = api.config(db);
+    if !response_media_type_supported(&config, media_type) {
+        return Err((
+            content_pointer,
+            format!("unsupported response media type {media_type:?}"),
+        ));
+    }
+    let ty = match (&overrides.response_schema, &value.schema) {
+        (Some(name), _) if primary => ty_by_name(db, api, format!("#/components/schemas/{name}")),
+        (_, Some(schema)) => {
+            let schema_pointer = format!("{content_pointer}/schema");
+            let ty = shallow_schema_ty(db, api, schema, schema_pointer.clone());
+            simplify_ty(db, api, ty, schema_pointer)
+        }
+        (_, None) => {
+            report(
+                db,
+                &content_pointer,
+                "response without a schema, typing as unknown",
+            );
+            Type::unknown(db)
+        }
+    };
+
+    let kind = match media_type.as_str() {
+        "text/plain" | "text/plain; charset=utf-8" => {
+            // NOTE: Missing schemas are reported above already.
+            if !matches!(ty.kind(db), TypeKind::String | TypeKind::Unknown) {
+                report(
+                    db,
+                    content_pointer,
+                    format!(
+                        "plain text response typed as {}, resolving as a string",
+                        ty.to_typescript(db)
+                    ),
+                );
+            }
+            ResponseKind::Plain
+        }
+        "application/json" if ty.is_empty_object(db, api) => ResponseKind::Empty,
+        "application/json" => ResponseKind::Json(ty),
+        "text/event-stream" => ResponseKind::EventStream(ty),
+        "text/csv" | "text/csv; charset=utf-8" => {
+            // NOTE: The row type is given either by the extension, or by
+            // documenting the response as an array of rows.
+            let row = match value
+                .extensions
+                .get("x-abeye-csv-row")
+                .and_then(serde_json::Value::as_str)
+            {
+                Some(name) => Some(ty_by_name(db, api, format!("#/components/schemas/{name}"))),
+                None => match ty.resolve(db, api).kind(db) {
+                    TypeKind::Array(row) => Some(row),
+                    _ => None,
+                },
+            };
+            match row {
+                Some(row) => ResponseKind::Csv(row),
+                None => {
+                    tracing::debug!(pointer, "CSV response without rows, using text");
+                    ResponseKind::Plain
+                }
+            }
+        }
+        _ if config.media_types.contains_key(media_type) => ResponseKind::Custom(ty),
+        "application/xml" | "text/xml" => ResponseKind::Xml,
+        "application/octet-stream" | "application/pdf" => ResponseKind::Blob,
+        _ if media_type.starts_with("image/")
+            || media_type.starts_with("audio/")
+            || media_type.starts_with("video/") =>
+        {
+            ResponseKind::Blob
+        }
+        _ if media_type.ends_with("+xml") => ResponseKind::Xml,
+        _ => unreachable!("unsupported response media type: {media_type:?}"),
+    };
+    Ok((kind, Some(media_type.clone())))
+}
+
+/// Whether the response header communicates rate limiting.
+fn is_rate_limit_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name == "retry-after"
+        || ["ratelimit", "x-ratelimit", "x-rate-limit"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+/// Whether request bodies of the media type can be lowered.
+fn request_media_type_supported(config: &Config, media_type: &str) -> bool {
+    matches!(
+        media_type,
+        "application/json" | "multipart/form-data" | "application/x-www-form-urlencoded"
+    ) || config.media_types.contains_key(media_type)
+}
+
+/// The type of a `multipart/form-data` request body. Properties of the body
+/// which are strings of `format: binary`, or arrays of them, are typed as
+/// files rather than strings, in which case a referenced component schema is
+/// inlined.
+fn multipart_ty(
+    db: &dyn crate::Db,
+    api: InputApi,
+    schema: &oapi::ReferenceOr<oapi::Schema>,
+    pointer: String,
+) -> Type {
+    let ty = simplify_ty(
+        db,
+        api,
+        shallow_schema_ty(db, api, schema, pointer.clone()),
+        pointer.clone(),
+    );
+    let Some(schema) = resolve_schema(db, api, schema, pointer) else {
+        return ty;
+    };
+    let oapi::SchemaKind::Type(oapi::Type::Object(obj)) = schema.kind(db) else {
+        return ty;
+    };
+    let TypeKind::Object(mut properties) = ty.resolve(db, api).kind(db) else {
+        return ty;
+    };
+
+    let file = Type::new(db, TypeKind::Custom("File | Blob".to_string()));
+    let is_file = |schema: &oapi::ReferenceOr<oapi::Schema>| match schema {
+        oapi::ReferenceOr::Item(schema) => matches!(
+            &schema.schema_kind,
+            oapi::SchemaKind::Type(oapi::Type::String(str))
+                if format_name(&str.format).as_deref() == Some("binary")
+        ),
+        oapi::ReferenceOr::Reference { .. } => false,
+    };
+    let mut has_files = false;
+    for (name, prop) in &obj.properties {
+        let prop_schema = prop.clone().unbox();
+        let file_ty = match &prop_schema {
+            _ if is_file(&prop_schema) => file,
+            oapi::ReferenceOr::Item(oapi::Schema {
+                schema_kind: oapi::SchemaKind::Type(oapi::Type::Array(array)),
+                ..
+            }) if array
+                .items
+                .as_ref()
+                .is_some_and(|items| is_file(&items.clone().unbox())) =>
+            {
+                Type::new(db, TypeKind::Array(file))
+            }
+            _ => continue,
+        };
+        if let Some(property) = properties.get_mut(name) {
+            property.ty = file_ty;
+            has_files = true;
+        }
+    }
+
+    if has_files {
+        Type::new(db, TypeKind::Object(properties))
+    } else {
+        ty
+    }
+}
+
+/// Whether responses of the media type can be lowered.
+fn response_media_type_supported(config: &Config, media_type: &str) -> bool {
+    matches!(
+        media_type,
+        "text/plain"
+            | "text/plain; charset=utf-8"
+            | "application/json"
+            | "text/event-stream"
+            | "text/csv"
+            | "text/csv; charset=utf-8"
+            | "application/xml"
+            | "text/xml"
+            | "application/octet-stream"
+            | "application/pdf"
+    ) || ["image/", "audio/", "video/"]
+        .iter()
+        .any(|prefix| media_type.starts_with(prefix))
+        || media_type.ends_with("+xml")
+        || config.media_types.contains_key(media_type)
+}
+
+/// The names of the `{name}` placeholders in a path template.
+fn path_placeholders(path: &str) -> Vec<&str> {
+    path.split('{')
+        .skip(1)
+        .filter_map(|rest| Some(&rest[..rest.find('}')?]))
+        .collect()
+}
+
+impl Operation {
+    /// Every type appearing in the parameters, body, or responses.
+    pub fn types(&self) -> Vec<Type> {
+        let body = self.body.map(RequestKind::ty);
+        // NOTE: The primary response is among the documented ones.
+        let responses = self.responses.iter().filter_map(|(_, res)| match *res {
+            ResponseKind::Empty
+            | ResponseKind::Plain
+            | ResponseKind::Xml
+            | ResponseKind::Blob
+            | ResponseKind::Redirect => None,
+            ResponseKind::Json(ty)
+            | ResponseKind::EventStream(ty)
+            | ResponseKind::Csv(ty)
+            | ResponseKind::Custom(ty) => Some(ty),
+        });
+
+        self.path_params
+            .values()
+            .copied()
+            .chain(self.query.values().map(|param| param.ty))
+            .chain(body)
+            .chain(responses)
+            .collect()
+    }
+
+    /// The uppercase method and the path template, for example
+    /// `"GET /users/{id}"`. Overrides and lock files are keyed by this.
+    pub fn key(&self) -> String {
+        format!("{} {}", self.method, self.path)
+    }
+
+    /// The name of the generated method, derived from the `operationId` or
+    /// the path with the configured API prefix removed, depending on the
+    /// [`NameStrategy`]. Names derived from a path shared with other
+    /// operations are prefixed with the method, such as `getUsersId` and
+    /// `deleteUsersId`.
+    pub fn name(&self, db: &dyn crate::Db, api: InputApi) -> String {
+        let config = api.config(db);
+        if let Some(name) = config.operation_override(self.method, &self.path).rename {
+            return name;
+        }
+        if let (NameStrategy::OperationId, Some(id)) = (config.name_strategy, &self.operation_id) {
+            return id.to_lower_camel_case();
+        }
+
+        let path = Utf8PathBuf::from(&self.path);
+        let name = if let Some(prefix) = config.api_prefix {
+            path.strip_prefix(prefix).unwrap()
+        } else {
+            &path
+        }
+        .components()
+        .join("_");
+        if self.shares_path(db, api) {
+            format!("{}_{name}", self.method).to_lower_camel_case()
+        } else {
+            name.to_lower_camel_case()
+        }
+    }
+
+    /// Whether other operations, which aren't skipped, are defined on the
+    /// path of the operation.
+    fn shares_path(&self, db: &dyn crate::Db, api: InputApi) -> bool {
+        let config = api.config(db);
+        let Some(oapi::ReferenceOr::Item(item)) = api.api(db).paths.paths.get(&self.path) else {
+            return false;
+        };
+        [
+            ("DELETE", &item.delete),
+            ("GET", &item.get),
+            ("PUT", &item.put),
+            ("POST", &item.post),
+            ("HEAD", &item.head),
+            ("TRACE", &item.trace),
+            ("PATCH", &item.patch),
+        ]
+        .into_iter()
+        .any(|(method, op)| {
+            method != self.method
+                && op.is_some()
+                && !config.operation_override(method, &self.path).skip
+        })
+    }
+}
+
+/// The generated method names shared by several operations, with the
+/// operations sharing each, such as `GET /users`. These are usually caused
+/// by renames in the configuration or duplicate `operationId`s.
+pub fn name_collisions(db: &dyn crate::Db, api: InputApi) -> Vec<(String, Vec<String>)> {
+    lower_api(db, api)
+        .iter()
+        .map(|op| (op.name(db, api), op.key()))
+        .into_group_map()
+        .into_iter()
+        .filter(|(_, keys)| keys.len() > 1)
+        .sorted()
+        .collect()
+}
+
+/// The component schemas which are emitted as named types, in the order they
+/// are declared. Schemas containing `_` are left out unless the document was
+/// passed through [`Transform::SanitizeSchemaNames`], as the CLI does.
+fn component_types(db: &dyn crate::Db, api: InputApi) -> Vec<(String, Type)> {
+    let Some(components) = api.api(db).components.as_ref() else {
+        return Vec::new();
+    };
+
+    components
+        .schemas
+        .keys()
+        .filter_map(|name| {
+            if let Some((generic, _)) = generic_instance(db, api, name) {
+                report_excluded(
+                    db,
+                    format!("/components/schemas/{}", escape_pointer(name)),
+                    format!("declared through the generic type {generic:?}"),
+                );
+                return None;
+            }
+            if name.contains('_') && !recursive_schema(db, api, name.clone()) {
+                report_excluded(
+                    db,
+                    format!("/components/schemas/{}", escape_pointer(name)),
+                    "names containing '_' are inlined where referenced rather than declared",
+                );
+                return None;
+            }
+
+            Some((name.clone(), lower_schema(db, api, name)?))
+        })
+        .collect()
+}
+
+/// Lowers the component schema with the given name to the type declared for
+/// it in the generated output, or `None` if there is no such schema.
+///
+/// Other component schemas are referenced by name, see [`TypeKind::Reference`].
+pub fn lower_schema(db: &dyn crate::Db, api: InputApi, name: &str) -> Option<Type> {
+    if let Some(custom) = api.config(db).schema_types.get(name) {
+        return Some(Type::new(db, TypeKind::Custom(custom.ts.clone())));
+    }
+
+    let schema = schema_by_name(db, api, name.to_string())?;
+    let pointer = format!("/components/schemas/{}", escape_pointer(name));
+    let ty = simplify_ty(db, api, schema_ty(db, api, schema), pointer.clone());
+    if ty.is_circular(db, api, name) {
+        report(
+            db,
+            pointer,
+            "schema is a union or intersection including itself, typing as unknown",
+        );
+        return Some(Type::unknown(db));
+    }
+    Some(match discriminator_tags(db, api).get(name) {
+        Some(tags) => narrow_discriminators(db, &pointer, ty, tags),
+        None => ty,
+    })
+}
+
+/// The description of a component schema, if any.
+fn schema_description(db: &dyn crate::Db, api: InputApi, name: &str) -> Option<String> {
+    schema_by_name(db, api, name.to_string())?
+        .data(db)
+        .description
+        .clone()
+}
+
+/// The values of a component schema written as a `oneOf` or `anyOf` of single
+/// value enums, with the schema of the option of each, for example
+/// `oneOf: [{ enum: [pending], description: Waiting to start }, ...]`.
+///
+/// Options which aren't a single string value are left out.
+fn constant_options(
+    db: &dyn crate::Db,
+    api: InputApi,
+    name: &str,
+) -> Vec<(String, oapi::SchemaData)> {
+    let Some(schema) = schema_by_name(db, api, name.to_string()) else {
+        return Vec::new();
+    };
+    let (oapi::SchemaKind::OneOf { one_of: options } | oapi::SchemaKind::AnyOf { any_of: options }) =
+        schema.kind(db)
+    else {
+        return Vec::new();
+    };
+
+    options
+        .iter()
+        .filter_map(|option| {
+            let option = match option {
+                oapi::ReferenceOr::Reference { reference } => {
+                    &schema_by_name(db, api, reference.clone())?
+                        .schema(db)
+                        .schema
+                }
+                oapi::ReferenceOr::Item(option) => option,
+            };
+            let value = match &option.schema_kind {
+                oapi::SchemaKind::Type(oapi::Type::String(str)) => match str.enumeration.as_slice()
+                {
+                    [Some(value)] => value.clone(),
+                    _ => return None,
+                },
+                oapi::SchemaKind::Any(any) => match any.enumeration.as_slice() {
+                    [serde_json::Value::String(value)] => value.clone(),
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            Some((value, option.schema_data.clone()))
+        })
+        .collect()
+}
+
+/// The descriptions of the values of a component schema, keyed by value, from
+/// the options of [`constant_options`] which have one.
+fn constant_descriptions(
+    db: &dyn crate::Db,
+    api: InputApi,
+    name: &str,
+) -> BTreeMap<String, String> {
+    constant_options(db, api, name)
+        .into_iter()
+        .filter_map(|(value, data)| Some((value, data.description?)))
+        .collect()
+}
+
+/// Human-readable labels of the values of a component schema, keyed by value,
+/// taken from the `x-display-name` extension of the option of each, or else
+/// its description.
+fn constant_labels(db: &dyn crate::Db, api: InputApi, name: &str) -> BTreeMap<String, String> {
+    constant_options(db, api, name)
+        .into_iter()
+        .filter_map(|(value, data)| {
+            let label = match data.extensions.get("x-display-name") {
+                Some(serde_json::Value::String(label)) => label.clone(),
+                _ => data.description?,
+            };
+            Some((value, label))
+        })
+        .collect()
+}
+
+/// The tags under which each component schema appears in the mapping of a
+/// discriminator, keyed by schema name and then discriminator property.
+#[salsa::tracked(return_ref)]
+fn discriminator_tags(
+    db: &dyn crate::Db,
+    api: InputApi,
+) -> BTreeMap<String, BTreeMap<String, BTreeSet<String>>> {
+    let mut tags = BTreeMap::<String, BTreeMap<String, BTreeSet<String>>>::new();
+    let Some(components) = api.api(db).components.as_ref() else {
+        return tags;
+    };
+
+    for schema in components.schemas.values() {
+        let oapi::ReferenceOr::Item(schema) = schema else {
+            continue;
+        };
+        let Some(disc) = &schema.schema_data.discriminator else {
+            continue;
+        };
+        for (tag, reference) in &disc.mapping {
+            let name = reference
+                .strip_prefix("#/components/schemas/")
+                .unwrap_or(reference);
+            tags.entry(name.to_string())
+                .or_default()
+                .entry(disc.property_name.clone())
+                .or_default()
+                .insert(tag.clone());
+        }
+    }
+
+    tags
+}
+
+/// Narrows the discriminator properties of a variant schema from `string` to
+/// the literal tags it is mapped from, so the variant type carries its tag no
+/// matter how it is referenced.
+///
+/// Only properties declared by the schema itself are narrowed, while those
+/// inherited through references in an `allOf` are left to their own schema.
+fn narrow_discriminators(
+    db: &dyn crate::Db,
+    pointer: &str,
+    ty: Type,
+    tags: &BTreeMap<String, BTreeSet<String>>,
+) -> Type {
+    match ty.kind(db) {
+        TypeKind::Object(mut obj) => {
+            for (property, values) in tags {
+                let Some(prop) = obj.get_mut(property) else {
+                    continue;
+                };
+                match prop.ty.kind(db) {
+                    TypeKind::String => {
+                        let literals = values
+                            .iter()
+                            .map(|value| Type::new(db, TypeKind::Ident(value.clone())))
+                            .collect_vec();
+                        prop.ty = match <[Type; 1]>::try_from(literals) {
+                            Ok([literal]) => literal,
+                            Err(literals) => Type::new(db, TypeKind::Or(literals)),
+                        };
+                    }
+                    TypeKind::Ident(value) if values.contains(&value) => {}
+                    _ => report_inconsistent(
+                        db,
+                        pointer,
+                        format!(
+                            "discriminator property {property:?} is typed as {} rather than a \
+                             string, leaving it as is",
+                            prop.ty.ts(db)
+                        ),
+                    ),
+                }
+            }
+            Type::new(db, TypeKind::Object(obj))
+        }
+        TypeKind::And(parts) => Type::new(
+            db,
+            TypeKind::And(
+                parts
+                    .into_iter()
+                    .map(|part| narrow_discriminators(db, pointer, part, tags))
+                    .collect(),
+            ),
+        ),
+        _ => ty,
+    }
+}
+
+/// 64-bit FNV-1a, which unlike the standard library hashers is stable across
+/// Rust versions and platforms.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The component schema with the given name or reference, following
+/// component schemas which are only a reference to another. `None` if there
+/// is no such schema, or if the references lead back to where they started.
+#[salsa::tracked]
+fn schema_by_name(db: &dyn crate::Db, api: InputApi, name: String) -> Option<Schema> {
+    tracing::debug!(?name, "schema_by_name");
+
+    let schemas = &api.api(db).components.as_ref()?.schemas;
+    let mut name = name;
+    let mut followed = BTreeSet::new();
+    loop {
+        if let Some(stripped) = name.strip_prefix("#/components/schemas/") {
+            name = stripped.to_string();
+        }
+        let pointer = format!("/components/schemas/{}", escape_pointer(&name));
+        if !followed.insert(name.clone()) {
+            report(
+                db,
+                pointer,
+                "schema is a cycle of references, typing as unknown",
+            );
+            return None;
+        }
+        match schemas.get(&name)? {
+            oapi::ReferenceOr::Reference { reference } => name = reference.clone(),
+            oapi::ReferenceOr::Item(schema) => {
+                return Some(Schema::from_oapi(db, schema.clone(), pointer))
+            }
+        }
+    }
+}
+
+/// The families of component schemas declared as a single generic type with
+/// [`Config::generic_schemas`], see [`generic_families`].
+#[salsa::tracked]
+fn schema_generics(
+    db: &dyn crate::Db,
+    api: InputApi,
+) -> BTreeMap<String, BTreeMap<String, String>> {
+    let Some(components) = api.api(db).components.as_ref() else {
+        return BTreeMap::new();
+    };
+    if !api.config(db).generic_schemas {
+        return BTreeMap::new();
+    }
+
+    match serde_json::to_value(&components.schemas) {
+        Ok(serde_json::Value::Object(schemas)) => generic_families(&schemas),
+        _ => BTreeMap::new(),
+    }
+}
+
+/// The generic type and type argument of the component schema with the given
+/// name, if it belongs to one of the [`schema_generics`].
+fn generic_instance(db: &dyn crate::Db, api: InputApi, name: &str) -> Option<(String, String)> {
+    schema_generics(db, api)
+        .into_iter()
+        .find_map(|(generic, instances)| Some((generic, instances.get(name)?.clone())))
+}
+
+/// The generic types of the [`schema_generics`] with the type declared for
+/// each, which is that of its first instance with the type argument replaced
+/// by the parameter `T`.
+pub(crate) fn generic_declarations(db: &dyn crate::Db, api: InputApi) -> Vec<(String, Type)> {
+    let parameter = Type::new(db, TypeKind::Parameter("T".to_string()));
+
+    schema_generics(db, api)
+        .into_iter()
+        .filter_map(|(generic, instances)| {
+            let (instance, argument) = instances.into_iter().next()?;
+            let argument = ty_by_name(db, api, format!("#/components/schemas/{argument}"));
+            let ty = lower_schema(db, api, &instance)?.substitute(db, argument, parameter);
+            Some((generic, ty))
+        })
+        .collect()
+}
+
+/// Whether the component schema with the given name, which contains `_` and
+/// would otherwise be inlined where referenced, references itself through
+/// other such schemas, as a tree node holding its children does. Inlining it
+/// would never end, so it is declared and referenced like any other
+/// component schema instead.
+#[salsa::tracked]
+fn recursive_schema(db: &dyn crate::Db, api: InputApi, name: String) -> bool {
+    let Some(components) = api.api(db).components.as_ref() else {
+        return false;
+    };
+
+    let mut inlined = BTreeSet::new();
+    let mut pending = vec![name.clone()];
+    while let Some(current) = pending.pop() {
+        let Some(schema) = components.schemas.get(&current) else {
+            continue;
+        };
+        let mut refs = Vec::new();
+        transform::collect_refs(&serde_json::to_value(schema).unwrap_or_default(), &mut refs);
+        for reference in refs {
+            let Some(target) = reference.strip_prefix("#/components/schemas/") else {
+                continue;
+            };
+            if target == name {
+                return true;
+            }
+            if target.contains('_') && inlined.insert(target.to_string()) {
+                pending.push(target.to_string());
+            }
+        }
+    }
+    false
+}
+
+#[salsa::tracked]
+fn schema_ty(db: &dyn crate::Db, api: InputApi, schema: Schema) -> Type {
+    let ty = non_nullable_schema_ty(db, api, schema);
+    if schema.data(db).nullable {
+        Type::new(db, TypeKind::Or(vec![ty, Type::new(db, TypeKind::Null)]))
+    } else {
+        ty
+    }
+}
+
+/// The type of the schema, disregarding whether it is `nullable`.
+fn non_nullable_schema_ty(db: &dyn crate::Db, api: InputApi, schema: Schema) -> Type {
+    let format = match schema.kind(db) {
+        oapi::SchemaKind::Type(oapi::Type::String(str)) => format_name(&str.format),
+        oapi::SchemaKind::Type(oapi::Type::Number(num)) => format_name(&num.format),
+        oapi::SchemaKind::Type(oapi::Type::Integer(int)) => format_name(&int.format),
+        _ => None,
+    };
+    if let Some(custom) =
+        format.and_then(|format| api.config(db).format_types.get(&format).cloned())
+    {
+        return Type::new(db, TypeKind::Custom(custom.ts));
+    }
+
+    let pointer = schema.pointer(db);
+    match schema.kind(db) {
+        oapi::SchemaKind::Type(ty) => match ty {
+            oapi::Type::String(str) => {
+                if str.enumeration.is_empty() {
+                    Type::new(db, TypeKind::String)
                 } else {
                     Type::new(
                         db,
                         TypeKind::Or(
                             str.enumeration
                                 .iter()
-                                .map(|e| Type::new(db, TypeKind::Ident(e.clone().unwrap())))
+                                .map(|e| match e {
+                                    Some(e) => Type::new(db, TypeKind::Ident(e.clone())),
+                                    None => Type::new(db, TypeKind::Null),
+                                })
                                 .collect(),
                         ),
                     )
@@ -347,92 +2681,432 @@ fn schema_ty(db: &dyn crate::Db, api: InputApi, schema: Schema) -> Type {
                 let mut properties = BTreeMap::default();
 
                 for (name, prop) in &obj.properties {
-                    let ty = shallow_schema_ty(db, api, &prop.clone().unbox());
+                    let ty = shallow_schema_ty(
+                        db,
+                        api,
+                        &prop.clone().unbox(),
+                        format!("{pointer}/properties/{}", escape_pointer(name)),
+                    );
                     let required = obj.required.contains(name);
                     properties.insert(
                         name.clone(),
                         Property {
                             ty,
                             optional: !required,
+                            doc: Doc::of(db, prop),
                         },
                     );
                 }
 
-                if let Some(disc) = &schema.data(db).discriminator {
-                    assert!(disc.extensions.is_empty());
-
-                    match disc.mapping.len() {
-                        0 => todo!(),
-                        1 => todo!(),
-                        _ => Type::new(
+                match &schema.data(db).discriminator {
+                    Some(disc) if disc.mapping.is_empty() => {
+                        report(
                             db,
-                            TypeKind::Or(
-                                disc.mapping
-                                    .iter()
-                                    .map(|(name, rest)| {
-                                        let ty = ty_by_name(db, api, rest.clone());
-                                        let marker = Type::new(
-                                            db,
-                                            TypeKind::Object(
-                                                [(
-                                                    disc.property_name.clone(),
-                                                    Property::required(Type::new(
-                                                        db,
-                                                        TypeKind::Ident(name.clone()),
-                                                    )),
-                                                )]
-                                                .into_iter()
-                                                .collect(),
-                                            ),
-                                        );
-                                        Type::new(db, TypeKind::And(vec![marker, ty]))
-                                    })
-                                    .collect(),
-                            ),
+                            format!("{pointer}/discriminator"),
+                            "discriminators without a mapping are not supported, ignoring it",
+                        );
+                        Type::new(db, TypeKind::Object(properties))
+                    }
+                    Some(disc) => Type::new(
+                        db,
+                        TypeKind::Or(
+                            disc.mapping
+                                .iter()
+                                .map(|(name, rest)| {
+                                    let ty = ty_by_name(db, api, rest.clone());
+                                    let marker = Type::new(
+                                        db,
+                                        TypeKind::Object(
+                                            [(
+                                                disc.property_name.clone(),
+                                                Property::required(Type::new(
+                                                    db,
+                                                    TypeKind::Ident(name.clone()),
+                                                )),
+                                            )]
+                                            .into_iter()
+                                            .collect(),
+                                        ),
+                                    );
+                                    Type::new(db, TypeKind::And(vec![marker, ty]))
+                                })
+                                .collect(),
                         ),
+                    ),
+                    None => {
+                        let values = match &obj.additional_properties {
+                            Some(oapi::AdditionalProperties::Schema(values)) => {
+                                Some(shallow_schema_ty(
+                                    db,
+                                    api,
+                                    values,
+                                    format!("{pointer}/additionalProperties"),
+                                ))
+                            }
+                            // NOTE: Free-form objects may hold anything
+                            // under any key.
+                            Some(oapi::AdditionalProperties::Any(true)) => Some(Type::unknown(db)),
+                            Some(oapi::AdditionalProperties::Any(false)) | None => None,
+                        };
+                        let keys = || map_keys(db, api, schema.data(db), &pointer);
+                        match values {
+                            None => Type::new(db, TypeKind::Object(properties)),
+                            Some(values) if properties.is_empty() => {
+                                Type::new(db, TypeKind::Map(keys(), values))
+                            }
+                            // NOTE: Named properties alongside an index
+                            // signature become the intersection of the two.
+                            Some(values) => Type::new(
+                                db,
+                                TypeKind::And(vec![
+                                    Type::new(db, TypeKind::Object(properties)),
+                                    Type::new(db, TypeKind::Map(keys(), values)),
+                                ]),
+                            ),
+                        }
                     }
-                } else {
-                    Type::new(db, TypeKind::Object(properties))
                 }
             }
             oapi::Type::Array(array_ty) => {
-                let ty = shallow_schema_ty(db, api, &array_ty.items.clone().unwrap().unbox());
+                let ty = match &array_ty.items {
+                    Some(items) => shallow_schema_ty(
+                        db,
+                        api,
+                        &items.clone().unbox(),
+                        format!("{pointer}/items"),
+                    ),
+                    None => {
+                        report(
+                            db,
+                            pointer,
+                            "arrays without `items` are typed as `unknown[]`",
+                        );
+                        Type::unknown(db)
+                    }
+                };
                 match (array_ty.min_items, array_ty.max_items) {
                     (Some(min), Some(max)) if min == max => {
                         Type::new(db, TypeKind::Tuple(vec![ty; min]))
                     }
-                    (None, None) => Type::new(db, TypeKind::Array(ty)),
-                    (min, max) => todo!("{:?}", (min, max)),
+                    // NOTE: Other bounds aren't expressible as TypeScript types
+                    _ => Type::new(db, TypeKind::Array(ty)),
                 }
             }
             oapi::Type::Boolean {} => Type::new(db, TypeKind::Boolean),
         },
-        oapi::SchemaKind::OneOf { one_of } => Type::new(
-            db,
-            TypeKind::Or(
-                one_of
-                    .iter()
-                    .map(|item| shallow_schema_ty(db, api, item))
-                    .collect(),
-            ),
-        ),
-        oapi::SchemaKind::AllOf { all_of } => Type::new(
+        oapi::SchemaKind::OneOf { one_of: items } | oapi::SchemaKind::AnyOf { any_of: items } => {
+            let key = match schema.kind(db) {
+                oapi::SchemaKind::OneOf { .. } => "oneOf",
+                _ => "anyOf",
+            };
+            Type::new(
+                db,
+                TypeKind::Or(
+                    items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, item)| {
+                            shallow_schema_ty(db, api, item, format!("{pointer}/{key}/{i}"))
+                        })
+                        .collect(),
+                ),
+            )
+        }
+        oapi::SchemaKind::AllOf { all_of } => {
+            let members = all_of
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let name = match item {
+                        oapi::ReferenceOr::Reference { reference } => reference
+                            .rsplit('/')
+                            .next()
+                            .unwrap_or(reference)
+                            .to_string(),
+                        oapi::ReferenceOr::Item(_) => format!("allOf/{i}"),
+                    };
+                    let ty = shallow_schema_ty(db, api, item, format!("{pointer}/allOf/{i}"));
+                    (name, ty)
+                })
+                .collect_vec();
+            report_intersection_conflicts(db, api, pointer, &members);
+            Type::new(
+                db,
+                TypeKind::And(members.into_iter().map(|(_, ty)| ty).collect()),
+            )
+        }
+        oapi::SchemaKind::Not { .. } => {
+            report(db, pointer, "`not` schemas are typed as `unknown`");
+            Type::unknown(db)
+        }
+        // NOTE: Schemas without a `type` listing strings, such as those
+        // converted from `const`, are string literals.
+        oapi::SchemaKind::Any(any)
+            if !any.enumeration.is_empty() && any.enumeration.iter().all(|e| e.is_string()) =>
+        {
+            Type::new(
+                db,
+                TypeKind::Or(
+                    any.enumeration
+                        .iter()
+                        .filter_map(|e| e.as_str())
+                        .map(|e| Type::new(db, TypeKind::Ident(e.to_string())))
+                        .collect(),
+                ),
+            )
+        }
+        // NOTE: `{}` accepts any value, so only schemas with constraints
+        // which are left out are reported.
+        oapi::SchemaKind::Any(any) if *any == oapi::AnySchema::default() => Type::unknown(db),
+        oapi::SchemaKind::Any(_) => {
+            report(
+                db,
+                pointer,
+                "schemas without a `type` are typed as `unknown`",
+            );
+            Type::unknown(db)
+        }
+    }
+}
+
+/// The `format` of a schema as written in the document, if any.
+fn format_name<T: serde::Serialize>(format: &oapi::VariantOrUnknownOrEmpty<T>) -> Option<String> {
+    match serde_json::to_value(format).ok()? {
+        serde_json::Value::String(format) => Some(format),
+        _ => None,
+    }
+}
+
+/// Whether every value of `sub` is also a value of `sup`.
+///
+/// This is conservative: objects are only related when they declare the same
+/// properties, and references and custom types only to themselves, so `false`
+/// may be returned for actual subtypes but `true` never for others.
+fn is_subtype(db: &dyn crate::Db, sub: Type, sup: Type) -> bool {
+    if sub == sup {
+        return true;
+    }
+
+    match (sub.kind(db), sup.kind(db)) {
+        (_, TypeKind::Unknown) => true,
+        (TypeKind::Or(subs), _) => subs.iter().all(|sub| is_subtype(db, *sub, sup)),
+        (_, TypeKind::Or(sups)) => sups.iter().any(|sup| is_subtype(db, sub, *sup)),
+        (TypeKind::Ident(_), TypeKind::String) => true,
+        (TypeKind::Array(a), TypeKind::Array(b)) => is_subtype(db, a, b),
+        (TypeKind::Map(ka, a), TypeKind::Map(kb, b)) => {
+            is_subtype(db, ka, kb) && is_subtype(db, a, b)
+        }
+        (TypeKind::Tuple(xs), TypeKind::Array(b)) => xs.iter().all(|x| is_subtype(db, *x, b)),
+        (TypeKind::Tuple(xs), TypeKind::Tuple(ys)) => {
+            xs.len() == ys.len() && xs.iter().zip(&ys).all(|(x, y)| is_subtype(db, *x, *y))
+        }
+        (TypeKind::Object(a), TypeKind::Object(b)) => {
+            a.len() == b.len()
+                && a.iter().zip(&b).all(|((ka, pa), (kb, pb))| {
+                    ka == kb && (pb.optional || !pa.optional) && is_subtype(db, pa.ty, pb.ty)
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Whether no value can have both types, judging only by their outermost
+/// kind. References, custom types, unions, and intersections never conflict.
+fn conflicts(db: &dyn crate::Db, a: Type, b: Type) -> bool {
+    fn category(kind: &TypeKind) -> Option<&'static str> {
+        match kind {
+            TypeKind::Number => Some("number"),
+            TypeKind::String | TypeKind::Ident(_) => Some("string"),
+            TypeKind::Boolean => Some("boolean"),
+            TypeKind::Null => Some("null"),
+            TypeKind::Object(_) | TypeKind::Map(..) => Some("object"),
+            TypeKind::Array(_) | TypeKind::Tuple(_) => Some("array"),
+            TypeKind::Reference(_)
+            | TypeKind::Custom(_)
+            | TypeKind::Generic(..)
+            | TypeKind::Parameter(_)
+            | TypeKind::Unknown
+            | TypeKind::Or(_)
+            | TypeKind::And(_) => None,
+        }
+    }
+
+    match (a.kind(db), b.kind(db)) {
+        (TypeKind::Ident(a), TypeKind::Ident(b)) => a != b,
+        (a, b) => match (category(&a), category(&b)) {
+            (Some(a), Some(b)) => a != b,
+            _ => false,
+        },
+    }
+}
+
+/// Intersections of incompatible types left in the lowered component types
+/// and operations, keyed by the component type or operation containing them.
+///
+/// These are only left when [`Config::intersection_conflict`] isn't
+/// [`IntersectionConflict::Never`].
+pub fn intersection_conflicts(db: &dyn crate::Db, api: InputApi) -> Vec<(String, String)> {
+    fn find(db: &dyn crate::Db, ty: Type, out: &mut Vec<String>) {
+        match ty.kind(db) {
+            TypeKind::And(options) => {
+                for (a, b) in options.iter().tuple_combinations() {
+                    if conflicts(db, *a, *b) {
+                        out.push(format!("{} & {}", a.to_typescript(db), b.to_typescript(db)));
+                    }
+                }
+                options.iter().for_each(|ty| find(db, *ty, out));
+            }
+            TypeKind::Object(obj) => obj.values().for_each(|prop| find(db, prop.ty, out)),
+            TypeKind::Map(_, ty) | TypeKind::Array(ty) => find(db, ty, out),
+            TypeKind::Tuple(tys) | TypeKind::Or(tys) | TypeKind::Generic(_, tys) => {
+                tys.iter().for_each(|ty| find(db, *ty, out))
+            }
+            TypeKind::Reference(_)
+            | TypeKind::Number
+            | TypeKind::String
+            | TypeKind::Boolean
+            | TypeKind::Null
+            | TypeKind::Ident(_)
+            | TypeKind::Custom(_)
+            | TypeKind::Parameter(_)
+            | TypeKind::Unknown => {}
+        }
+    }
+
+    let types = component_types(db, api)
+        .into_iter()
+        .map(|(name, ty)| (name, vec![ty]));
+    let operations = lower_api(db, api)
+        .into_iter()
+        .map(|op| (op.key(), op.types()));
+
+    types
+        .chain(operations)
+        .flat_map(|(owner, tys)| {
+            let mut found = Vec::new();
+            tys.into_iter().for_each(|ty| find(db, ty, &mut found));
+            found
+                .into_iter()
+                .map(move |conflict| (owner.clone(), conflict))
+        })
+        .collect()
+}
+
+/// Merges the object members of a union which declare the same properties
+/// with the same types, where one of them requires every property the other
+/// requires, into the one requiring fewer. That member admits every value of
+/// the other, so the union admits exactly the same values.
+///
+/// Members whose optionality differs both ways, such as `{ a: X; b?: Y }` and
+/// `{ a?: X; b: Y }`, are kept apart, as merging them would admit `{}`.
+fn merge_optionality(db: &dyn crate::Db, options: Vec<Type>) -> Vec<Type> {
+    let mut merged: Vec<Type> = Vec::new();
+
+    for opt in options {
+        let TypeKind::Object(obj) = opt.kind(db) else {
+            merged.push(opt);
+            continue;
+        };
+        let same = merged.iter_mut().find(|other| match other.kind(db) {
+            TypeKind::Object(other) => {
+                let pairs = || other.iter().zip(&obj);
+                other.len() == obj.len()
+                    && pairs().all(|((ka, pa), (kb, pb))| ka == kb && pa.ty == pb.ty)
+                    && (pairs().all(|((_, pa), (_, pb))| pa.optional || !pb.optional)
+                        || pairs().all(|((_, pa), (_, pb))| pb.optional || !pa.optional))
+            }
+            _ => false,
+        });
+        match same {
+            Some(other) => {
+                let TypeKind::Object(mut fields) = other.kind(db) else {
+                    unreachable!()
+                };
+                for (name, prop) in &mut fields {
+                    prop.optional |= obj[name].optional;
+                }
+                *other = Type::new(db, TypeKind::Object(fields));
+            }
+            None => merged.push(opt),
+        }
+    }
+
+    merged
+}
+
+/// Reports the properties which two of the intersected schemas, named by their
+/// component schema or position, declare with incompatible types. These are
+/// typed according to [`Config::intersection_conflict`].
+fn report_intersection_conflicts(
+    db: &dyn crate::Db,
+    api: InputApi,
+    pointer: &str,
+    members: &[(String, Type)],
+) {
+    for ((a_name, a), (b_name, b)) in members.iter().tuple_combinations() {
+        let (TypeKind::Object(a), TypeKind::Object(b)) =
+            (a.resolve(db, api).kind(db), b.resolve(db, api).kind(db))
+        else {
+            continue;
+        };
+        for (field, prop) in &a {
+            let Some(other) = b.get(field) else {
+                continue;
+            };
+            if prop.ty != other.ty && conflicts(db, prop.ty, other.ty) {
+                report_inconsistent(
+                    db,
+                    pointer,
+                    format!(
+                        "{a_name} and {b_name} declare {field:?} with the incompatible types {} and {}",
+                        prop.ty.to_typescript(db),
+                        other.ty.to_typescript(db),
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// The property of an intersection of objects which both declare it.
+///
+/// A literal is narrower than `string`, so it is kept regardless of which
+/// side it is on, and the property is only optional if it is on both sides.
+/// Types which can't be intersected are handled according to
+/// [`Config::intersection_conflict`].
+fn intersect_property(
+    db: &dyn crate::Db,
+    api: InputApi,
+    a: Property,
+    b: Property,
+    pointer: &str,
+) -> Property {
+    let ty = match (a.ty.kind(db), b.ty.kind(db)) {
+        _ if a.ty == b.ty => a.ty,
+        (TypeKind::Ident(_), TypeKind::String) => a.ty,
+        (TypeKind::String, TypeKind::Ident(_)) => b.ty,
+        _ if conflicts(db, a.ty, b.ty) => match api.config(db).intersection_conflict {
+            IntersectionConflict::Never => Type::new(db, TypeKind::Or(Vec::new())),
+            IntersectionConflict::Intersect | IntersectionConflict::Error => {
+                Type::new(db, TypeKind::And(vec![a.ty, b.ty]))
+            }
+        },
+        _ => simplify_ty(
             db,
-            TypeKind::And(
-                all_of
-                    .iter()
-                    .map(|item| shallow_schema_ty(db, api, item))
-                    .collect(),
-            ),
+            api,
+            Type::new(db, TypeKind::And(vec![a.ty, b.ty])),
+            pointer.to_string(),
         ),
-        oapi::SchemaKind::AnyOf { .. } => todo!(),
-        oapi::SchemaKind::Not { .. } => todo!(),
-        oapi::SchemaKind::Any(_) => todo!(),
+    };
+    Property {
+        ty,
+        optional: a.optional && b.optional,
+        doc: a.doc.or(b.doc),
     }
 }
 
 #[salsa::tracked]
-fn simplify_ty(db: &dyn crate::Db, ty: Type) -> Type {
+fn simplify_ty(db: &dyn crate::Db, api: InputApi, ty: Type, pointer: String) -> Type {
     match ty.kind(db) {
         TypeKind::Reference(_) => ty,
         TypeKind::Object(obj) => Type::new(
@@ -443,38 +3117,117 @@ fn simplify_ty(db: &dyn crate::Db, ty: Type) -> Type {
                         (
                             name.clone(),
                             Property {
-                                ty: simplify_ty(db, prop.ty),
+                                ty: simplify_ty(db, api, prop.ty, pointer.clone()),
                                 optional: prop.optional,
+                                doc: prop.doc,
                             },
                         )
                     })
                     .collect(),
             ),
         ),
-        TypeKind::Array(array_ty) => Type::new(db, TypeKind::Array(simplify_ty(db, array_ty))),
-        TypeKind::Tuple(elements) => Type::new(
+        TypeKind::Map(keys, values) => Type::new(
             db,
-            TypeKind::Tuple(elements.iter().map(|ty| simplify_ty(db, *ty)).collect()),
+            TypeKind::Map(
+                simplify_ty(db, api, keys, pointer.clone()),
+                simplify_ty(db, api, values, pointer.clone()),
+            ),
+        ),
+        TypeKind::Array(array_ty) => Type::new(
+            db,
+            TypeKind::Array(simplify_ty(db, api, array_ty, pointer.clone())),
         ),
-        TypeKind::Or(options) => Type::new(
+        TypeKind::Tuple(elements) => Type::new(
             db,
-            TypeKind::Or(
-                options
+            TypeKind::Tuple(
+                elements
                     .iter()
-                    .map(|opt| simplify_ty(db, *opt))
-                    .sorted()
-                    .dedup()
+                    .map(|ty| simplify_ty(db, api, *ty, pointer.clone()))
                     .collect(),
             ),
         ),
+        TypeKind::Or(options) => {
+            // NOTE: The members are simplified first, so nested unions are
+            // already flat and only need to be spliced in.
+            let options = options
+                .iter()
+                .map(|opt| simplify_ty(db, api, *opt, pointer.clone()))
+                .flat_map(|opt| match opt.kind(db) {
+                    TypeKind::Or(inner) => inner,
+                    _ => vec![opt],
+                })
+                .collect_vec();
+            // NOTE: `unknown` absorbs every other member.
+            if options
+                .iter()
+                .any(|opt| matches!(opt.kind(db), TypeKind::Unknown))
+            {
+                return Type::unknown(db);
+            }
+            // NOTE: Types are ordered by when they were interned, so `null` is
+            // moved last to render nullable types as `T | null` regardless.
+            let options = merge_optionality(db, options)
+                .into_iter()
+                .sorted()
+                .dedup()
+                .sorted_by_key(|opt| matches!(opt.kind(db), TypeKind::Null))
+                .collect_vec();
+
+            // NOTE: Of members subsuming each other only the first is kept.
+            let kept = options
+                .iter()
+                .enumerate()
+                .filter(|&(i, &opt)| {
+                    !options.iter().enumerate().any(|(j, &other)| {
+                        i != j
+                            && is_subtype(db, opt, other)
+                            && (j < i || !is_subtype(db, other, opt))
+                    })
+                })
+                .map(|(_, &opt)| opt)
+                .collect_vec();
+
+            if kept.len() < options.len() {
+                let dropped = options
+                    .iter()
+                    .filter(|opt| !kept.contains(opt))
+                    .map(|opt| opt.ts(db))
+                    .join(", ");
+                report_inconsistent(
+                    db,
+                    &pointer,
+                    format!(
+                        "union members {dropped} are subsumed by other members, leaving them out"
+                    ),
+                );
+            }
+
+            // NOTE: `anyOf` and `oneOf` with a single member remain, for
+            // example, when FastAPI wraps an optional reference with `null`.
+            match kept.as_slice() {
+                [only] => *only,
+                _ => Type::new(db, TypeKind::Or(kept)),
+            }
+        }
         TypeKind::And(options) => {
             let options = options
                 .iter()
-                .map(|opt| simplify_ty(db, *opt))
+                .map(|opt| simplify_ty(db, api, *opt, pointer.clone()))
+                // NOTE: `unknown` doesn't constrain the intersection.
+                .filter(|opt| !matches!(opt.kind(db), TypeKind::Unknown))
                 .sorted()
                 .dedup()
                 .collect_vec();
 
+            if options.is_empty() {
+                return Type::unknown(db);
+            }
+            // NOTE: A single `allOf` member is commonly used for attaching a
+            // description to a reference.
+            if let [only] = options.as_slice() {
+                return *only;
+            }
+
             if options
                 .iter()
                 .all(|opt| matches!(opt.kind(db), TypeKind::Object(_)))
@@ -484,15 +3237,12 @@ fn simplify_ty(db: &dyn crate::Db, ty: Type) -> Type {
                 for opt in options {
                     match opt.kind(db) {
                         TypeKind::Object(fs) => {
-                            for (field, ty) in fs {
-                                let Some(old) = fields.insert(field.clone(), ty) else {
-                                    continue;
+                            for (field, prop) in fs {
+                                let merged = match fields.remove(&field) {
+                                    Some(old) => intersect_property(db, api, old, prop, &pointer),
+                                    None => prop,
                                 };
-                                if let (TypeKind::Ident(_), TypeKind::String) =
-                                    (old.ty.kind(db), ty.ty.kind(db))
-                                {
-                                    fields.insert(field, old);
-                                }
+                                fields.insert(field, merged);
                             }
                         }
                         _ => unreachable!(),
@@ -504,6 +3254,14 @@ fn simplify_ty(db: &dyn crate::Db, ty: Type) -> Type {
                 Type::new(db, TypeKind::And(options))
             }
         }
-        TypeKind::Number | TypeKind::String | TypeKind::Boolean | TypeKind::Ident(_) => ty,
+        TypeKind::Number
+        | TypeKind::String
+        | TypeKind::Boolean
+        | TypeKind::Null
+        | TypeKind::Ident(_)
+        | TypeKind::Custom(_)
+        | TypeKind::Generic(..)
+        | TypeKind::Parameter(_)
+        | TypeKind::Unknown => ty,
     }
 }
@@ -0,0 +1,407 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use itertools::Itertools;
+
+use crate::{component_types, fnv1a, lower_api, InputApi, Operation, ResponseKind, Type, TypeKind};
+
+/// The names of everything generated for an API, recorded so that later runs
+/// can detect renames which would break code using the generated client.
+///
+/// The shapes of the request and response types are recorded as well, for
+/// detecting changes breaking compatibility with the previous version.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Lock {
+    /// The names of the generated component types.
+    pub types: BTreeSet<String>,
+    /// The name of the method generated per operation, keyed by the uppercase
+    /// method and the path template.
+    pub operations: BTreeMap<String, String>,
+    /// The request and response types of every operation, keyed like
+    /// `operations`.
+    pub signatures: BTreeMap<String, Signature>,
+    /// The shape of every component type.
+    pub schemas: BTreeMap<String, Shape>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Signature {
+    pub params: Option<Shape>,
+    pub query: Option<Shape>,
+    pub body: Option<Shape>,
+    pub response: Option<Shape>,
+}
+
+/// A serializable copy of a [`Type`], where component types are referenced
+/// by name.
+///
+/// The options of unions and intersections are sorted, so equal types have
+/// equal shapes regardless of the order they were lowered in.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Shape {
+    Reference {
+        name: String,
+    },
+    Object {
+        properties: BTreeMap<String, PropertyShape>,
+    },
+    Map {
+        values: Box<Shape>,
+    },
+    Array {
+        items: Box<Shape>,
+    },
+    Tuple {
+        elements: Vec<Shape>,
+    },
+    Or {
+        options: Vec<Shape>,
+    },
+    And {
+        options: Vec<Shape>,
+    },
+    Number,
+    Ident {
+        value: String,
+    },
+    String,
+    Boolean,
+    Null,
+    Custom {
+        ts: String,
+    },
+    Generic {
+        name: String,
+        arguments: Vec<Shape>,
+    },
+    Parameter {
+        name: String,
+    },
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub struct PropertyShape {
+    pub shape: Shape,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+impl Lock {
+    pub fn new(db: &dyn crate::Db, api: InputApi) -> Lock {
+        let types = component_types(db, api);
+        let operations = lower_api(db, api);
+
+        Lock {
+            types: types.iter().map(|(name, _)| name.clone()).collect(),
+            operations: operations
+                .iter()
+                .map(|op| (op.key(), op.name(db, api)))
+                .collect(),
+            signatures: operations
+                .iter()
+                .map(|op| (op.key(), Signature::new(db, api, op)))
+                .collect(),
+            schemas: types
+                .iter()
+                .map(|(name, ty)| (name.clone(), Shape::new(db, *ty)))
+                .collect(),
+        }
+    }
+
+    /// Describes every name recorded in `self` which was changed or removed
+    /// in `new`. Added names are not considered changes.
+    pub fn changes(&self, new: &Lock) -> Vec<String> {
+        let mut changes = Vec::new();
+
+        for (operation, name) in &self.operations {
+            match new.operations.get(operation) {
+                Some(new_name) if new_name == name => {}
+                Some(new_name) => changes.push(format!(
+                    "operation `{operation}` was renamed from `{name}` to `{new_name}`"
+                )),
+                None => changes.push(format!("operation `{operation}` (`{name}`) was removed")),
+            }
+        }
+        for name in self.types.difference(&new.types) {
+            changes.push(format!("type `{name}` was removed"));
+        }
+
+        changes
+    }
+
+    /// Describes every change from `self` to `new` which breaks existing
+    /// callers: the [`changes`](Lock::changes) to names, requests accepting
+    /// less than before, and responses no longer matching their previous
+    /// types.
+    pub fn breaking_changes(&self, new: &Lock) -> Vec<String> {
+        let mut changes = self.changes(new);
+
+        for (operation, old) in &self.signatures {
+            let Some(new_signature) = new.signatures.get(operation) else {
+                continue;
+            };
+
+            // NOTE: Callers must still be able to send what they sent before,
+            // so the old request types must be assignable to the new ones.
+            let requests = [
+                ("path parameters", &old.params, &new_signature.params),
+                ("query", &old.query, &new_signature.query),
+                ("body", &old.body, &new_signature.body),
+            ];
+            for (part, old_shape, new_shape) in requests {
+                let compatible = match (old_shape, new_shape) {
+                    (_, None) => true,
+                    (None, Some(new_shape)) => new_shape.is_optional_object(),
+                    (Some(old_shape), Some(new_shape)) => {
+                        assignable((old_shape, self), (new_shape, new), &mut BTreeSet::new())
+                    }
+                };
+                if !compatible {
+                    changes.push(format!(
+                        "the {part} of operation `{operation}` accepts less than before"
+                    ));
+                }
+            }
+
+            // NOTE: Responses must still satisfy what callers expect, so the
+            // new response type must be assignable to the old one.
+            let compatible = match (&old.response, &new_signature.response) {
+                (None, _) => true,
+                (Some(_), None) => false,
+                (Some(old_shape), Some(new_shape)) => {
+                    assignable((new_shape, new), (old_shape, self), &mut BTreeSet::new())
+                }
+            };
+            if !compatible {
+                changes.push(format!(
+                    "the response of operation `{operation}` no longer matches its previous type"
+                ));
+            }
+        }
+
+        changes
+    }
+}
+
+impl Signature {
+    fn new(db: &dyn crate::Db, api: InputApi, op: &Operation) -> Signature {
+        let config = api.config(db);
+
+        let object = |properties: BTreeMap<String, PropertyShape>| {
+            (!properties.is_empty()).then_some(Shape::Object { properties })
+        };
+
+        Signature {
+            params: object(
+                op.path_params
+                    .iter()
+                    .map(|(name, ty)| {
+                        let shape = Shape::new(db, *ty);
+                        (
+                            name.clone(),
+                            PropertyShape {
+                                shape,
+                                optional: false,
+                            },
+                        )
+                    })
+                    .collect(),
+            ),
+            query: object(
+                op.query
+                    .iter()
+                    .map(|(name, param)| {
+                        let property = PropertyShape {
+                            shape: Shape::new(db, param.ty),
+                            optional: param.optional(&config),
+                        };
+                        (name.clone(), property)
+                    })
+                    .collect(),
+            ),
+            body: op.body.map(|body| Shape::new(db, body.ty())),
+            response: op.response.and_then(|response| match response {
+                ResponseKind::Empty => None,
+                ResponseKind::Plain | ResponseKind::Xml => Some(Shape::String),
+                // NOTE: Blobs have no shape to compare, so they are recorded as
+                // an empty object which any other blob is assignable to.
+                ResponseKind::Blob => Some(Shape::Object {
+                    properties: BTreeMap::new(),
+                }),
+                ResponseKind::Csv(row) => Some(Shape::Array {
+                    items: Box::new(Shape::new(db, row)),
+                }),
+                ResponseKind::Redirect => Some(Shape::Object {
+                    properties: BTreeMap::from([(
+                        "redirectedTo".to_string(),
+                        PropertyShape {
+                            shape: Shape::String,
+                            optional: false,
+                        },
+                    )]),
+                }),
+                ResponseKind::Json(ty)
+                | ResponseKind::EventStream(ty)
+                | ResponseKind::Custom(ty) => Some(Shape::new(db, ty)),
+            }),
+        }
+    }
+}
+
+impl Type {
+    /// A hash of the structure of the type which is stable across runs, for
+    /// detecting when a type changed even though its name didn't.
+    ///
+    /// Component types are referenced by name, so the hash of a type only
+    /// changes with its own structure and not with that of the types it
+    /// references.
+    pub fn content_hash(self, db: &dyn crate::Db) -> u64 {
+        fnv1a(
+            serde_json::to_string(&Shape::new(db, self))
+                .unwrap()
+                .as_bytes(),
+        )
+    }
+}
+
+impl Shape {
+    pub(crate) fn new(db: &dyn crate::Db, ty: Type) -> Shape {
+        match ty.kind(db) {
+            TypeKind::Reference(name) => Shape::Reference { name },
+            TypeKind::Object(properties) => Shape::Object {
+                properties: properties
+                    .into_iter()
+                    .map(|(name, prop)| {
+                        let shape = Shape::new(db, prop.ty);
+                        (
+                            name,
+                            PropertyShape {
+                                shape,
+                                optional: prop.optional,
+                            },
+                        )
+                    })
+                    .collect(),
+            },
+            TypeKind::Map(_, values) => Shape::Map {
+                values: Box::new(Shape::new(db, values)),
+            },
+            TypeKind::Array(items) => Shape::Array {
+                items: Box::new(Shape::new(db, items)),
+            },
+            TypeKind::Tuple(elements) => Shape::Tuple {
+                elements: elements.into_iter().map(|ty| Shape::new(db, ty)).collect(),
+            },
+            TypeKind::Or(options) => Shape::Or {
+                options: options
+                    .into_iter()
+                    .map(|ty| Shape::new(db, ty))
+                    .sorted()
+                    .collect(),
+            },
+            TypeKind::And(options) => Shape::And {
+                options: options
+                    .into_iter()
+                    .map(|ty| Shape::new(db, ty))
+                    .sorted()
+                    .collect(),
+            },
+            TypeKind::Number => Shape::Number,
+            TypeKind::Ident(value) => Shape::Ident { value },
+            TypeKind::String => Shape::String,
+            TypeKind::Boolean => Shape::Boolean,
+            TypeKind::Null => Shape::Null,
+            TypeKind::Custom(ts) => Shape::Custom { ts },
+            TypeKind::Generic(name, arguments) => Shape::Generic {
+                name,
+                arguments: arguments.into_iter().map(|ty| Shape::new(db, ty)).collect(),
+            },
+            TypeKind::Parameter(name) => Shape::Parameter { name },
+            TypeKind::Unknown => Shape::Unknown,
+        }
+    }
+
+    /// Whether the shape is an object which may be left out entirely, as none
+    /// of its properties are required.
+    fn is_optional_object(&self) -> bool {
+        match self {
+            Shape::Object { properties } => properties.values().all(|prop| prop.optional),
+            _ => false,
+        }
+    }
+}
+
+/// Whether every value of the shape `from` is a value of the shape `to`, with
+/// references resolved in the lock each shape was taken from.
+///
+/// Pairs of references already being compared are assumed to be assignable,
+/// which keeps recursive types from recursing forever.
+fn assignable(
+    (from, from_lock): (&Shape, &Lock),
+    (to, to_lock): (&Shape, &Lock),
+    assumed: &mut BTreeSet<(String, String)>,
+) -> bool {
+    let resolve = |lock: &Lock, name: &str| lock.schemas.get(name).cloned();
+
+    match (from, to) {
+        (_, Shape::Unknown) => true,
+        (Shape::Reference { name: a }, Shape::Reference { name: b }) => {
+            if !assumed.insert((a.clone(), b.clone())) {
+                return true;
+            }
+            match (resolve(from_lock, a), resolve(to_lock, b)) {
+                (Some(a), Some(b)) => assignable((&a, from_lock), (&b, to_lock), assumed),
+                _ => a == b,
+            }
+        }
+        (Shape::Reference { name }, _) => resolve(from_lock, name)
+            .is_some_and(|from| assignable((&from, from_lock), (to, to_lock), assumed)),
+        (_, Shape::Reference { name }) => resolve(to_lock, name)
+            .is_some_and(|to| assignable((from, from_lock), (&to, to_lock), assumed)),
+        (Shape::Or { options }, _) => options
+            .iter()
+            .all(|from| assignable((from, from_lock), (to, to_lock), assumed)),
+        (_, Shape::Or { options }) => options
+            .iter()
+            .any(|to| assignable((from, from_lock), (to, to_lock), assumed)),
+        (_, Shape::And { options }) => options
+            .iter()
+            .all(|to| assignable((from, from_lock), (to, to_lock), assumed)),
+        (Shape::And { options }, _) => options
+            .iter()
+            .any(|from| assignable((from, from_lock), (to, to_lock), assumed)),
+        (Shape::Object { properties: from }, Shape::Object { properties: to }) => {
+            to.iter().all(|(name, to)| match from.get(name) {
+                Some(from) => {
+                    (to.optional || !from.optional)
+                        && assignable((&from.shape, from_lock), (&to.shape, to_lock), assumed)
+                }
+                None => to.optional,
+            })
+        }
+        (Shape::Array { items: from }, Shape::Array { items: to })
+        | (Shape::Map { values: from }, Shape::Map { values: to }) => {
+            assignable((from, from_lock), (to, to_lock), assumed)
+        }
+        (Shape::Tuple { elements: from }, Shape::Tuple { elements: to }) => {
+            from.len() == to.len()
+                && from
+                    .iter()
+                    .zip(to)
+                    .all(|(from, to)| assignable((from, from_lock), (to, to_lock), assumed))
+        }
+        (Shape::Ident { value: a }, Shape::Ident { value: b }) => a == b,
+        (Shape::Custom { ts: a }, Shape::Custom { ts: b }) => a == b,
+        (Shape::Generic { .. }, Shape::Generic { .. })
+        | (Shape::Parameter { .. }, Shape::Parameter { .. }) => from == to,
+        (Shape::Ident { .. }, Shape::String)
+        | (Shape::Number, Shape::Number)
+        | (Shape::String, Shape::String)
+        | (Shape::Boolean, Shape::Boolean)
+        | (Shape::Null, Shape::Null) => true,
+        _ => false,
+    }
+}
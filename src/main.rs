@@ -1,9 +1,24 @@
-use std::io::Read;
+use std::{
+    collections::BTreeMap,
+    io::Read,
+    sync::{Arc, Mutex},
+};
 
-use abeye::{generate_ts, Config, Database, InputApi};
-use camino::Utf8PathBuf;
+use abeye::{
+    apply_transforms, bundle_external_refs, compatibility, convert_openapi_31, diagnostics, fnv1a,
+    generate_dot, generate_md, generate_mermaid, generate_py, generate_rs, generate_template,
+    generate_ts, generate_ts_files, generate_ts_runtime, intersection_conflicts, lower_api,
+    media_type_stats, merge_document, name_collisions, ts_size_report, Config, Database,
+    Diagnostic, DiagnosticKind, EmptyResponse, GroupBy, InputApi, IntersectionConflict, Lock,
+    NameStrategy, Overlay, PatchOperation, QueryTrace, Runtime, SseTransport, Transform,
+    TsValidators, TypeImports,
+};
+use camino::{Utf8Path, Utf8PathBuf};
 use clap::{Parser, Subcommand, ValueEnum};
-use color_eyre::Result;
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
 use openapiv3 as oapi;
 use tracing_subscriber::{filter::LevelFilter, prelude::*, EnvFilter};
 
@@ -42,40 +57,403 @@ fn run() -> Result<()> {
             source,
             target,
             output,
+            template,
+            fetch,
             api_prefix,
+            name_strategy,
+            empty_response,
+            status_results,
+            typed_errors,
+            intersection_conflict,
+            merges,
+            resolve_external,
+            drop_paths,
+            rewrite_servers,
+            inject_responses,
+            patches,
+            overlays,
+            schema_names,
+            generic_schemas,
+            split,
+            inject,
+            watch,
+            cache_dir,
+            config,
+            fill_query_defaults,
+            operation_constants,
+            runtime_import,
+            sse_transport,
+            runtime,
+            telemetry,
+            trace_context,
+            csrf_header,
+            dedupe,
+            offline_queue,
+            cache_keys,
+            query_parsers,
+            poll_helpers,
+            union_constructors,
+            client_factory,
+            path_types,
+            mock_api,
+            lock,
+            update_lock,
+            deny_breaking,
+            report,
+            trace_queries,
+            validate,
+            ts_validators,
+            branded,
+            tag_clients,
+            group_by,
+            type_hashes,
+            type_imports,
+            strict,
+            keep_going,
         } => {
-            let api: oapi::OpenAPI = match source {
-                Some(s) if s.starts_with("http://") || s.starts_with("https://") => {
-                    tracing::info!(url=?s, "fetching schema");
-                    reqwest::blocking::get(s)?.json()?
+            let mut api_config: Config = match config {
+                Some(path) => {
+                    read_structured(path).wrap_err_with(|| format!("parsing config in {path:?}"))?
                 }
-                Some(s) => serde_json::from_str(&std::fs::read_to_string(s)?)?,
-                None => {
-                    let mut buf = String::new();
-                    std::io::stdin().read_to_string(&mut buf)?;
-                    serde_json::from_str(&buf)?
+                None => Config::default(),
+            };
+            api_config.generic_schemas |= *generic_schemas;
+            let templates = match template {
+                Some(dir) => {
+                    read_templates(dir).wrap_err_with(|| format!("reading templates in {dir:?}"))?
+                }
+                None if matches!(target, Target::Template) => {
+                    bail!("the template target requires --template")
+                }
+                None => BTreeMap::new(),
+            };
+            if api_config.generic_schemas
+                && (*split || !matches!(target, Target::TypeScript | Target::Python | Target::Rust))
+            {
+                bail!(
+                    "--generic-schemas is only supported for the ts, py, and rs targets without --split"
+                );
+            }
+
+            let mut transforms = Vec::new();
+            for pattern in drop_paths {
+                transforms.push(Transform::DropPaths(
+                    globset::Glob::new(pattern)?.compile_matcher(),
+                ));
+            }
+            for (from, to) in rewrite_servers {
+                transforms.push(Transform::RewriteServers {
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+            for (status, path) in inject_responses {
+                let response = serde_json::from_str(&std::fs::read_to_string(path)?)
+                    .wrap_err_with(|| format!("parsing response in {path:?}"))?;
+                transforms.push(Transform::InjectResponse {
+                    status: status.clone(),
+                    response,
+                });
+            }
+            for path in patches {
+                let operations: Vec<PatchOperation> =
+                    read_structured(path).wrap_err_with(|| format!("parsing patch in {path:?}"))?;
+                transforms.push(Transform::Patch(operations));
+            }
+            for path in overlays {
+                let overlay: Overlay = read_structured(path)
+                    .wrap_err_with(|| format!("parsing overlay in {path:?}"))?;
+                transforms.push(Transform::Overlay(overlay));
+            }
+            if let SchemaNames::Sanitize = schema_names {
+                transforms.push(Transform::SanitizeSchemaNames {
+                    keep_generic: api_config.generic_schemas,
+                });
+            }
+            let load = || -> Result<oapi::OpenAPI> {
+                let mut doc = read_source(source.as_deref(), fetch)?;
+                for other in merges {
+                    merge_document(
+                        &mut doc,
+                        read_source(Some(other.as_str()), &fetch.for_references())?,
+                    )
+                    .wrap_err_with(|| format!("merging {other:?}"))?;
+                }
+                bundle_external_refs(&mut doc, *resolve_external, |location| {
+                    read_source(
+                        Some(resolve_location(source.as_deref(), location)?.as_str()),
+                        &fetch.for_references(),
+                    )
+                })?;
+                apply_transforms(&mut doc, &transforms)?;
+                Ok(serde_json::from_value(doc)?)
+            };
+
+            if let Some(prefix) = api_prefix {
+                api_config.api_prefix = Some(prefix.trim_end_matches('/').into());
+            }
+            if let Some(specifier) = runtime_import {
+                api_config.runtime_import = Some(specifier.clone());
+            }
+            if let Some(header) = csrf_header {
+                api_config.csrf_header = Some(header.clone());
+            }
+            if let Some(validators) = ts_validators {
+                api_config.ts_validators = *validators;
+                api_config.validate = true;
+            }
+            api_config.name_strategy = name_strategy.unwrap_or(api_config.name_strategy);
+            api_config.empty_response = empty_response.unwrap_or(api_config.empty_response);
+            api_config.intersection_conflict =
+                intersection_conflict.unwrap_or(api_config.intersection_conflict);
+            api_config.sse_transport = sse_transport.unwrap_or(api_config.sse_transport);
+            api_config.runtime = runtime.unwrap_or(api_config.runtime);
+            api_config.group_by = group_by.unwrap_or(api_config.group_by);
+            api_config.type_imports = type_imports.unwrap_or(api_config.type_imports);
+            api_config.status_results |= *status_results;
+            api_config.typed_errors |= *typed_errors;
+            api_config.fill_query_defaults |= *fill_query_defaults;
+            api_config.operation_constants |= *operation_constants;
+            api_config.telemetry |= *telemetry;
+            api_config.trace_context |= *trace_context;
+            api_config.dedupe |= *dedupe;
+            api_config.offline_queue |= *offline_queue;
+            api_config.cache_keys |= *cache_keys;
+            api_config.query_parsers |= *query_parsers;
+            api_config.poll_helpers |= *poll_helpers;
+            api_config.union_constructors |= *union_constructors;
+            api_config.client_factory |= *client_factory;
+            api_config.path_types |= *path_types;
+            api_config.mock_api |= *mock_api;
+            api_config.validate |= *validate;
+            api_config.branded |= *branded;
+            api_config.tag_clients |= *tag_clients;
+            api_config.type_hashes |= *type_hashes;
+            api_config.keep_going |= *keep_going;
+
+            let mut db = if *trace_queries {
+                let trace = Arc::<Mutex<QueryTrace>>::default();
+                trace_query_cycles(trace.clone());
+                Database::with_query_trace(trace)
+            } else {
+                Database::default()
+            };
+
+            let api = InputApi::new(&db, load()?, api_config);
+
+            let mut emitter = Emitter {
+                target: *target,
+                split: *split,
+                inject: *inject,
+                output: output.as_deref(),
+                cache_dir: cache_dir.as_deref(),
+                templates: &templates,
+                written: BTreeMap::new(),
+            };
+            let cached = emitter.cached(&db, api)?;
+            let reported = match &cached {
+                Some(entry) => entry.diagnostics.clone(),
+                None => diagnostics(&db, api),
+            };
+            check_diagnostics(&db, api, &reported, *strict)?;
+            // NOTE: Outputs are only cached once these have passed for the
+            // same document and configuration.
+            if cached.is_none() {
+                check_intersections(&db, api)?;
+                check_name_collisions(&db, api)?;
+            }
+            if let Some(lock) = lock {
+                check_lock(&db, api, lock, *update_lock, *deny_breaking)?;
+            }
+            emitter.emit(&db, api, cached)?;
+            print_diagnostics(&reported);
+
+            if *report {
+                print_report(&db, api);
+            }
+            if let Some(trace) = db.query_trace() {
+                print_query_trace(&trace);
+            }
+
+            if *watch {
+                let Some(source) = source.as_deref().filter(|s| !is_url(s)) else {
+                    bail!("--watch requires the source to be a file");
+                };
+
+                tracing::info!(?source, "watching for changes");
+
+                let mut last_modified = std::fs::metadata(source)?.modified()?;
+                loop {
+                    std::thread::sleep(std::time::Duration::from_millis(500));
+
+                    let modified = std::fs::metadata(source)?.modified()?;
+                    if modified == last_modified {
+                        continue;
+                    }
+                    last_modified = modified;
+
+                    tracing::info!("source changed, regenerating");
+                    match load() {
+                        Ok(new_api) => {
+                            api.set_api(&mut db).to(new_api);
+                            let cached = emitter.cached(&db, api)?;
+                            let reported = match &cached {
+                                Some(entry) => entry.diagnostics.clone(),
+                                None => diagnostics(&db, api),
+                            };
+                            let mut checked = check_diagnostics(&db, api, &reported, *strict);
+                            if cached.is_none() {
+                                checked = checked
+                                    .and_then(|()| check_intersections(&db, api))
+                                    .and_then(|()| check_name_collisions(&db, api));
+                            }
+                            if let Err(err) = checked {
+                                tracing::error!("{err:?}");
+                                continue;
+                            }
+                            if let Some(lock) = lock {
+                                if let Err(err) =
+                                    check_lock(&db, api, lock, *update_lock, *deny_breaking)
+                                {
+                                    tracing::error!("{err:?}");
+                                    continue;
+                                }
+                            }
+                            emitter.emit(&db, api, cached)?;
+                            print_diagnostics(&reported);
+                        }
+                        Err(err) => tracing::error!("failed to load source: {err:?}"),
+                    }
+                }
+            }
+        }
+        Command::Stats {
+            source,
+            config,
+            fetch,
+        } => {
+            let api_config: Config = match config {
+                Some(path) => {
+                    read_structured(path).wrap_err_with(|| format!("parsing config in {path:?}"))?
                 }
+                None => Config::default(),
             };
 
             let db = Database::default();
+            let api = InputApi::new(
+                &db,
+                serde_json::from_value(read_source(source.as_deref(), fetch)?)?,
+                api_config,
+            );
+
+            let stats = media_type_stats(&db, api);
+            let width = stats
+                .iter()
+                .map(|stats| stats.media_type.len())
+                .max()
+                .unwrap_or(0)
+                .max("media type".len());
+            println!(
+                "{:<width$}  {:>8}  {:>9}  supported",
+                "media type", "requests", "responses"
+            );
+            for stats in &stats {
+                println!(
+                    "{:<width$}  {:>8}  {:>9}  {}",
+                    stats.media_type,
+                    stats.requests,
+                    stats.responses,
+                    if stats.supported { "yes" } else { "no" }
+                );
+            }
 
+            let total = api
+                .api(&db)
+                .paths
+                .paths
+                .values()
+                .filter_map(|item| item.as_item())
+                .map(|item| item.iter().count())
+                .sum::<usize>();
+            println!();
+            println!(
+                "{} of {total} operations are generated",
+                lower_api(&db, api).len()
+            );
+        }
+        Command::Score {
+            source,
+            config,
+            fetch,
+        } => {
+            let api_config: Config = match config {
+                Some(path) => {
+                    read_structured(path).wrap_err_with(|| format!("parsing config in {path:?}"))?
+                }
+                None => Config::default(),
+            };
+
+            let db = Database::default();
             let api = InputApi::new(
                 &db,
-                api,
-                Config {
-                    api_prefix: api_prefix
-                        .clone()
-                        .map(|prefix| prefix.trim_end_matches('/').into()),
-                },
+                serde_json::from_value(read_source(source.as_deref(), fetch)?)?,
+                api_config,
             );
 
-            let output_text = match target {
-                Target::TypeScript => generate_ts(&db, api),
+            let compatibility = compatibility(&db, api);
+            println!(
+                "score: {:.0}% ({} of {} operations generated, {} without diagnostics)",
+                compatibility.score(),
+                compatibility.generated,
+                compatibility.operations,
+                compatibility.clean
+            );
+            if compatibility.gaps.is_empty() {
+                return Ok(());
+            }
+
+            let width = compatibility
+                .gaps
+                .iter()
+                .map(|gap| gap.reason.chars().count())
+                .max()
+                .unwrap_or(0)
+                .max("reason".len());
+            println!();
+            println!(
+                "{:<12}  {:>5}  {:<width$}  example",
+                "kind", "count", "reason"
+            );
+            for gap in &compatibility.gaps {
+                println!(
+                    "{:<12}  {:>5}  {:<width$}  {}",
+                    gap.kind.as_str(),
+                    gap.count,
+                    gap.reason,
+                    gap.example
+                );
+            }
+        }
+        Command::Runtime {
+            output,
+            config,
+            sse_transport,
+            runtime,
+        } => {
+            let mut api_config: Config = match config {
+                Some(path) => {
+                    read_structured(path).wrap_err_with(|| format!("parsing config in {path:?}"))?
+                }
+                None => Config::default(),
             };
 
+            api_config.sse_transport = sse_transport.unwrap_or(api_config.sse_transport);
+            api_config.runtime = runtime.unwrap_or(api_config.runtime);
+            let output_text = generate_ts_runtime(&api_config);
+
             match output {
                 Some(output_path) => {
-                    tracing::info!(path=?output_path,"writing output");
+                    tracing::info!(path=?output_path, "writing output");
                     std::fs::write(output_path, output_text)?;
                 }
                 None => {
@@ -100,7 +478,8 @@ enum Command {
     /// Generate type definitions and client for the given OpenAPI.
     Generate {
         /// Path or URL of the OpenAPI document. If none is provided the
-        /// document will be read from STDIN.
+        /// document will be read from STDIN. Both OpenAPI 3.0 and 3.1
+        /// documents are supported.
         source: Option<String>,
         /// The output format of the generated file.
         #[clap(long, short)]
@@ -109,6 +488,14 @@ enum Command {
         /// out generated file will be printed to STDOUT.
         #[clap(long, short)]
         output: Option<Utf8PathBuf>,
+        /// The directory of Jinja templates rendered by the template target,
+        /// each to the same path in the --output directory with a trailing
+        /// ".jinja" removed. Templates whose file name starts with "_" are
+        /// only included or imported by the others.
+        #[clap(long, value_name = "DIR")]
+        template: Option<Utf8PathBuf>,
+        #[clap(flatten)]
+        fetch: SourceOptions,
         /// A common prefix for API endpoints to exclude when determining names
         /// generated methods.
         ///
@@ -126,11 +513,975 @@ enum Command {
         /// * "/beta/api/webgraph/host/outgoing" => "webgraphHostOutgoing"
         #[clap(long)]
         api_prefix: Option<String>,
+        /// How the generated methods are named. Methods can also be renamed
+        /// individually in the configuration file.
+        #[clap(long, value_enum)]
+        name_strategy: Option<NameStrategy>,
+        /// How responses without a body, or with an object schema without any
+        /// properties, are typed in the generated client.
+        #[clap(long, value_enum)]
+        empty_response: Option<EmptyResponse>,
+        /// Resolve methods of JSON operations with the status and body of every
+        /// documented response, such as `{ status: 404; data: NotFound }`,
+        /// instead of rejecting those which aren't successful.
+        #[clap(long)]
+        status_results: bool,
+        /// Reject requests whose response isn't successful with an `ApiError`
+        /// holding the status and the body parsed as JSON, and export the
+        /// union of the documented error bodies of each operation, such as
+        /// `GetUserError`.
+        #[clap(long)]
+        typed_errors: bool,
+        /// What a field becomes when the objects of an intersection declare
+        /// it with incompatible types, such as `number` and an object.
+        #[clap(long, value_enum)]
+        intersection_conflict: Option<IntersectionConflict>,
+        /// Path or URL of another OpenAPI document whose paths and components
+        /// are merged into the source, for generating a single client for
+        /// several services. May be given multiple times.
+        ///
+        /// References to schemas in other documents, such as
+        /// "shared.json#/components/schemas/Error", are resolved relative to
+        /// the source, and copied into the components of the merged document.
+        /// Identical schemas are only emitted once.
+        #[clap(long = "merge", value_name = "SOURCE")]
+        merges: Vec<String>,
+        /// Resolve references to any value of other files and URLs, such as
+        /// "./components/user.yaml#/User" or "./paths/users.yaml", for specs
+        /// split across several files. Referenced values are copied into the
+        /// component schemas, named after the last segment of the reference,
+        /// and referenced path items are inlined.
+        #[clap(long)]
+        resolve_external: bool,
+        /// Remove paths matching the glob pattern from the document before
+        /// generating, for example "/internal/**". May be given multiple times.
+        ///
+        /// Transforms are applied in the order: dropped paths, rewritten
+        /// servers, injected responses, patches, overlays, and finally
+        /// sanitized schema names.
+        #[clap(long = "drop-path", value_name = "GLOB")]
+        drop_paths: Vec<String>,
+        /// Rewrite server URLs starting with FROM to start with TO instead.
+        /// May be given multiple times.
+        #[clap(long = "rewrite-server", value_name = "FROM=TO", value_parser = parse_key_value)]
+        rewrite_servers: Vec<(String, String)>,
+        /// Add the response object in FILE under STATUS to every operation not
+        /// already documenting that status. May be given multiple times.
+        #[clap(long = "inject-response", value_name = "STATUS=FILE", value_parser = parse_key_value)]
+        inject_responses: Vec<(String, String)>,
+        /// Apply the RFC 6902 JSON Patch in FILE to the document before
+        /// generating. May be given multiple times.
+        #[clap(long = "patch", value_name = "FILE")]
+        patches: Vec<Utf8PathBuf>,
+        /// Apply the OpenAPI Overlay document in FILE to the document before
+        /// generating. May be given multiple times.
+        #[clap(long = "overlay", value_name = "FILE")]
+        overlays: Vec<Utf8PathBuf>,
+        /// What becomes of component schemas whose names aren't identifiers,
+        /// such as "Page_SearchResult". Names in the configuration file refer
+        /// to the sanitized names.
+        #[clap(long, value_enum, default_value_t = SchemaNames::Sanitize)]
+        schema_names: SchemaNames,
+        /// Declare families of component schemas such as "Page_User" and
+        /// "Page_Post", which differ only in a referenced schema, as a single
+        /// generic type "Page<T>". Only supported for the ts, py, and rs
+        /// targets without --split.
+        #[clap(long)]
+        generic_schemas: bool,
+        /// Write the output as a directory of modules instead of a single file,
+        /// with one module per component type. Requires --output, which is
+        /// used as the directory. Only supported for the ts target.
+        #[clap(long)]
+        split: bool,
+        /// Replace only the lines between the `abeye:start` and `abeye:end`
+        /// marker comments of the existing --output file, keeping the code
+        /// around them.
+        #[clap(long)]
+        inject: bool,
+        /// Keep running after generating, regenerating whenever the source
+        /// file changes. Only outputs whose contents changed are rewritten.
+        #[clap(long)]
+        watch: bool,
+        /// Directory in which generated outputs are cached between
+        /// invocations. When the document and all options are identical to a
+        /// previous run, its outputs and diagnostics are reused without
+        /// lowering the document, except for --lock and --report. Any change
+        /// to the document misses the cache.
+        #[clap(long)]
+        cache_dir: Option<Utf8PathBuf>,
+        /// Path of a TOML, YAML, or JSON file with additional configuration.
+        #[clap(long, short)]
+        config: Option<Utf8PathBuf>,
+        /// Fill in query parameters with a documented default when the caller
+        /// omits them, making them optional in the generated client.
+        #[clap(long)]
+        fill_query_defaults: bool,
+        /// Export a constant per operation describing its method and path, for
+        /// example `export const AUTOSUGGEST = { method: "POST", path:
+        /// "/beta/api/autosuggest" } as const`.
+        #[clap(long)]
+        operation_constants: bool,
+        /// Import the runtime helpers from this module specifier instead of
+        /// including them in the generated output, for example
+        /// "@ourorg/abeye-runtime". Use `abeye runtime` to generate the
+        /// module.
+        #[clap(long, value_name = "SPECIFIER")]
+        runtime_import: Option<String>,
+        /// How event streams are consumed by the generated client.
+        #[clap(long, value_enum)]
+        sse_transport: Option<SseTransport>,
+        /// The JavaScript runtime the generated client targets.
+        #[clap(long, value_enum)]
+        runtime: Option<Runtime>,
+        /// Report the name, method, path, status, and duration of every request
+        /// to the hook registered with `setOnMetric`.
+        #[clap(long)]
+        telemetry: bool,
+        /// Send the W3C `traceparent` and `tracestate` headers of the context
+        /// returned by the getter registered with `setTraceContext`, linking
+        /// requests to the spans of the backend. Can be overridden per
+        /// operation with the `x-abeye-trace` extension. Not supported by
+        /// event streams using `EventSource`.
+        #[clap(long)]
+        trace_context: bool,
+        /// Send the token returned by the `csrfToken` option in this header
+        /// with every POST, PUT, PATCH, and DELETE request, for deployments
+        /// authenticating with cookies. The runtime provides `csrfFromCookie`
+        /// and `csrfFromMeta` for reading the token, for example
+        /// `createApi({ csrfToken: csrfFromCookie("csrftoken") })`.
+        #[clap(long, value_name = "HEADER")]
+        csrf_header: Option<String>,
+        /// Share a single request between identical concurrent GET calls. Can
+        /// be overridden per operation with the `x-abeye-dedupe` extension.
+        #[clap(long)]
+        dedupe: bool,
+        /// Generate `offlineQueue`, which persists POST, PUT, PATCH, and DELETE
+        /// calls made while offline and replays them once back online.
+        #[clap(long)]
+        offline_queue: bool,
+        /// Export `cacheKeys`, deriving a stable key from the parameters of
+        /// every GET operation for use with client side caches.
+        #[clap(long)]
+        cache_keys: bool,
+        /// Export `parseXQuery` for every GET operation with query parameters,
+        /// validating and converting the `URLSearchParams` of a page into the
+        /// typed query of the operation. Throws `QueryParseError` on missing
+        /// or invalid parameters.
+        #[clap(long)]
+        query_parsers: bool,
+        /// Export `pollX` for every GET operation responding with JSON, taking
+        /// the arguments of the method along with `{ intervalMs, until,
+        /// signal }` and returning an async iterator of every response, for
+        /// polling job status and progress endpoints.
+        #[clap(long)]
+        poll_helpers: bool,
+        /// Export `makeX` for every component schema used as a request body
+        /// which is a union tagged by a discriminator or a string literal
+        /// property, with a function per variant taking the other fields and
+        /// filling in the tag, such as `makeSearchQuery.plain({ text })`.
+        #[clap(long)]
+        union_constructors: bool,
+        /// Export `createApi`, which creates a client with the same methods as
+        /// `api` using the given options, such as a `locale`, for every call.
+        #[clap(long)]
+        client_factory: bool,
+        /// Export the `Paths` type mapping operation names to their path
+        /// templates, along with `PathOf` and `ExtractParams` for building
+        /// generic wrappers keyed on them.
+        #[clap(long)]
+        path_types: bool,
+        /// Export `createMockApi`, creating an object like `api` whose methods
+        /// resolve with fake data instead of making requests, for use in
+        /// tests. Individual methods can be overridden. With --split this is
+        /// written to `mock.ts`, which isn't re-exported by `index.ts`.
+        #[clap(long)]
+        mock_api: bool,
+        /// Record the generated operation and type names in this file, and
+        /// fail if a later run would rename or remove any of them.
+        #[clap(long, value_name = "PATH")]
+        lock: Option<Utf8PathBuf>,
+        /// Accept the changed names, rewriting the file given by --lock.
+        #[clap(long, requires = "lock")]
+        update_lock: bool,
+        /// Fail if the types recorded in the file given by --lock changed in a
+        /// way breaking existing callers: operations were removed, requests
+        /// accept less than before, or responses no longer match their
+        /// previous types.
+        #[clap(long, requires = "lock")]
+        deny_breaking: bool,
+        /// Print the size of the TypeScript generated for every type and
+        /// operation to STDERR, largest first.
+        #[clap(long)]
+        report: bool,
+        /// Print how often and for how long every query was executed while
+        /// lowering and generating to STDERR, and the queries leading up to a
+        /// query cycle if one is detected.
+        #[clap(long)]
+        trace_queries: bool,
+        /// Export a type guard per component type, and check JSON responses
+        /// and event stream payloads against them at runtime.
+        #[clap(long)]
+        validate: bool,
+        /// How values are checked at runtime, implying --validate.
+        #[clap(long, value_enum)]
+        ts_validators: Option<TsValidators>,
+        /// Brand every component type with its name, for example `type User =
+        /// { ... } & { readonly __schema: "User" }`, so that structurally
+        /// identical types are not interchangeable.
+        #[clap(long)]
+        branded: bool,
+        /// Export a client object per tag, such as `searchApi`, holding the
+        /// methods of the operations with that tag, so bundles using a single
+        /// tag only include its operations. `api` combines all of them.
+        #[clap(long)]
+        tag_clients: bool,
+        /// Group the methods of `api` by the first tag of each operation, such
+        /// as `api.search.autosuggest`. Untagged operations are grouped under
+        /// `api.default`.
+        #[clap(long, value_enum)]
+        group_by: Option<GroupBy>,
+        /// Precede every component type with a comment holding a hash of its
+        /// structure, which only changes when the type itself does.
+        #[clap(long)]
+        type_hashes: bool,
+        /// How types are imported alongside values from the same module. The
+        /// output compiles with `isolatedModules` and `verbatimModuleSyntax`
+        /// either way.
+        #[clap(long, value_enum)]
+        type_imports: Option<TypeImports>,
+        /// Fail on constructs of the document which aren't supported, instead
+        /// of leaving them out or typing them as `unknown`. Operations skipped
+        /// by the configuration are still allowed.
+        #[clap(long)]
+        strict: bool,
+        /// Skip operations which can't be lowered without failing `--strict`,
+        /// leaving a commented placeholder for each in the `api` object.
+        #[clap(long)]
+        keep_going: bool,
+    },
+    /// Generate the standalone runtime module imported by clients generated
+    /// with --runtime-import.
+    Runtime {
+        /// The path where the output will be written. If none is provided the
+        /// out generated file will be printed to STDOUT.
+        #[clap(long, short)]
+        output: Option<Utf8PathBuf>,
+        /// Path of a TOML, YAML, or JSON file with additional configuration,
+        /// of which only the JSON hooks apply to the runtime.
+        #[clap(long, short)]
+        config: Option<Utf8PathBuf>,
+        /// How event streams are consumed by the generated client.
+        #[clap(long, value_enum)]
+        sse_transport: Option<SseTransport>,
+        /// The JavaScript runtime the generated client targets.
+        #[clap(long, value_enum)]
+        runtime: Option<Runtime>,
+    },
+    /// Print how many operations use each media type, and which media types
+    /// keep operations from being generated.
+    Stats {
+        /// Path or URL of the OpenAPI document. If none is provided the
+        /// document will be read from STDIN. Both OpenAPI 3.0 and 3.1
+        /// documents are supported.
+        source: Option<String>,
+        /// Path of a TOML, YAML, or JSON file with additional configuration.
+        #[clap(long, short)]
+        config: Option<Utf8PathBuf>,
+        #[clap(flatten)]
+        fetch: SourceOptions,
     },
+    /// Score how much of a document would be generated without writing
+    /// anything, listing the unsupported features it uses by how often they
+    /// occur, to judge whether a client can be generated for an API.
+    Score {
+        /// Path or URL of the OpenAPI document. If none is provided the
+        /// document will be read from STDIN. Both OpenAPI 3.0 and 3.1
+        /// documents are supported.
+        source: Option<String>,
+        /// Path of a TOML, YAML, or JSON file with additional configuration.
+        #[clap(long, short)]
+        config: Option<Utf8PathBuf>,
+        #[clap(flatten)]
+        fetch: SourceOptions,
+    },
+}
+
+/// How the source document is read, when it isn't a file.
+#[derive(Debug, Clone, clap::Args)]
+struct SourceOptions {
+    /// The format of the document read from STDIN.
+    #[clap(long, value_enum, default_value_t = DocumentFormat::Json)]
+    stdin_format: DocumentFormat,
+    /// The HTTP method used for fetching a source URL. Defaults to POST if
+    /// --source-body is given, and GET otherwise.
+    #[clap(long, value_name = "METHOD")]
+    source_method: Option<reqwest::Method>,
+    /// Send the contents of FILE as the body of the request fetching a source
+    /// URL, for gateways only exposing the document through a POST.
+    #[clap(long, value_name = "FILE")]
+    source_body: Option<Utf8PathBuf>,
+    /// Send the header with the request fetching a source URL, for example
+    /// "Authorization=Bearer ...". May be given multiple times.
+    #[clap(long = "source-header", value_name = "NAME=VALUE", value_parser = parse_key_value)]
+    source_headers: Vec<(String, String)>,
+    /// How many times fetching a document from a URL is retried after a
+    /// timeout, a failed connection, or a 5xx or 429 response.
+    #[clap(long, value_name = "RETRIES", default_value_t = 0)]
+    fetch_retries: u32,
+    /// Give up fetching a document from a URL after this many seconds.
+    #[clap(long, value_name = "SECONDS", default_value_t = 30)]
+    fetch_timeout: u64,
+    /// Accept invalid TLS certificates when fetching documents from URLs.
+    /// Only use this for internal endpoints you trust.
+    #[clap(long)]
+    insecure: bool,
+    /// Trust the PEM encoded certificate in FILE when fetching documents
+    /// from URLs, for internal endpoints using a private certificate
+    /// authority. May be given multiple times.
+    #[clap(long = "ca-cert", value_name = "FILE")]
+    ca_certs: Vec<Utf8PathBuf>,
+}
+
+impl SourceOptions {
+    /// The options for documents merged into or referenced from the source,
+    /// which only keep how they are fetched.
+    fn for_references(&self) -> SourceOptions {
+        SourceOptions {
+            stdin_format: DocumentFormat::Json,
+            source_method: None,
+            source_body: None,
+            source_headers: Vec::new(),
+            fetch_retries: self.fetch_retries,
+            fetch_timeout: self.fetch_timeout,
+            insecure: self.insecure,
+            ca_certs: self.ca_certs.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, ValueEnum)]
+enum DocumentFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+impl DocumentFormat {
+    /// The format of a document at the path or URL, judging by its extension.
+    fn of(location: &str) -> DocumentFormat {
+        let path = location.split(['?', '#']).next().unwrap_or(location);
+        if path.ends_with(".yaml") || path.ends_with(".yml") {
+            DocumentFormat::Yaml
+        } else {
+            DocumentFormat::Json
+        }
+    }
+
+    fn parse(self, text: &str) -> Result<serde_json::Value> {
+        match self {
+            DocumentFormat::Json => Ok(serde_json::from_str(text)?),
+            DocumentFormat::Yaml => Ok(serde_yaml::from_str(text)?),
+        }
+    }
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+/// Resolves the location of a document referenced from the source, relative to
+/// the source if it is a URL or a path.
+fn resolve_location(source: Option<&str>, location: &str) -> Result<String> {
+    Ok(match source {
+        _ if is_url(location) => location.to_string(),
+        Some(source) if is_url(source) => reqwest::Url::parse(source)?.join(location)?.to_string(),
+        Some(source) => Utf8Path::new(source)
+            .parent()
+            .unwrap_or(Utf8Path::new(""))
+            .join(location)
+            .to_string(),
+        None => location.to_string(),
+    })
+}
+
+/// Reads the OpenAPI document from a URL, a file, or STDIN if no source is
+/// given, converting it to OpenAPI 3.0 if it is a 3.1 document.
+///
+/// Files and URLs ending in `.yaml` or `.yml` are read as YAML, as are
+/// responses with a YAML content type. Everything else is read as JSON.
+fn read_source(source: Option<&str>, options: &SourceOptions) -> Result<serde_json::Value> {
+    let mut doc = match source {
+        Some(s) if is_url(s) => fetch_source(s, options)?,
+        Some(s) => DocumentFormat::of(s).parse(&std::fs::read_to_string(s)?)?,
+        None => {
+            let mut buf = String::new();
+            std::io::stdin().read_to_string(&mut buf)?;
+            options.stdin_format.parse(&buf)?
+        }
+    };
+    convert_openapi_31(&mut doc);
+    Ok(doc)
+}
+
+/// Fetches the document at the URL, retrying timeouts, failed connections,
+/// and 5xx and 429 responses with exponential backoff. Redirects are
+/// followed.
+///
+/// The format is picked from the content type of the response, or else the
+/// extension of the URL. HTML responses, such as the error or login pages of
+/// gateways, are rejected.
+fn fetch_source(url: &str, options: &SourceOptions) -> Result<serde_json::Value> {
+    let method = match (&options.source_method, &options.source_body) {
+        (Some(method), _) => method.clone(),
+        (None, Some(_)) => reqwest::Method::POST,
+        (None, None) => reqwest::Method::GET,
+    };
+    let body = match &options.source_body {
+        Some(body) => {
+            Some(std::fs::read(body).wrap_err_with(|| format!("reading body in {body:?}"))?)
+        }
+        None => None,
+    };
+    let mut client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(options.fetch_timeout))
+        .danger_accept_invalid_certs(options.insecure);
+    for path in &options.ca_certs {
+        let pem = std::fs::read(path).wrap_err_with(|| format!("reading {path:?}"))?;
+        let cert = reqwest::Certificate::from_pem(&pem)
+            .wrap_err_with(|| format!("parsing certificate in {path:?}"))?;
+        client = client.add_root_certificate(cert);
+    }
+    let client = client.build()?;
+
+    let mut attempt = 0;
+    let res = loop {
+        tracing::info!(?url, %method, "fetching schema");
+
+        let mut request = client.request(method.clone(), url);
+        for (name, value) in &options.source_headers {
+            request = request.header(name, value);
+        }
+        if let Some(body) = &body {
+            request = request.body(body.clone());
+        }
+        let err = match request.send().and_then(|res| res.error_for_status()) {
+            Ok(res) => break res,
+            Err(err) => err,
+        };
+
+        let transient = err.is_timeout()
+            || err.is_connect()
+            || err.status().is_some_and(|status| {
+                status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            });
+        if !transient || attempt >= options.fetch_retries {
+            return Err(err.into());
+        }
+        let delay = std::time::Duration::from_millis(500 << attempt.min(6));
+        attempt += 1;
+        tracing::warn!(
+            ?url,
+            attempt,
+            ?delay,
+            "fetching schema failed, retrying: {err}"
+        );
+        std::thread::sleep(delay);
+    };
+
+    if res.url().as_str() != url {
+        tracing::info!(url = %res.url(), "followed redirect");
+    }
+    let content_type = res
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|ty| ty.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let text = res.text()?;
+
+    if content_type.contains("html") || text.trim_start().starts_with('<') {
+        let start = text.trim_start().chars().take(80).collect::<String>();
+        bail!(
+            "expected an OpenAPI document from {url:?}, but got an HTML page ({content_type:?}) \
+             starting with {start:?}"
+        );
+    }
+    let format = if content_type.contains("json") {
+        DocumentFormat::Json
+    } else if content_type.contains("yaml") {
+        DocumentFormat::Yaml
+    } else {
+        DocumentFormat::of(url)
+    };
+    format
+        .parse(&text)
+        .wrap_err_with(|| format!("parsing the document from {url:?} ({content_type:?})"))
+}
+
+/// Where and how generated output is written.
+struct Emitter<'a> {
+    target: Target,
+    split: bool,
+    inject: bool,
+    output: Option<&'a Utf8Path>,
+    cache_dir: Option<&'a Utf8Path>,
+    /// The sources of the template target keyed by their path relative to
+    /// the template directory.
+    templates: &'a BTreeMap<String, String>,
+    /// The contents of every file written so far.
+    written: BTreeMap<Utf8PathBuf, String>,
+}
+
+/// The outputs and diagnostics of an earlier invocation, as stored in the
+/// cache directory.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CacheEntry {
+    outputs: BTreeMap<String, String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Emitter<'_> {
+    /// Generates the output for the target, or takes it from the `cached`
+    /// entry, and writes it, skipping files whose contents are unchanged since
+    /// they were last written. Files which were written previously but are no
+    /// longer generated are removed.
+    fn emit(&mut self, db: &Database, api: InputApi, cached: Option<CacheEntry>) -> Result<()> {
+        if self.split && (self.output.is_none() || !matches!(self.target, Target::TypeScript)) {
+            bail!("--split requires --output and the ts target");
+        }
+        let directory = self.split || matches!(self.target, Target::Template);
+        if self.inject && (self.output.is_none() || directory) {
+            bail!(
+                "--inject requires --output and can't be combined with --split or the template target"
+            );
+        }
+
+        let outputs = match cached {
+            Some(entry) => entry.outputs,
+            None => {
+                let outputs = self.generate(db, api)?;
+                self.store(db, api, &outputs)?;
+                outputs
+            }
+        };
+
+        let files: BTreeMap<Utf8PathBuf, String> = match self.output {
+            Some(output) if directory => outputs
+                .into_iter()
+                .map(|(path, text)| (output.join(path), text))
+                .collect(),
+            Some(output) if self.inject => {
+                let existing = std::fs::read_to_string(output)
+                    .wrap_err_with(|| format!("reading {output:?} to inject into"))?;
+                let mut files = BTreeMap::new();
+                for text in outputs.into_values() {
+                    let text = inject(&existing, &text)
+                        .wrap_err_with(|| format!("injecting into {output:?}"))?;
+                    files.insert(output.to_owned(), text);
+                }
+                files
+            }
+            Some(output) => outputs
+                .into_values()
+                .map(|text| (output.to_owned(), text))
+                .collect(),
+            None => {
+                for text in outputs.values() {
+                    println!("{text}");
+                }
+                return Ok(());
+            }
+        };
+
+        for (path, text) in &files {
+            if self.written.get(path) == Some(text) {
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            tracing::info!(?path, "writing output");
+            std::fs::write(path, text)?;
+        }
+        for path in self
+            .written
+            .keys()
+            .filter(|path| !files.contains_key(*path))
+        {
+            tracing::info!(?path, "removing stale output");
+            std::fs::remove_file(path)?;
+        }
+
+        self.written = files;
+
+        Ok(())
+    }
+
+    /// The generated outputs keyed by their path relative to the output
+    /// directory. Unless splitting or rendering templates, there is a single
+    /// output keyed by "".
+    fn generate(&self, db: &Database, api: InputApi) -> Result<BTreeMap<String, String>> {
+        Ok(match self.target {
+            Target::TypeScript if self.split => generate_ts_files(db, api),
+            Target::TypeScript => [(String::new(), generate_ts(db, api))].into(),
+            Target::Markdown => [(String::new(), generate_md(db, api))].into(),
+            Target::Mermaid => [(String::new(), generate_mermaid(db, api))].into(),
+            Target::Dot => [(String::new(), generate_dot(db, api))].into(),
+            Target::Python => [(String::new(), generate_py(db, api))].into(),
+            Target::Rust => [(String::new(), generate_rs(db, api))].into(),
+            Target::Template => generate_template(db, api, self.templates)?,
+        })
+    }
+
+    /// Where the outputs for the exact same document and options are cached,
+    /// if a cache directory is configured.
+    fn cache_path(&self, db: &Database, api: InputApi) -> Result<Option<Utf8PathBuf>> {
+        let Some(cache_dir) = self.cache_dir else {
+            return Ok(None);
+        };
+
+        let key = fnv1a(
+            format!(
+                "{}\0{:?}\0{:?}\0{}\0{:?}\0{}",
+                env!("CARGO_PKG_VERSION"),
+                self.target,
+                api.config(db),
+                self.split,
+                self.templates,
+                serde_json::to_string(api.api(db))?,
+            )
+            .as_bytes(),
+        );
+        Ok(Some(cache_dir.join(format!("{key:016x}.json"))))
+    }
+
+    /// The entry stored by an earlier invocation for the exact same document
+    /// and options, if any.
+    fn cached(&self, db: &Database, api: InputApi) -> Result<Option<CacheEntry>> {
+        let Some(cache_path) = self.cache_path(db, api)? else {
+            return Ok(None);
+        };
+        let Ok(cached) = std::fs::read_to_string(&cache_path) else {
+            return Ok(None);
+        };
+        match serde_json::from_str(&cached) {
+            Ok(entry) => {
+                tracing::info!(path=?cache_path, "reusing cached output");
+                Ok(Some(entry))
+            }
+            Err(err) => {
+                tracing::warn!(path=?cache_path, "ignoring invalid cache entry: {err}");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Stores the generated outputs along with the diagnostics of the
+    /// document, if a cache directory is configured.
+    fn store(
+        &self,
+        db: &Database,
+        api: InputApi,
+        outputs: &BTreeMap<String, String>,
+    ) -> Result<()> {
+        let Some(cache_path) = self.cache_path(db, api)? else {
+            return Ok(());
+        };
+
+        let entry = CacheEntry {
+            outputs: outputs.clone(),
+            diagnostics: diagnostics(db, api),
+        };
+        if let Some(parent) = cache_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&cache_path, serde_json::to_string(&entry)?)?;
+
+        Ok(())
+    }
+}
+
+/// Replaces the lines between the line containing `abeye:start` and the one
+/// containing `abeye:end` in `existing` with `generated`, keeping the marker
+/// lines themselves. The markers may be written in whatever comment syntax
+/// the file uses, such as `// abeye:start` or `# abeye:start`.
+fn inject(existing: &str, generated: &str) -> Result<String> {
+    let Some(start) = existing.find("abeye:start") else {
+        bail!("missing the abeye:start marker");
+    };
+    let start = existing[start..]
+        .find('\n')
+        .map_or(existing.len(), |end| start + end + 1);
+    let Some(end) = existing[start..].find("abeye:end") else {
+        bail!("missing the abeye:end marker after abeye:start");
+    };
+    let end = existing[..start + end]
+        .rfind('\n')
+        .map_or(0, |line| line + 1);
+
+    let mut text = existing[..start].to_string();
+    if !existing[..start].ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(generated.trim_end_matches('\n'));
+    text.push('\n');
+    text.push_str(&existing[end.max(start)..]);
+    Ok(text)
+}
+
+/// Fails if `--strict` was given and the document has constructs which
+/// aren't supported, printing them first. Constructs excluded on purpose are
+/// allowed, as are skipped operations with `--keep-going`.
+fn check_diagnostics(
+    db: &Database,
+    api: InputApi,
+    diagnostics: &[Diagnostic],
+    strict: bool,
+) -> Result<()> {
+    if !strict {
+        return Ok(());
+    }
+    let keep_going = api.config(db).keep_going;
+    let unsupported = diagnostics
+        .iter()
+        .filter(|diagnostic| match diagnostic.kind {
+            DiagnosticKind::Excluded => false,
+            DiagnosticKind::Skipped => !keep_going,
+            DiagnosticKind::Unsupported | DiagnosticKind::Inconsistent => true,
+        })
+        .count();
+    if unsupported > 0 {
+        print_diagnostics(diagnostics);
+        bail!("found {unsupported} unsupported constructs");
+    }
+    Ok(())
+}
+
+/// Prints everything left out of the output or typed loosely as a table,
+/// skipped operations first, so what's missing from the client can be
+/// audited.
+fn print_diagnostics(diagnostics: &[Diagnostic]) {
+    if diagnostics.is_empty() {
+        return;
+    }
+    let mut diagnostics = diagnostics.to_vec();
+    diagnostics.sort_by_key(|diagnostic| diagnostic.kind);
+    let width = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.pointer.len())
+        .max()
+        .unwrap_or(0)
+        .max("pointer".len());
+
+    eprintln!("{:<12}  {:<width$}  reason", "kind", "pointer");
+    for diagnostic in &diagnostics {
+        eprintln!(
+            "{:<12}  {:<width$}  {}",
+            diagnostic.kind.as_str(),
+            diagnostic.pointer,
+            diagnostic.message
+        );
+    }
+}
+
+/// Fails if `--intersection-conflict error` was given and the document
+/// intersects incompatible types.
+fn check_intersections(db: &Database, api: InputApi) -> Result<()> {
+    if api.config(db).intersection_conflict != IntersectionConflict::Error {
+        return Ok(());
+    }
+
+    let conflicts = intersection_conflicts(db, api);
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    for (owner, conflict) in &conflicts {
+        tracing::error!(%owner, "incompatible intersection: {conflict}");
+    }
+    bail!("found {} incompatible intersections", conflicts.len())
+}
+
+/// Fails if several operations would generate methods with the same name,
+/// which would otherwise overwrite each other.
+fn check_name_collisions(db: &Database, api: InputApi) -> Result<()> {
+    let collisions = name_collisions(db, api);
+    if collisions.is_empty() {
+        return Ok(());
+    }
+    for (name, operations) in &collisions {
+        tracing::error!(
+            %name,
+            "generated for each of {}, rename all but one with an operation override",
+            operations.join(", ")
+        );
+    }
+    bail!("found {} colliding method names", collisions.len())
+}
+
+/// Prints the sizes of the generated TypeScript as a table.
+fn print_report(db: &Database, api: InputApi) {
+    let entries = ts_size_report(db, api);
+    let width = entries
+        .iter()
+        .map(|entry| entry.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("name".len());
+
+    eprintln!(
+        "{:<9}  {:<width$}  {:>6}  {:>10}  {:>10}",
+        "kind", "name", "lines", "characters", "references"
+    );
+    for entry in &entries {
+        eprintln!(
+            "{:<9}  {:<width$}  {:>6}  {:>10}  {:>10}",
+            entry.kind, entry.name, entry.lines, entry.characters, entry.references
+        );
+    }
+}
+
+fn print_query_trace(trace: &QueryTrace) {
+    let mut queries: Vec<_> = trace.queries.iter().collect();
+    queries.sort_by(|(_, a), (_, b)| b.time.cmp(&a.time));
+    let width = queries
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max("query".len());
+
+    eprintln!("{:<width$}  {:>10}  {:>10}", "query", "executions", "time");
+    for (name, stats) in queries {
+        eprintln!(
+            "{:<width$}  {:>10}  {:>10}",
+            name,
+            stats.executions,
+            format!("{:.2?}", stats.time)
+        );
+    }
+    for query in &trace.blocked {
+        eprintln!("blocked on {query}");
+    }
+}
+
+/// Prints the queries executed before a panic, such as the one salsa raises
+/// when a query depends on itself, so the cycle can be read off the trace.
+fn trace_query_cycles(trace: Arc<Mutex<QueryTrace>>) {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Ok(trace) = trace.try_lock() {
+            eprintln!("queries executed before the panic, most recent last:");
+            for query in &trace.recent {
+                eprintln!("  {query}");
+            }
+        }
+        hook(info);
+    }));
+}
+
+/// Compares the names generated for the API with those recorded in the lock
+/// file at `path`, failing if any were changed or removed, or with
+/// `deny_breaking` if any change breaks existing callers. The lock file is
+/// written if it is missing or out of date, or if `update` is set.
+fn check_lock(
+    db: &Database,
+    api: InputApi,
+    path: &Utf8Path,
+    update: bool,
+    deny_breaking: bool,
+) -> Result<()> {
+    let lock = Lock::new(db, api);
+
+    if path.exists() && !update {
+        let previous: Lock = toml::from_str(&std::fs::read_to_string(path)?)
+            .wrap_err_with(|| format!("parsing lock in {path:?}"))?;
+        if previous == lock {
+            return Ok(());
+        }
+
+        let changes = if deny_breaking {
+            previous.breaking_changes(&lock)
+        } else {
+            previous.changes(&lock)
+        };
+        if !changes.is_empty() {
+            for change in &changes {
+                tracing::error!("{change}");
+            }
+            bail!(
+                "{} incompatible changes since {path:?}, rerun with --update-lock to accept",
+                changes.len()
+            );
+        }
+    }
+
+    tracing::info!(?path, "writing lock");
+    std::fs::write(path, toml::to_string(&lock)?)?;
+
+    Ok(())
+}
+
+/// Reads a TOML, YAML, or JSON file, picking the format from the file extension.
+fn read_structured<T: serde::de::DeserializeOwned>(path: &Utf8Path) -> Result<T> {
+    let text = std::fs::read_to_string(path)?;
+    match path.extension() {
+        Some("yaml" | "yml") => Ok(serde_yaml::from_str(&text)?),
+        Some("toml") => Ok(toml::from_str(&text)?),
+        _ => Ok(serde_json::from_str(&text)?),
+    }
+}
+
+/// The sources of every file below `dir`, keyed by their path relative to it
+/// with `/` as the separator.
+fn read_templates(dir: &Utf8Path) -> Result<BTreeMap<String, String>> {
+    let mut templates = BTreeMap::new();
+    let mut pending = vec![dir.to_owned()];
+    while let Some(current) = pending.pop() {
+        for entry in current.read_dir_utf8()? {
+            let path = entry?.into_path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                let name = path.strip_prefix(dir)?.as_str().replace('\\', "/");
+                templates.insert(name, std::fs::read_to_string(&path)?);
+            }
+        }
+    }
+    Ok(templates)
+}
+
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got {s:?}"))
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SchemaNames {
+    /// Remove the separators and uppercase the letters following them, as in
+    /// "PageSearchResult", numbering names already taken by another schema.
+    Sanitize,
+    /// Inline the schemas where they are referenced instead of declaring
+    /// them, unless they reference themselves.
+    Inline,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum Target {
     #[value(name = "ts")]
     TypeScript,
+    #[value(name = "md")]
+    Markdown,
+    /// A Mermaid flowchart of the references between operations and types.
+    Mermaid,
+    /// A Graphviz graph of the references between operations and types.
+    Dot,
+    /// Pydantic models and an async client using httpx.
+    #[value(name = "py")]
+    Python,
+    /// Serde types and an async client using reqwest.
+    #[value(name = "rs")]
+    Rust,
+    /// Files rendered from the Jinja templates given by --template.
+    Template,
 }
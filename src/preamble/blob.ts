@@ -0,0 +1,71 @@
+export const requestBlob = (
+  method: Method,
+  url: string,
+  body: unknown,
+  options: ApiOptions = {}
+): {
+  data: Promise<Blob>;
+  cancel: (reason?: string) => void;
+  /**
+   * Resolves to an object URL of the response, usable as the `src` of an
+   * `<img>`. Release it with `URL.revokeObjectURL` once no longer shown.
+   */
+  objectUrl: () => Promise<string>;
+  /** Resolves to the raw bytes of the response. */
+  arrayBuffer: () => Promise<ArrayBuffer>;
+  /**
+   * Resolves to the file name of a download, as given by the
+   * `Content-Disposition` header, if any.
+   */
+  filename: Promise<string | undefined>;
+} => {
+  let inFlight = true;
+  const controller = new AbortController();
+  const response = (options.fetch ?? fetch)(`${getApiBase(options)}${url}`, {
+    method: method.toUpperCase(),
+    body: typeof body != "undefined" ? stringifyJson(body) : void 0,
+    signal: controller.signal,
+    headers: {
+      ...optionHeaders(options),
+      ...(typeof body != "undefined"
+        ? { "Content-Type": "application/json" }
+        : {}),
+    },
+  });
+  const data = response.then(async (res) => {
+    inFlight = false;
+    if (res.ok) return res.blob();
+    throw res.text();
+  });
+
+  return {
+    data,
+    cancel: (reason) => {
+      if (inFlight) controller.abort(reason);
+    },
+    objectUrl: () => data.then((blob) => URL.createObjectURL(blob)),
+    arrayBuffer: () => data.then((blob) => blob.arrayBuffer()),
+    filename: response.then(
+      (res) => dispositionFilename(res.headers.get("Content-Disposition")),
+      () => undefined
+    ),
+  };
+};
+
+/**
+ * The file name of a `Content-Disposition` header, preferring the UTF-8
+ * encoded `filename*` parameter over `filename`.
+ */
+const dispositionFilename = (header: string | null): string | undefined => {
+  if (header == null) return undefined;
+  const encoded = /filename\*\s*=\s*UTF-8''([^;]+)/i.exec(header);
+  if (encoded) {
+    try {
+      return decodeURIComponent(encoded[1].trim());
+    } catch (_) {
+      // NOTE: Malformed encodings fall back to the plain parameter.
+    }
+  }
+  const plain = /filename\s*=\s*("([^"]*)"|[^;]+)/i.exec(header);
+  return plain ? (plain[2] ?? plain[1]).trim() : undefined;
+};
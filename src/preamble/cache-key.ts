@@ -0,0 +1,17 @@
+/**
+ * Serializes the request identified by the arguments, with object keys in a
+ * stable order so equal arguments always produce equal keys.
+ */
+export const cacheKey = (
+  method: Method,
+  path: string,
+  params?: object,
+  query?: object
+): string =>
+  JSON.stringify([method, path, params ?? {}, query ?? {}], (_, value) =>
+    value && typeof value == "object" && !Array.isArray(value)
+      ? Object.fromEntries(
+          Object.entries(value).sort(([a], [b]) => (a < b ? -1 : a > b ? 1 : 0))
+        )
+      : value
+  );
@@ -0,0 +1,96 @@
+from __future__ import annotations
+
+import csv
+import io
+import json
+from enum import Enum
+from typing import (
+    Any,
+    AsyncIterator,
+    Generic,
+    Literal,
+    NoReturn,
+    Optional,
+    TypeVar,
+    Union,
+)
+from urllib.parse import quote
+
+import httpx
+from pydantic import BaseModel, ConfigDict, Field, TypeAdapter
+
+
+def _encode(ty: Any, value: Any) -> Any:
+    return TypeAdapter(ty).dump_python(
+        value, mode="json", by_alias=True, exclude_none=True
+    )
+
+
+def _decode(ty: Any, value: Any) -> Any:
+    return TypeAdapter(ty).validate_python(value)
+
+
+def _header(value: Any) -> str:
+    if isinstance(value, bool):
+        return "true" if value else "false"
+    return str(value)
+
+
+def _csv(text: str) -> list[dict[str, str]]:
+    return list(csv.DictReader(io.StringIO(text)))
+
+
+class _Client:
+    def __init__(
+        self,
+        base_url: str = "",
+        *,
+        client: Optional[httpx.AsyncClient] = None,
+        headers: Optional[dict[str, str]] = None,
+    ) -> None:
+        self._base_url = base_url.rstrip("/")
+        self._client = client or httpx.AsyncClient()
+        self._headers = headers or {}
+
+    def _build(
+        self,
+        method: str,
+        path: str,
+        *,
+        params: Optional[dict[str, Any]] = None,
+        body: Any = None,
+        form: Any = None,
+        headers: Optional[dict[str, Any]] = None,
+    ) -> httpx.Request:
+        return self._client.build_request(
+            method,
+            self._base_url + path,
+            params={k: v for k, v in (params or {}).items() if v is not None},
+            json=body,
+            data=form,
+            headers={
+                **self._headers,
+                **{k: _header(v) for k, v in (headers or {}).items() if v is not None},
+            },
+        )
+
+    async def _request(self, method: str, path: str, **kwargs: Any) -> httpx.Response:
+        res = await self._client.send(self._build(method, path, **kwargs))
+        res.raise_for_status()
+        return res
+
+    async def _sse(self, method: str, path: str, **kwargs: Any) -> AsyncIterator[str]:
+        request = self._build(method, path, **kwargs)
+        request.headers["Accept"] = "text/event-stream"
+        res = await self._client.send(request, stream=True)
+        try:
+            res.raise_for_status()
+            data: list[str] = []
+            async for line in res.aiter_lines():
+                if line.startswith("data:"):
+                    data.append(line[5:].removeprefix(" "))
+                elif not line and data:
+                    yield "\n".join(data)
+                    data = []
+        finally:
+            await res.aclose()
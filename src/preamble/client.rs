@@ -0,0 +1,72 @@
+#![allow(clippy::all, dead_code, unused_variables)]
+
+/// A client for the API, making requests with `reqwest`.
+#[derive(Debug, Clone)]
+pub struct Api {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl Api {
+    pub fn new(base_url: impl Into<String>) -> Api {
+        Api::with_client(reqwest::Client::new(), base_url)
+    }
+
+    /// Creates a client making requests with the given `reqwest` client, for
+    /// example one configured with default headers.
+    pub fn with_client(client: reqwest::Client, base_url: impl Into<String>) -> Api {
+        Api {
+            client,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+/// The query pairs of a parameter, repeating the name for every item of an
+/// array and leaving out `None`.
+fn query_pairs(name: &'static str, value: &impl serde::Serialize) -> Vec<(&'static str, String)> {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| Some((name, text(item)?)))
+            .collect(),
+        Ok(value) => text(&value).map(|text| (name, text)).into_iter().collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// The headers of header parameters given as pairs by [`query_pairs`],
+/// leaving out names and values which aren't valid in a header.
+fn header_map(pairs: &[(&'static str, String)]) -> reqwest::header::HeaderMap {
+    pairs
+        .iter()
+        .filter_map(|(name, value)| {
+            Some((
+                reqwest::header::HeaderName::from_bytes(name.as_bytes()).ok()?,
+                reqwest::header::HeaderValue::from_str(value).ok()?,
+            ))
+        })
+        .collect()
+}
+
+/// A parameter as a percent encoded path segment.
+fn path_segment(value: &impl serde::Serialize) -> String {
+    let value = serde_json::to_value(value).ok();
+    let text = value.as_ref().and_then(text).unwrap_or_default();
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
@@ -0,0 +1,165 @@
+/* eslint-disable @typescript-eslint/no-unused-vars */
+
+type Method = "DELETE" | "GET" | "PUT" | "POST" | "HEAD" | "TRACE" | "PATCH";
+
+let GLOBAL_API_BASE = "";
+export const getApiBase = (options?: ApiOptions) =>
+  options?.apiBase ?? GLOBAL_API_BASE;
+export const setGlobalApiBase = (apiBase: string) =>
+  (GLOBAL_API_BASE = apiBase);
+
+export type ApiOptions = {
+  fetch?: typeof fetch;
+  apiBase?: string;
+  headers?: Record<string, string>;
+  /** Sent as the `Accept-Language` header, for APIs with localized responses. */
+  locale?: string;
+  /**
+   * Returns the CSRF token sent with mutating requests, for clients generated
+   * with --csrf-header. See `csrfFromCookie` and `csrfFromMeta`.
+   */
+  csrfToken?: () => string | undefined;
+  /**
+   * Follows the redirects of operations documenting them, resolving with the
+   * final URL instead of the `Location` header of the first redirect.
+   */
+  followRedirects?: boolean;
+} & PlatformOptions;
+
+/** Parses a JSON body, with the configured reviver, if any. */
+export const parseJson = (text: string): unknown =>
+  JSON.parse(text, JSON_REVIVER);
+/** Serializes a JSON body, with the configured replacer, if any. */
+export const stringifyJson = (value: unknown): string =>
+  JSON.stringify(value, JSON_REPLACER);
+
+/** How a query parameter is serialized, per its `style` and `explode`. */
+type QueryStyle = {
+  style: "form" | "spaceDelimited" | "pipeDelimited" | "deepObject";
+  explode: boolean;
+};
+
+const FORM_EXPLODE: QueryStyle = { style: "form", explode: true };
+const QUERY_DELIMITERS = { form: ",", spaceDelimited: " ", pipeDelimited: "|" };
+
+const queryValue = (value: unknown): string =>
+  value === null
+    ? ""
+    : value instanceof Date
+    ? value.toISOString()
+    : typeof value == "object"
+    ? stringifyJson(value)
+    : String(value);
+
+const appendQuery = (
+  params: URLSearchParams,
+  name: string,
+  value: unknown,
+  { style, explode }: QueryStyle
+) => {
+  if (typeof value == "undefined") return;
+  if (Array.isArray(value)) {
+    if (explode || style == "deepObject")
+      for (const item of value) params.append(name, queryValue(item));
+    else
+      params.append(
+        name,
+        value.map(queryValue).join(QUERY_DELIMITERS[style])
+      );
+  } else if (
+    typeof value == "object" &&
+    value !== null &&
+    !(value instanceof Date)
+  ) {
+    const entries = Object.entries(value).filter(
+      ([, item]) => typeof item != "undefined"
+    );
+    if (style == "deepObject")
+      for (const [key, item] of entries)
+        appendQuery(params, `${name}[${key}]`, item, { style, explode });
+    else if (explode)
+      for (const [key, item] of entries)
+        appendQuery(params, key, item, FORM_EXPLODE);
+    else
+      params.append(
+        name,
+        entries
+          .flatMap(([key, item]) => [key, queryValue(item)])
+          .join(QUERY_DELIMITERS[style])
+      );
+  } else {
+    params.append(name, queryValue(value));
+  }
+};
+
+/**
+ * The query string of the parameters, leaving out those which are
+ * `undefined` unless they have a default. Parameters are serialized as
+ * `form` and exploded unless given another style: arrays repeat the name,
+ * and the properties of objects become parameters of their own.
+ */
+export const queryString = (
+  query: Record<string, unknown> = {},
+  defaults: Record<string, unknown> = {},
+  styles: Record<string, QueryStyle> = {}
+): string => {
+  const params = new URLSearchParams();
+  const names = new Set([...Object.keys(defaults), ...Object.keys(query)]);
+  for (const name of names) {
+    const value =
+      typeof query[name] != "undefined" ? query[name] : defaults[name];
+    appendQuery(params, name, value, styles[name] ?? FORM_EXPLODE);
+  }
+  return params.toString();
+};
+
+const optionHeaders = (options?: ApiOptions): Record<string, string> => ({
+  ...platformHeaders(options),
+  ...(typeof options?.locale != "undefined"
+    ? { "Accept-Language": options.locale }
+    : {}),
+  ...options?.headers,
+});
+
+export const requestPlain = (
+  method: Method,
+  url: string,
+  body?: unknown,
+  options?: ApiOptions
+): {
+  data: Promise<string>;
+  cancel: (reason?: string) => void;
+} => {
+  let inFlight = true;
+  const controller = new AbortController();
+  const data = (options?.fetch ?? fetch)(`${getApiBase(options)}${url}`, {
+    method: method.toUpperCase(),
+    body: typeof body != "undefined" ? stringifyJson(body) : void 0,
+    signal: controller.signal,
+    headers: {
+      ...optionHeaders(options),
+      ...(typeof body != "undefined"
+        ? { "Content-Type": "application/json" }
+        : {}),
+    },
+  }).then(async (res) => {
+    inFlight = false;
+    if (res.ok) {
+      const text = await res.text();
+      try {
+        return text;
+      } catch (_) {
+        throw text;
+      }
+    } else {
+      throw res.text();
+    }
+  });
+
+  return {
+    data,
+    cancel: (reason) => {
+      if (inFlight) controller.abort(reason);
+    },
+  };
+};
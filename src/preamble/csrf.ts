@@ -0,0 +1,24 @@
+/** Reads the CSRF token from the cookie with the given name. */
+export const csrfFromCookie =
+  (name: string) => (): string | undefined => {
+    const prefix = `${encodeURIComponent(name)}=`;
+    const cookie = document.cookie
+      .split("; ")
+      .find((cookie) => cookie.startsWith(prefix));
+    return cookie ? decodeURIComponent(cookie.slice(prefix.length)) : void 0;
+  };
+
+/** Reads the CSRF token from the `content` of the meta tag with the given name. */
+export const csrfFromMeta =
+  (name: string) => (): string | undefined =>
+    document.querySelector<HTMLMetaElement>(`meta[name="${name}"]`)?.content ??
+    void 0;
+
+export const withCsrf = (
+  header: string,
+  options: ApiOptions = {}
+): ApiOptions => {
+  const token = options.csrfToken?.();
+  if (typeof token == "undefined") return options;
+  return { ...options, headers: { ...options.headers, [header]: token } };
+};
@@ -0,0 +1,71 @@
+export type CsvColumn = "string" | "number" | "boolean";
+
+const parseCsv = (text: string): string[][] => {
+  const rows: string[][] = [];
+  let row: string[] = [];
+  let field = "";
+  let quoted = false;
+  for (let i = 0; i < text.length; i++) {
+    const c = text[i];
+    if (quoted) {
+      if (c != '"') field += c;
+      else if (text[i + 1] == '"') field += text[++i];
+      else quoted = false;
+    } else if (c == '"') {
+      quoted = true;
+    } else if (c == ",") {
+      row.push(field);
+      field = "";
+    } else if (c == "\n" || c == "\r") {
+      if (c == "\r" && text[i + 1] == "\n") i++;
+      row.push(field);
+      rows.push(row);
+      row = [];
+      field = "";
+    } else {
+      field += c;
+    }
+  }
+  if (field || row.length > 0) {
+    row.push(field);
+    rows.push(row);
+  }
+  return rows;
+};
+
+/**
+ * Requests a CSV document with a header row, converting the values of every
+ * following row as given by `columns`. Empty values of columns which are not
+ * strings are left out.
+ */
+export const requestCsv = <T>(
+  method: Method,
+  url: string,
+  body: unknown,
+  options: ApiOptions = {},
+  columns: Record<string, CsvColumn>
+): {
+  data: Promise<T[]>;
+  cancel: (reason?: string) => void;
+} => {
+  const { data, cancel } = requestPlain(method, url, body, options);
+  return {
+    data: data.then((text) => {
+      const [header = [], ...rows] = parseCsv(text);
+      return rows.map(
+        (row) =>
+          Object.fromEntries(
+            header.flatMap((name, i) => {
+              const value = row[i] ?? "";
+              const column = columns[name] ?? "string";
+              if (value == "" && column != "string") return [];
+              if (column == "number") return [[name, Number(value)]];
+              if (column == "boolean") return [[name, value == "true"]];
+              return [[name, value]];
+            })
+          ) as T
+      );
+    }),
+    cancel,
+  };
+};
@@ -0,0 +1,53 @@
+export type Encoder = {
+  mediaType: string;
+  encode: (value: unknown) => BodyInit;
+};
+export type Decoder = {
+  mediaType: string;
+  decode: (response: Response) => Promise<unknown>;
+};
+
+/**
+ * Like `requestPlain`, but with the body encoded and the response decoded by
+ * the given hooks.
+ */
+export const requestCustom = <T>(
+  method: Method,
+  url: string,
+  body: unknown,
+  options: ApiOptions = {},
+  encoder: Encoder,
+  decoder: Decoder
+): {
+  data: Promise<T>;
+  cancel: (reason?: string) => void;
+} => {
+  let inFlight = true;
+  const controller = new AbortController();
+  const encoded = typeof body != "undefined" ? encoder.encode(body) : void 0;
+  const data = (options.fetch ?? fetch)(`${getApiBase(options)}${url}`, {
+    method: method.toUpperCase(),
+    body: encoded,
+    signal: controller.signal,
+    headers: {
+      Accept: decoder.mediaType,
+      ...optionHeaders(options),
+      // NOTE: `fetch` sets the content type of form data itself, as it
+      // includes the boundary between the parts.
+      ...(typeof encoded != "undefined" && !(encoded instanceof FormData)
+        ? { "Content-Type": encoder.mediaType }
+        : {}),
+    },
+  }).then(async (res) => {
+    inFlight = false;
+    if (res.ok) return (await decoder.decode(res)) as T;
+    throw res.text();
+  });
+
+  return {
+    data,
+    cancel: (reason) => {
+      if (inFlight) controller.abort(reason);
+    },
+  };
+};
@@ -0,0 +1,48 @@
+const IN_FLIGHT = new Map<
+  string,
+  {
+    request: { data: Promise<unknown>; cancel: (reason?: string) => void };
+    waiting: number;
+  }
+>();
+
+/**
+ * Shares a single in-flight request between all identical calls made before
+ * it completes. The request is only cancelled once every caller cancels.
+ */
+export const dedupe = <T>(
+  method: Method,
+  url: string,
+  options: ApiOptions | undefined,
+  request: () => { data: Promise<T>; cancel: (reason?: string) => void }
+): { data: Promise<T>; cancel: (reason?: string) => void } => {
+  const key = `${method} ${getApiBase(options)}${url}`;
+
+  let entry = IN_FLIGHT.get(key);
+  if (!entry) {
+    const created = { request: request(), waiting: 0 };
+    created.request.data
+      .finally(() => {
+        if (IN_FLIGHT.get(key) === created) IN_FLIGHT.delete(key);
+      })
+      .catch(() => {});
+    IN_FLIGHT.set(key, created);
+    entry = created;
+  }
+
+  const shared = entry;
+  shared.waiting += 1;
+  let cancelled = false;
+  return {
+    data: shared.request.data as Promise<T>,
+    cancel: (reason) => {
+      if (cancelled) return;
+      cancelled = true;
+      shared.waiting -= 1;
+      if (shared.waiting == 0) {
+        IN_FLIGHT.delete(key);
+        shared.request.cancel(reason);
+      }
+    },
+  };
+};
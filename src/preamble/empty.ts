@@ -0,0 +1,25 @@
+export const requestEmpty = (
+  method: Method,
+  url: string,
+  body?: unknown,
+  options: ApiOptions = {}
+): {
+  data: Promise<Record<string, never>>;
+  cancel: (reason?: string) => void;
+} => {
+  const { data, cancel } = requestPlain(method, url, body, options);
+  return { data: data.then(() => ({})), cancel };
+};
+
+export const requestVoid = (
+  method: Method,
+  url: string,
+  body?: unknown,
+  options: ApiOptions = {}
+): {
+  data: Promise<void>;
+  cancel: (reason?: string) => void;
+} => {
+  const { data, cancel } = requestPlain(method, url, body, options);
+  return { data: data.then(() => {}), cancel };
+};
@@ -0,0 +1,42 @@
+/**
+ * The error rejecting requests whose response isn't successful, holding the
+ * status and the body parsed as JSON, or the text if it isn't JSON. `E` is
+ * the union of the error bodies documented for the operation, such as
+ * `GetUserError`.
+ */
+export class ApiError<E = unknown> extends Error {
+  constructor(
+    public readonly status: number,
+    public readonly body: E,
+    public readonly text: string
+  ) {
+    super(`request failed with status ${status}`);
+    this.name = "ApiError";
+  }
+}
+
+/**
+ * Whether the error rejected a request with a response which isn't
+ * successful, narrowing its body to the given error type.
+ */
+export const isApiError = <E = unknown>(error: unknown): error is ApiError<E> =>
+  error instanceof ApiError;
+
+export const withErrors = (options: ApiOptions = {}): ApiOptions => {
+  const baseFetch = options.fetch ?? fetch;
+  return {
+    ...options,
+    fetch: async (input, init) => {
+      const res = await baseFetch(input, init);
+      if (res.ok) return res;
+      const text = await res.text();
+      let body: unknown = text;
+      try {
+        body = parseJson(text);
+      } catch (_) {
+        // NOTE: Bodies which aren't JSON are kept as text.
+      }
+      throw new ApiError(res.status, body, text);
+    },
+  };
+};
@@ -0,0 +1,12 @@
+/**
+ * Combines the options a client was created with and those of a single call,
+ * with the latter taking precedence. Headers are merged.
+ */
+export const mergeOptions = (
+  defaults: ApiOptions,
+  options?: ApiOptions
+): ApiOptions => ({
+  ...defaults,
+  ...options,
+  headers: { ...defaults.headers, ...options?.headers },
+});
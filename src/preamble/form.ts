@@ -0,0 +1,18 @@
+/**
+ * Encodes an `application/x-www-form-urlencoded` body. Arrays are sent as a
+ * field per item and other objects as JSON. `null` and `undefined` are left
+ * out.
+ */
+export const formUrlEncoded = (body: unknown): URLSearchParams => {
+  const params = new URLSearchParams();
+  const append = (name: string, value: unknown) => {
+    if (value == null) return;
+    if (typeof value == "object") params.append(name, stringifyJson(value));
+    else params.append(name, String(value));
+  };
+  for (const [name, value] of Object.entries(body as Record<string, unknown>)) {
+    if (Array.isArray(value)) value.forEach((item) => append(name, item));
+    else append(name, value);
+  }
+  return params;
+};
@@ -0,0 +1,18 @@
+/**
+ * Sends the header parameters of an operation, leaving out those which are
+ * `undefined`. They take precedence over the headers of the options.
+ */
+export const withHeaders = (
+  headers: Record<string, unknown> = {},
+  options: ApiOptions = {}
+): ApiOptions => ({
+  ...options,
+  headers: {
+    ...options.headers,
+    ...Object.fromEntries(
+      Object.entries(headers)
+        .filter(([, value]) => typeof value != "undefined")
+        .map(([name, value]) => [name, String(value)])
+    ),
+  },
+});
@@ -0,0 +1,26 @@
+/**
+ * The error rejecting requests guarded by `If-Match` when the resource was
+ * modified since the etag was obtained.
+ */
+export class PreconditionFailed extends Error {
+  constructor(public readonly body: string) {
+    super("412 Precondition Failed");
+    this.name = "PreconditionFailed";
+  }
+}
+
+export const withIfMatch = (
+  etag: string,
+  options: ApiOptions = {}
+): ApiOptions => {
+  const baseFetch = options.fetch ?? fetch;
+  return {
+    ...options,
+    headers: { ...options.headers, "If-Match": etag },
+    fetch: async (input, init) => {
+      const res = await baseFetch(input, init);
+      if (res.status == 412) throw new PreconditionFailed(await res.text());
+      return res;
+    },
+  };
+};
@@ -0,0 +1,31 @@
+/**
+ * The error rejecting requests whose response does not match the documented
+ * type, when runtime validation is enabled.
+ */
+export class InvalidResponse extends Error {
+  constructor(public readonly body: string) {
+    super("response does not match the documented type");
+    this.name = "InvalidResponse";
+  }
+}
+
+export const requestJson = <T>(
+  method: Method,
+  url: string,
+  body?: unknown,
+  options: ApiOptions = {},
+  validate?: (data: unknown) => data is T
+): {
+  data: Promise<T>;
+  cancel: (reason?: string) => void;
+} => {
+  const { data, cancel } = requestPlain(method, url, body, options);
+  return {
+    data: data.then((text) => {
+      const parsed: unknown = parseJson(text);
+      if (validate && !validate(parsed)) throw new InvalidResponse(text);
+      return parsed as T;
+    }),
+    cancel,
+  };
+};
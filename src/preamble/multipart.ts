@@ -0,0 +1,19 @@
+/**
+ * Encodes a `multipart/form-data` body. Files are sent as is, arrays as a
+ * field per item, and other objects as JSON. `null` and `undefined` are left
+ * out.
+ */
+export const formData = (body: unknown): FormData => {
+  const form = new FormData();
+  const append = (name: string, value: unknown) => {
+    if (value == null) return;
+    if (value instanceof Blob) form.append(name, value);
+    else if (typeof value == "object") form.append(name, stringifyJson(value));
+    else form.append(name, String(value));
+  };
+  for (const [name, value] of Object.entries(body as Record<string, unknown>)) {
+    if (Array.isArray(value)) value.forEach((item) => append(name, item));
+    else append(name, value);
+  }
+  return form;
+};
@@ -0,0 +1,51 @@
+/**
+ * A queue of mutations persisted to `localStorage` while offline, and replayed
+ * in order once the browser is back online.
+ */
+export const createOfflineQueue = <M>(
+  storageKey: string,
+  execute: (mutation: M) => Promise<unknown>
+) => {
+  const storage = () =>
+    typeof localStorage != "undefined" ? localStorage : undefined;
+  const load = (): M[] => {
+    const stored = storage()?.getItem(storageKey);
+    return stored ? (JSON.parse(stored) as M[]) : [];
+  };
+  const save = (mutations: M[]) => {
+    if (mutations.length == 0) storage()?.removeItem(storageKey);
+    else storage()?.setItem(storageKey, JSON.stringify(mutations));
+  };
+  const isOnline = () =>
+    typeof navigator == "undefined" || navigator.onLine;
+
+  let flushing: Promise<void> | undefined;
+  const flush = (): Promise<void> =>
+    (flushing ??= (async () => {
+      try {
+        let pending = load();
+        while (pending.length > 0 && isOnline()) {
+          await execute(pending[0]);
+          // NOTE: Reloading picks up mutations enqueued while executing.
+          pending = load().slice(1);
+          save(pending);
+        }
+      } finally {
+        flushing = void 0;
+      }
+    })());
+
+  if (typeof window != "undefined")
+    window.addEventListener("online", () => void flush().catch(() => {}));
+
+  return {
+    /** Adds the mutation to the queue, and flushes it if online. */
+    enqueue: (mutation: M): Promise<void> => {
+      save([...load(), mutation]);
+      return isOnline() ? flush() : Promise.resolve();
+    },
+    flush,
+    /** The mutations not yet executed. */
+    pending: load,
+  };
+};
@@ -0,0 +1,10 @@
+/** The names of the `{name}` placeholders in the path template `P`. */
+export type ExtractParams<P extends string> =
+  P extends `${string}{${infer Param}}${infer Rest}`
+    ? Param | ExtractParams<Rest>
+    : never;
+
+/** An object with a value for every placeholder in the path template `P`. */
+export type PathParams<P extends string> = {
+  [K in ExtractParams<P>]: string;
+};
@@ -0,0 +1,5 @@
+export type PlatformOptions = Record<never, never>;
+
+const platformHeaders = (
+  _options?: PlatformOptions
+): Record<string, string> => ({});
@@ -0,0 +1,9 @@
+export type PlatformOptions = {
+  /** Sent as the `Cookie` header, as there is no cookie jar outside browsers. */
+  cookie?: string;
+};
+
+const platformHeaders = (
+  options?: PlatformOptions
+): Record<string, string> =>
+  typeof options?.cookie != "undefined" ? { Cookie: options.cookie } : {};
@@ -0,0 +1,39 @@
+export type PollOptions<T> = {
+  /** The time to wait after each response before requesting the next. */
+  intervalMs: number;
+  /** Stop polling after the first response for which this returns `true`. */
+  until?: (data: T) => boolean;
+  /** Stops polling, cancelling the request in flight. */
+  signal?: AbortSignal;
+};
+
+/** Repeats the request, yielding every response until stopped. */
+export async function* poll<T>(
+  request: () => { data: Promise<T>; cancel: (reason?: string) => void },
+  { intervalMs, until, signal }: PollOptions<T>
+): AsyncGenerator<T, void, undefined> {
+  while (!signal?.aborted) {
+    const { data, cancel } = request();
+    const abort = () => cancel("polling stopped");
+    signal?.addEventListener("abort", abort);
+    let value: T;
+    try {
+      value = await data;
+    } finally {
+      signal?.removeEventListener("abort", abort);
+    }
+
+    yield value;
+    if (until?.(value)) return;
+
+    await new Promise<void>((resolve) => {
+      const done = () => {
+        clearTimeout(timeout);
+        signal?.removeEventListener("abort", done);
+        resolve();
+      };
+      const timeout = setTimeout(done, intervalMs);
+      signal?.addEventListener("abort", done);
+    });
+  }
+}
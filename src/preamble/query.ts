@@ -0,0 +1,75 @@
+/** A query parameter of a URL missing or not matching its documented type. */
+export class QueryParseError extends Error {
+  constructor(readonly param: string, readonly value: string | null) {
+    super(
+      value == null
+        ? `missing query parameter ${JSON.stringify(param)}`
+        : `invalid query parameter ${JSON.stringify(param)}: ${JSON.stringify(
+            value
+          )}`
+    );
+  }
+}
+
+/** Converts the raw value of a query parameter, or `undefined` if invalid. */
+type QueryCoerce<T> = (value: string) => T | undefined;
+
+export const queryCoerce = {
+  string: (value: string): string | undefined => value,
+  number: (value: string): number | undefined => {
+    const number = Number(value);
+    return value.trim() != "" && !Number.isNaN(number) ? number : undefined;
+  },
+  boolean: (value: string): boolean | undefined =>
+    value == "true" ? true : value == "false" ? false : undefined,
+  literal:
+    <T extends string>(...values: T[]): QueryCoerce<T> =>
+    (value) =>
+      (values as string[]).includes(value) ? (value as T) : undefined,
+  json: <T>(value: string): T | undefined => {
+    try {
+      return JSON.parse(value) as T;
+    } catch (_) {
+      return undefined;
+    }
+  },
+};
+
+export function queryParam<T>(
+  searchParams: URLSearchParams,
+  name: string,
+  coerce: QueryCoerce<T>
+): T;
+export function queryParam<T>(
+  searchParams: URLSearchParams,
+  name: string,
+  coerce: QueryCoerce<T>,
+  optional: true
+): T | undefined;
+export function queryParam<T>(
+  searchParams: URLSearchParams,
+  name: string,
+  coerce: QueryCoerce<T>,
+  optional?: true
+): T | undefined {
+  const raw = searchParams.get(name);
+  if (raw == null) {
+    if (optional) return void 0;
+    throw new QueryParseError(name, raw);
+  }
+  const value = coerce(raw);
+  if (typeof value == "undefined") throw new QueryParseError(name, raw);
+  return value;
+}
+
+/** Every value of a repeated query parameter, which is empty if missing. */
+export const queryArray = <T>(
+  searchParams: URLSearchParams,
+  name: string,
+  coerce: QueryCoerce<T>
+): T[] =>
+  searchParams.getAll(name).map((raw) => {
+    const value = coerce(raw);
+    if (typeof value == "undefined") throw new QueryParseError(name, raw);
+    return value;
+  });
@@ -0,0 +1,58 @@
+let GLOBAL_RATE_LIMIT_RETRIES = 0;
+/**
+ * Retry rate limited requests up to `retries` times, waiting as long as the
+ * server asks to with `Retry-After`. By default they are rejected right away.
+ */
+export const setRateLimitRetries = (retries: number) =>
+  (GLOBAL_RATE_LIMIT_RETRIES = retries);
+
+/**
+ * The error rejecting requests answered with 429 Too Many Requests, carrying
+ * how long to wait before retrying if the server said so.
+ */
+export class RateLimitError extends Error {
+  constructor(public readonly retryAfterMs: number | undefined) {
+    super("429 Too Many Requests");
+    this.name = "RateLimitError";
+  }
+}
+
+const retryAfterMs = (res: Response): number | undefined => {
+  const header = res.headers.get("Retry-After");
+  if (header == null) return void 0;
+  const seconds = Number(header);
+  if (!Number.isNaN(seconds)) return seconds * 1000;
+  const date = Date.parse(header);
+  return Number.isNaN(date) ? void 0 : Math.max(0, date - Date.now());
+};
+
+const sleep = (ms: number, signal?: AbortSignal | null) =>
+  new Promise<void>((resolve, reject) => {
+    if (signal?.aborted) return reject(signal.reason);
+    const abort = () => {
+      clearTimeout(timeout);
+      reject(signal?.reason);
+    };
+    const timeout = setTimeout(() => {
+      signal?.removeEventListener("abort", abort);
+      resolve();
+    }, ms);
+    signal?.addEventListener("abort", abort, { once: true });
+  });
+
+export const withRateLimit = (options: ApiOptions = {}): ApiOptions => {
+  const baseFetch = options.fetch ?? fetch;
+  return {
+    ...options,
+    fetch: async (input, init) => {
+      for (let attempt = 0; ; attempt++) {
+        const res = await baseFetch(input, init);
+        if (res.status != 429) return res;
+        const wait = retryAfterMs(res);
+        if (attempt >= GLOBAL_RATE_LIMIT_RETRIES || typeof wait == "undefined")
+          throw new RateLimitError(wait);
+        await sleep(wait, init?.signal);
+      }
+    },
+  };
+};
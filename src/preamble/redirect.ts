@@ -0,0 +1,56 @@
+export const requestRedirect = (
+  method: Method,
+  url: string,
+  body: unknown,
+  options: ApiOptions = {}
+): {
+  /**
+   * Resolves to the URL given by the `Location` header of the redirect,
+   * which is not followed unless `followRedirects` is set.
+   */
+  data: Promise<{ redirectedTo: string }>;
+  cancel: (reason?: string) => void;
+} => {
+  let inFlight = true;
+  const controller = new AbortController();
+  const data = (options.fetch ?? fetch)(`${getApiBase(options)}${url}`, {
+    method: method.toUpperCase(),
+    body: typeof body != "undefined" ? stringifyJson(body) : void 0,
+    signal: controller.signal,
+    redirect: options.followRedirects ? "follow" : "manual",
+    headers: {
+      ...optionHeaders(options),
+      ...(typeof body != "undefined"
+        ? { "Content-Type": "application/json" }
+        : {}),
+    },
+  }).then(async (res) => {
+    inFlight = false;
+    // NOTE: Browsers hide the headers of redirects which are not followed.
+    if (res.type == "opaqueredirect")
+      throw new Error(
+        `the redirect of ${method.toUpperCase()} ${url} can't be read in a browser, set followRedirects to follow it instead`
+      );
+    const location = res.headers.get("Location");
+    if (res.status >= 300 && res.status < 400 && location != null)
+      return { redirectedTo: absoluteLocation(location, res.url) };
+    if (res.ok && res.redirected) return { redirectedTo: res.url };
+    throw res.text();
+  });
+
+  return {
+    data,
+    cancel: (reason) => {
+      if (inFlight) controller.abort(reason);
+    },
+  };
+};
+
+/** The `Location` header resolved against the URL of the response, if any. */
+const absoluteLocation = (location: string, base: string): string => {
+  try {
+    return new URL(location, base || void 0).href;
+  } catch (_) {
+    return location;
+  }
+};
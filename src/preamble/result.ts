@@ -0,0 +1,50 @@
+/**
+ * Like `requestJson`, but resolving with the status and body of every
+ * response, including those which aren't successful unless `rejectFailures`
+ * is set. Bodies which aren't JSON are passed through as text, and empty ones
+ * as `undefined`.
+ */
+export const requestResult = <R extends { status: number; data: unknown }>(
+  method: Method,
+  url: string,
+  body: unknown,
+  options: ApiOptions = {},
+  rejectFailures = false
+): {
+  data: Promise<R>;
+  cancel: (reason?: string) => void;
+} => {
+  let inFlight = true;
+  const controller = new AbortController();
+  const data = (options.fetch ?? fetch)(`${getApiBase(options)}${url}`, {
+    method: method.toUpperCase(),
+    body: typeof body != "undefined" ? stringifyJson(body) : void 0,
+    signal: controller.signal,
+    headers: {
+      ...optionHeaders(options),
+      ...(typeof body != "undefined"
+        ? { "Content-Type": "application/json" }
+        : {}),
+    },
+  }).then(async (res) => {
+    inFlight = false;
+    if (rejectFailures && !res.ok) throw res.text();
+    const text = await res.text();
+    let data: unknown = void 0;
+    if (text != "") {
+      try {
+        data = parseJson(text);
+      } catch (_) {
+        data = text;
+      }
+    }
+    return { status: res.status, data } as R;
+  });
+
+  return {
+    data,
+    cancel: (reason) => {
+      if (inFlight) controller.abort(reason);
+    },
+  };
+};
@@ -0,0 +1,83 @@
+export type SSEStream<T> = (
+  event:
+    | { type: "message"; data: T }
+    | {
+        type: "invalid";
+        /** The raw data of an event not matching the documented type. */
+        data: string;
+      }
+    | {
+        type: "error";
+        event: unknown;
+      }
+) => void;
+
+export const sse = <T>(
+  method: Method,
+  url: string,
+  body?: unknown,
+  options?: ApiOptions,
+  validate?: (data: unknown) => data is T
+): {
+  cancel: () => void;
+  listen: (stream: SSEStream<T>) => void;
+} => {
+  const controller = new AbortController();
+
+  let stream: SSEStream<T> | null = null;
+
+  const message = (data: string): Parameters<SSEStream<T>>[0] => {
+    if (!validate) return { type: "message", data: data as T };
+    try {
+      const parsed: unknown = parseJson(data);
+      if (validate(parsed)) return { type: "message", data: parsed };
+    } catch (_) {
+      // NOTE: Malformed JSON is reported like any other invalid event.
+    }
+    return { type: "invalid", data };
+  };
+
+  (options?.fetch ?? fetch)(`${getApiBase(options)}${url}`, {
+    method: method.toUpperCase(),
+    body: typeof body != "undefined" ? stringifyJson(body) : void 0,
+    signal: controller.signal,
+    headers: {
+      Accept: "text/event-stream",
+      ...optionHeaders(options),
+      ...(typeof body != "undefined"
+        ? { "Content-Type": "application/json" }
+        : {}),
+    },
+  })
+    .then(async (res) => {
+      if (!res.ok || !res.body) throw res;
+
+      const reader = res.body.pipeThrough(new TextDecoderStream()).getReader();
+      let buffer = "";
+      for (;;) {
+        const { done, value } = await reader.read();
+        if (done) break;
+
+        buffer += value;
+        const events = buffer.split(/\r?\n\r?\n/);
+        buffer = events.pop() ?? "";
+
+        for (const event of events) {
+          const data = event
+            .split(/\r?\n/)
+            .filter((line) => line.startsWith("data:"))
+            .map((line) => line.slice(5).replace(/^ /, ""))
+            .join("\n");
+          if (data) stream?.(message(data));
+        }
+      }
+    })
+    .catch((event) => {
+      if (!controller.signal.aborted) stream?.({ type: "error", event });
+    });
+
+  return {
+    cancel: () => controller.abort(),
+    listen: (newStream) => (stream = newStream),
+  };
+};
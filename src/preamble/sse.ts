@@ -0,0 +1,49 @@
+export type SSEStream<T> = (
+  event:
+    | { type: "message"; data: T }
+    | {
+        type: "invalid";
+        /** The raw data of an event not matching the documented type. */
+        data: string;
+      }
+    | {
+        type: "error";
+        event: Event;
+      }
+) => void;
+
+export const sse = <T>(
+  _method: Method,
+  url: string,
+  options?: ApiOptions,
+  validate?: (data: unknown) => data is T
+): {
+  cancel: () => void;
+  listen: (stream: SSEStream<T>) => void;
+} => {
+  const source = new EventSource(`${getApiBase(options)}${url}`);
+
+  let stream: SSEStream<T> | null = null;
+
+  const message = (data: string): Parameters<SSEStream<T>>[0] => {
+    if (!validate) return { type: "message", data: data as T };
+    try {
+      const parsed: unknown = parseJson(data);
+      if (validate(parsed)) return { type: "message", data: parsed };
+    } catch (_) {
+      // NOTE: Malformed JSON is reported like any other invalid event.
+    }
+    return { type: "invalid", data };
+  };
+
+  source.onmessage = (event) => {
+    stream?.(message(event.data));
+  };
+  source.onerror = (event) => {
+    stream?.({ type: "error", event });
+  };
+  return {
+    cancel: () => source.close(),
+    listen: (newStream) => (stream = newStream),
+  };
+};
@@ -0,0 +1,48 @@
+export type Metric = {
+  /** The name of the generated method. */
+  name: string;
+  method: Method;
+  /** The path template of the operation, without parameters filled in. */
+  path: string;
+  /** The response status, or `undefined` if the request failed. */
+  status: number | undefined;
+  durationMs: number;
+};
+
+let GLOBAL_ON_METRIC: ((metric: Metric) => void) | undefined;
+export const setOnMetric = (onMetric?: (metric: Metric) => void) =>
+  (GLOBAL_ON_METRIC = onMetric);
+
+export const withMetric = (
+  name: string,
+  method: Method,
+  path: string,
+  options: ApiOptions = {}
+): ApiOptions => {
+  const onMetric = GLOBAL_ON_METRIC;
+  if (!onMetric) return options;
+
+  const baseFetch = options.fetch ?? fetch;
+  return {
+    ...options,
+    fetch: async (input, init) => {
+      const start = performance.now();
+      const report = (status: number | undefined) =>
+        onMetric({
+          name,
+          method,
+          path,
+          status,
+          durationMs: performance.now() - start,
+        });
+      try {
+        const res = await baseFetch(input, init);
+        report(res.status);
+        return res;
+      } catch (error) {
+        report(undefined);
+        throw error;
+      }
+    },
+  };
+};
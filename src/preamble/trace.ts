@@ -0,0 +1,30 @@
+/** The W3C trace context of the span requests are made within. */
+export type TraceContext = {
+  /** 32 lowercase hexadecimal characters. */
+  traceId: string;
+  /** 16 lowercase hexadecimal characters. */
+  spanId: string;
+  sampled: boolean;
+  /** Sent as the `tracestate` header, if given. */
+  traceState?: string;
+};
+
+let GLOBAL_GET_TRACE_CONTEXT: (() => TraceContext | undefined) | undefined;
+export const setTraceContext = (
+  getTraceContext?: () => TraceContext | undefined
+) => (GLOBAL_GET_TRACE_CONTEXT = getTraceContext);
+
+export const withTrace = (options: ApiOptions = {}): ApiOptions => {
+  const context = GLOBAL_GET_TRACE_CONTEXT?.();
+  if (!context) return options;
+
+  const flags = context.sampled ? "01" : "00";
+  return {
+    ...options,
+    headers: {
+      traceparent: `00-${context.traceId}-${context.spanId}-${flags}`,
+      ...(context.traceState ? { tracestate: context.traceState } : {}),
+      ...options.headers,
+    },
+  };
+};
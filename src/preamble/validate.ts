@@ -0,0 +1,50 @@
+type Guard = (value: unknown) => boolean;
+
+/** Combinators for the type guards generated for runtime validation. */
+export const guards = {
+  string: (value: unknown) => typeof value == "string",
+  number: (value: unknown) => typeof value == "number",
+  boolean: (value: unknown) => typeof value == "boolean",
+  null: (value: unknown) => value === null,
+  literal:
+    (literal: string): Guard =>
+    (value) =>
+      value === literal,
+  array:
+    (items: Guard): Guard =>
+    (value) =>
+      Array.isArray(value) && value.every((item) => items(item)),
+  tuple:
+    (elements: Guard[]): Guard =>
+    (value) =>
+      Array.isArray(value) &&
+      value.length == elements.length &&
+      elements.every((element, i) => element(value[i])),
+  or:
+    (options: Guard[]): Guard =>
+    (value) =>
+      options.some((option) => option(value)),
+  and:
+    (options: Guard[]): Guard =>
+    (value) =>
+      options.every((option) => option(value)),
+  object:
+    (fields: Record<string, Guard>, optional: string[] = []): Guard =>
+    (value) =>
+      typeof value == "object" &&
+      value !== null &&
+      !Array.isArray(value) &&
+      Object.entries(fields).every(([name, field]) => {
+        const fieldValue = (value as Record<string, unknown>)[name];
+        return typeof fieldValue == "undefined"
+          ? optional.includes(name)
+          : field(fieldValue);
+      }),
+  record:
+    (values: Guard): Guard =>
+    (value) =>
+      typeof value == "object" &&
+      value !== null &&
+      !Array.isArray(value) &&
+      Object.values(value).every((item) => values(item)),
+};
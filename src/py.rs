@@ -0,0 +1,479 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use heck::{ToShoutySnakeCase, ToSnakeCase};
+use indent_write::indentable::Indentable;
+use itertools::Itertools;
+
+use crate::{
+    component_types, generic_declarations, lower_api, InputApi, Operation, Property, PyEnumStyle,
+    RequestKind, ResponseKind, Type, TypeKind,
+};
+
+/// A Python module with a pydantic model or type alias per component type, and
+/// an `Api` class with an async method per operation, making requests using
+/// httpx. Methods are named like those of the TypeScript client, but in snake
+/// case.
+#[salsa::tracked]
+pub fn generate_py(db: &dyn crate::Db, api: InputApi) -> String {
+    use std::fmt::Write;
+
+    let mut buf = String::new();
+
+    let info = &api.api(db).info;
+    let mut lines = vec![format!("{} ({})", info.title, info.version)];
+    let details = api.info_details(db);
+    if !details.is_empty() {
+        lines.push(String::new());
+        lines.extend(details);
+    }
+    writeln!(buf, "{}", docstring(&lines)).unwrap();
+    writeln!(buf).unwrap();
+    writeln!(buf, "{}", include_str!("./preamble/client.py")).unwrap();
+
+    let types = component_types(db, api);
+    let models = models(db, &types);
+    let mut declarations = Declarations {
+        db,
+        types: &types,
+        models: &models,
+        enum_style: api.config(db).target.python.enum_style,
+        declared: BTreeSet::new(),
+    };
+    for (name, ty) in &types {
+        declarations.declare(name, *ty, &mut buf);
+    }
+
+    // NOTE: Generic types are pydantic models generic over `T` if they are
+    // objects, and generic aliases otherwise.
+    let generics = generic_declarations(db, api);
+    if !generics.is_empty() {
+        writeln!(buf, "\n\nT = TypeVar(\"T\")").unwrap();
+    }
+    let mut generic_models = Vec::new();
+    for (name, ty) in &generics {
+        let declaration = match ty.kind(db) {
+            TypeKind::Object(fields) => {
+                generic_models.push(name);
+                let bases = ["BaseModel".to_string(), "Generic[T]".to_string()];
+                model_declaration(db, name, &bases, &fields)
+            }
+            _ => format!("{name} = {}", ty.py(db)),
+        };
+        write!(buf, "\n\n{declaration}\n").unwrap();
+    }
+
+    let operations = lower_api(db, api);
+    writeln!(buf, "\n\nclass Api(_Client):").unwrap();
+    if operations.is_empty() {
+        writeln!(buf, "    pass").unwrap();
+    }
+    for op in &operations {
+        writeln!(buf).unwrap();
+        writeln!(buf, "{}", op.py(db, api).indented("    ")).unwrap();
+    }
+
+    if !models.is_empty() || !generic_models.is_empty() {
+        writeln!(buf).unwrap();
+        writeln!(buf).unwrap();
+        for (name, _) in &types {
+            if models.contains_key(name) {
+                writeln!(buf, "{name}.model_rebuild()").unwrap();
+            }
+        }
+        for name in generic_models {
+            writeln!(buf, "{name}.model_rebuild()").unwrap();
+        }
+    }
+
+    tracing::info!(
+        "wrote {} types and {} operations",
+        types.len(),
+        operations.len()
+    );
+
+    buf
+}
+
+fn docstring(lines: &[String]) -> String {
+    let body = lines
+        .iter()
+        .map(|line| line.replace('\\', "\\\\").replace("\"\"\"", "\\\"\"\""))
+        .format("\n");
+    format!("\"\"\"{body}\n\"\"\"")
+}
+
+type Model = (Vec<String>, BTreeMap<String, Property>);
+
+/// The bases and fields of the component types declared as models, which are
+/// objects and intersections of objects and other models. The remaining types
+/// are declared as aliases.
+fn models(db: &dyn crate::Db, types: &[(String, Type)]) -> BTreeMap<String, Model> {
+    let mut models: BTreeMap<String, Model> = types
+        .iter()
+        .filter_map(|(name, ty)| Some((name.clone(), model_parts(db, *ty)?)))
+        .collect();
+    // NOTE: Removing a model may invalidate those inheriting from it, so this
+    // is repeated until nothing changes.
+    loop {
+        let invalid = models
+            .iter()
+            .filter(|(_, (bases, _))| bases.iter().any(|base| !models.contains_key(base)))
+            .map(|(name, _)| name.clone())
+            .collect_vec();
+        if invalid.is_empty() {
+            return models;
+        }
+        for name in invalid {
+            models.remove(&name);
+        }
+    }
+}
+
+/// The component types being declared, see [`Declarations::declare`].
+struct Declarations<'a> {
+    db: &'a dyn crate::Db,
+    types: &'a [(String, Type)],
+    models: &'a BTreeMap<String, Model>,
+    enum_style: PyEnumStyle,
+    declared: BTreeSet<String>,
+}
+
+impl Declarations<'_> {
+    /// Writes the declaration of a component type, preceded by those of its
+    /// bases, since Python requires base classes to be defined first.
+    fn declare(&mut self, name: &str, ty: Type, buf: &mut String) {
+        if !self.declared.insert(name.to_string()) {
+            return;
+        }
+
+        let (db, types, models) = (self.db, self.types, self.models);
+        let declaration = match models.get(name) {
+            Some((bases, fields)) => {
+                for base in bases {
+                    if let Some((_, ty)) = types.iter().find(|(name, _)| name == base) {
+                        self.declare(base, *ty, buf);
+                    }
+                }
+                model_declaration(db, name, bases, fields)
+            }
+            None => match ty.constants(db) {
+                Some(values) if self.enum_style == PyEnumStyle::Enum => {
+                    enum_declaration(name, &values)
+                }
+                _ => format!("{name} = {}", ty.py(db)),
+            },
+        };
+        buf.push_str("\n\n");
+        buf.push_str(&declaration);
+        buf.push('\n');
+    }
+}
+
+/// A subclass of `str` and `Enum` with a member per value, named in upper
+/// snake case.
+fn enum_declaration(name: &str, values: &[String]) -> String {
+    let mut members = BTreeSet::new();
+    let lines = values
+        .iter()
+        .map(|value| {
+            let mut member = value.to_shouty_snake_case();
+            if member.is_empty() || member.starts_with(|c: char| c.is_ascii_digit()) {
+                member = format!("VALUE_{member}");
+            }
+            let mut unique = member.clone();
+            let mut n = 2;
+            while !members.insert(unique.clone()) {
+                unique = format!("{member}_{n}");
+                n += 1;
+            }
+            format!("    {unique} = {value:?}")
+        })
+        .join("\n");
+    format!("class {name}(str, Enum):\n{lines}")
+}
+
+fn model_parts(db: &dyn crate::Db, ty: Type) -> Option<Model> {
+    match ty.kind(db) {
+        TypeKind::Object(properties) => Some((Vec::new(), properties)),
+        TypeKind::And(parts) => {
+            let mut bases = Vec::new();
+            let mut fields = BTreeMap::new();
+            for part in parts {
+                match part.kind(db) {
+                    TypeKind::Reference(name) => bases.push(name),
+                    TypeKind::Object(properties) => fields.extend(properties),
+                    // NOTE: Additional properties are ignored by pydantic
+                    // unless the model allows extra fields.
+                    TypeKind::Map(..) => {}
+                    _ => return None,
+                }
+            }
+            Some((bases, fields))
+        }
+        _ => None,
+    }
+}
+
+fn model_declaration(
+    db: &dyn crate::Db,
+    name: &str,
+    bases: &[String],
+    fields: &BTreeMap<String, Property>,
+) -> String {
+    let bases = if bases.is_empty() {
+        "BaseModel".to_string()
+    } else {
+        bases.join(", ")
+    };
+    let fields = fields
+        .iter()
+        .map(|(name, prop)| {
+            let field = identifier(name);
+            let ty = prop.ty.py(db);
+            match (prop.optional, &field == name) {
+                (false, true) => format!("{field}: {ty}"),
+                (false, false) => format!("{field}: {ty} = Field(alias={name:?})"),
+                (true, true) => format!("{field}: Optional[{ty}] = None"),
+                (true, false) => {
+                    format!("{field}: Optional[{ty}] = Field(default=None, alias={name:?})")
+                }
+            }
+        })
+        .collect_vec();
+
+    let mut body = vec!["model_config = ConfigDict(populate_by_name=True)".to_string()];
+    if !fields.is_empty() {
+        body.push(String::new());
+        body.extend(fields);
+    }
+    format!(
+        "class {name}({bases}):\n{}",
+        body.iter()
+            .map(|line| if line.is_empty() {
+                String::new()
+            } else {
+                format!("    {line}")
+            })
+            .format("\n")
+    )
+}
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// A snake case Python identifier for the name of a property or parameter.
+fn identifier(name: &str) -> String {
+    let ident = name.to_snake_case();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("field_{ident}")
+    } else if KEYWORDS.contains(&ident.as_str()) {
+        format!("{ident}_")
+    } else {
+        ident
+    }
+}
+
+/// A Python expression of a JSON value.
+fn literal(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "None".to_string(),
+        serde_json::Value::Bool(true) => "True".to_string(),
+        serde_json::Value::Bool(false) => "False".to_string(),
+        serde_json::Value::Number(number) => number.to_string(),
+        serde_json::Value::String(string) => format!("{string:?}"),
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            format!("json.loads({:?})", value.to_string())
+        }
+    }
+}
+
+impl Type {
+    /// The Python type annotation of the type. References to component types
+    /// are quoted, since they may be declared later in the module.
+    ///
+    /// Python has no structural object or intersection types, so those not
+    /// declared as components are typed as `dict[str, Any]` and `Any`.
+    fn py(self, db: &dyn crate::Db) -> String {
+        match self.kind(db) {
+            TypeKind::Reference(name) => format!("{name:?}"),
+            TypeKind::Object(_) => "dict[str, Any]".to_string(),
+            // NOTE: Keys are kept as strings, as they are in JSON.
+            TypeKind::Map(_, values) => format!("dict[str, {}]", values.py(db)),
+            TypeKind::Array(item) => format!("list[{}]", item.py(db)),
+            TypeKind::Tuple(elements) if elements.is_empty() => "tuple[()]".to_string(),
+            TypeKind::Tuple(elements) => {
+                format!("tuple[{}]", elements.iter().map(|ty| ty.py(db)).join(", "))
+            }
+            TypeKind::Or(options) if options.is_empty() => "NoReturn".to_string(),
+            TypeKind::Or(options) => match self.constants(db) {
+                Some(values) => format!(
+                    "Literal[{}]",
+                    values.iter().map(|value| format!("{value:?}")).join(", ")
+                ),
+                None => format!("Union[{}]", options.iter().map(|ty| ty.py(db)).join(", ")),
+            },
+            TypeKind::And(_) | TypeKind::Custom(_) | TypeKind::Unknown => "Any".to_string(),
+            // NOTE: The arguments are component types, quoted along with the
+            // generic type rather than on their own.
+            TypeKind::Generic(name, arguments) => format!(
+                "{:?}",
+                format!(
+                    "{name}[{}]",
+                    arguments
+                        .iter()
+                        .map(|ty| ty.py(db).trim_matches('"').to_string())
+                        .join(", ")
+                )
+            ),
+            TypeKind::Parameter(name) => name,
+            TypeKind::Number => "float".to_string(),
+            TypeKind::Ident(value) => format!("Literal[{value:?}]"),
+            TypeKind::String => "str".to_string(),
+            TypeKind::Boolean => "bool".to_string(),
+            TypeKind::Null => "None".to_string(),
+        }
+    }
+}
+
+impl Operation {
+    fn py(&self, db: &dyn crate::Db, api: InputApi) -> String {
+        let name = identifier(&self.name(db, api));
+
+        let body = match self.body {
+            Some(RequestKind::Json(body) | RequestKind::Form(body)) => Some(body),
+            None => None,
+            Some(RequestKind::Custom(_)) => {
+                return format!("# NOTE: `{name}` is left out, as its request body uses a codec");
+            }
+            Some(RequestKind::Multipart(_)) => {
+                return format!("# NOTE: `{name}` is left out, as its request body is multipart");
+            }
+        };
+        let (returns, decode) = match self.response {
+            Some(ResponseKind::Empty) => ("None".to_string(), "return None".to_string()),
+            Some(ResponseKind::Plain | ResponseKind::Xml) => {
+                ("str".to_string(), "return res.text".to_string())
+            }
+            Some(ResponseKind::Json(ty)) => (
+                ty.py(db),
+                format!("return _decode({}, res.json())", ty.py(db)),
+            ),
+            Some(ResponseKind::EventStream(ty)) => (
+                format!("AsyncIterator[{}]", ty.py(db)),
+                format!("yield _decode({}, json.loads(data))", ty.py(db)),
+            ),
+            Some(ResponseKind::Csv(row)) => (
+                format!("list[{}]", row.py(db)),
+                format!("return _decode(list[{}], _csv(res.text))", row.py(db)),
+            ),
+            Some(ResponseKind::Blob) => ("bytes".to_string(), "return res.content".to_string()),
+            Some(ResponseKind::Custom(_)) => {
+                return format!("# NOTE: `{name}` is left out, as its response uses a codec");
+            }
+            Some(ResponseKind::Redirect) => {
+                return format!("# NOTE: `{name}` is left out, as it responds with a redirect");
+            }
+            None => unreachable!("operations without a response are left out"),
+        };
+
+        let mut args = self
+            .path_params
+            .iter()
+            .map(|(param, ty)| format!("{}: {}", identifier(param), ty.py(db)))
+            .collect_vec();
+        if let Some(body) = body {
+            args.push(format!("body: {}", body.py(db)));
+        }
+        if self.if_match {
+            args.push("if_match: str".to_string());
+        }
+        let config = api.config(db);
+        for (param, query) in &self.query {
+            match &query.default {
+                Some(default) if config.fill_query_defaults => args.push(format!(
+                    "{}: {} = {}",
+                    identifier(param),
+                    query.ty.py(db),
+                    literal(default)
+                )),
+                _ if query.optional(&config) => args.push(format!(
+                    "{}: Optional[{}] = None",
+                    identifier(param),
+                    query.ty.py(db)
+                )),
+                _ => args.push(format!("{}: {}", identifier(param), query.ty.py(db))),
+            }
+        }
+        // NOTE: Header parameters are optional, as in the TypeScript client.
+        for (param, ty) in &self.headers {
+            args.push(format!(
+                "{}: Optional[{}] = None",
+                identifier(param),
+                ty.py(db)
+            ));
+        }
+
+        let mut path = self.path.clone();
+        for param in self.path_params.keys() {
+            path = path.replace(
+                &format!("{{{param}}}"),
+                &format!("{{quote(str({}), safe='')}}", identifier(param)),
+            );
+        }
+        let mut request = vec![format!("{:?}", self.method), format!("f{path:?}")];
+        if !self.query.is_empty() {
+            request.push(format!(
+                "params={{{}}}",
+                self.query
+                    .keys()
+                    .map(|param| format!("{param:?}: {}", identifier(param)))
+                    .join(", ")
+            ));
+        }
+        if let Some(body) = body {
+            let arg = match self.body {
+                Some(RequestKind::Form(_)) => "form",
+                _ => "body",
+            };
+            request.push(format!("{arg}=_encode({}, body)", body.py(db)));
+        }
+        let mut headers = self
+            .headers
+            .keys()
+            .map(|param| format!("{param:?}: {}", identifier(param)))
+            .collect_vec();
+        if self.if_match {
+            headers.insert(0, r#""If-Match": if_match"#.to_string());
+        }
+        if !headers.is_empty() {
+            request.push(format!("headers={{{}}}", headers.join(", ")));
+        }
+        let request = request.join(", ");
+
+        let signature = if args.is_empty() {
+            format!("async def {name}(self) -> {returns}:")
+        } else {
+            format!(
+                "async def {name}(self, *, {}) -> {returns}:",
+                args.iter().format(", ")
+            )
+        };
+        let body = match self.response {
+            Some(ResponseKind::EventStream(_)) => {
+                format!("async for data in self._sse({request}):\n    {decode}")
+            }
+            Some(ResponseKind::Empty) => format!("await self._request({request})\n{decode}"),
+            _ => format!("res = await self._request({request})\n{decode}"),
+        };
+        format!(
+            "{signature}\n    \"\"\"`{} {}`\"\"\"\n{}",
+            self.method,
+            self.path,
+            body.indented("    ")
+        )
+    }
+}
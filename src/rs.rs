@@ -0,0 +1,461 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use itertools::Itertools;
+
+use crate::{
+    component_types, generic_declarations, lower_api, InputApi, Operation, Property, RequestKind,
+    ResponseKind, Type, TypeKind,
+};
+
+/// A Rust module with a serde struct, enum, or type alias per component type,
+/// and an `Api` client with an async method per operation, making requests
+/// using reqwest. Methods are named like those of the TypeScript client, but in
+/// snake case.
+#[salsa::tracked]
+pub fn generate_rs(db: &dyn crate::Db, api: InputApi) -> String {
+    use std::fmt::Write;
+
+    let mut buf = String::new();
+
+    let info = &api.api(db).info;
+    writeln!(buf, "//! {} ({})", info.title, info.version).unwrap();
+    let details = api.info_details(db);
+    if !details.is_empty() {
+        writeln!(buf, "//!").unwrap();
+        for line in details {
+            writeln!(buf, "//! {line}").unwrap();
+        }
+    }
+    writeln!(buf).unwrap();
+    write!(buf, "{}", include_str!("./preamble/client.rs")).unwrap();
+
+    let types = component_types(db, api);
+    let ctx = Context {
+        cyclic: cyclic_types(db, &types),
+    };
+    for (name, ty) in &types {
+        writeln!(buf).unwrap();
+        writeln!(buf, "{}", ctx.type_declaration(db, name, &[], *ty)).unwrap();
+    }
+    for (name, ty) in generic_declarations(db, api) {
+        writeln!(buf).unwrap();
+        writeln!(buf, "{}", ctx.type_declaration(db, &name, &["T"], ty)).unwrap();
+    }
+
+    let operations = lower_api(db, api);
+    writeln!(buf).unwrap();
+    writeln!(buf, "impl Api {{").unwrap();
+    let methods = operations
+        .iter()
+        .map(|op| indent(&ctx.operation(db, api, op)))
+        .format("\n\n");
+    writeln!(buf, "{methods}").unwrap();
+    writeln!(buf, "}}").unwrap();
+
+    tracing::info!(
+        "wrote {} types and {} operations",
+        types.len(),
+        operations.len()
+    );
+
+    buf
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("    {line}")
+            }
+        })
+        .join("\n")
+}
+
+/// The component types which reference themselves, directly or through other
+/// components, and must be boxed where they are contained directly.
+fn cyclic_types(db: &dyn crate::Db, types: &[(String, Type)]) -> BTreeSet<String> {
+    let references: BTreeMap<&str, BTreeSet<String>> = types
+        .iter()
+        .map(|(name, ty)| (name.as_str(), ty.references(db)))
+        .collect();
+
+    types
+        .iter()
+        .filter(|(name, _)| {
+            let mut seen = BTreeSet::new();
+            let mut stack = references[name.as_str()].iter().collect_vec();
+            while let Some(next) = stack.pop() {
+                if next == name {
+                    return true;
+                }
+                if seen.insert(next) {
+                    stack.extend(references.get(next.as_str()).into_iter().flatten());
+                }
+            }
+            false
+        })
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+const DERIVES: &str = "#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]";
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// A snake case Rust identifier for the name of a property or parameter.
+fn field_name(name: &str) -> String {
+    let ident = name.to_snake_case();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("field_{ident}")
+    } else if matches!(ident.as_str(), "self" | "crate" | "super") {
+        // NOTE: These can't be raw identifiers
+        format!("{ident}_")
+    } else if KEYWORDS.contains(&ident.as_str()) {
+        format!("r#{ident}")
+    } else {
+        ident
+    }
+}
+
+/// An upper camel case Rust identifier for the name of a type or variant.
+fn type_name(name: &str) -> String {
+    let ident = name.to_upper_camel_case();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("V{ident}")
+    } else if ident == "Self" {
+        "Self_".to_string()
+    } else {
+        ident
+    }
+}
+
+/// Appends the index to names occurring more than once.
+fn dedupe(names: Vec<String>) -> Vec<String> {
+    let counts = names.iter().counts();
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            if counts[name] > 1 {
+                format!("{name}{i}")
+            } else {
+                name.clone()
+            }
+        })
+        .collect()
+}
+
+struct Context {
+    cyclic: BTreeSet<String>,
+}
+
+impl Context {
+    /// The Rust type of the type. `boxed` is set where the type is contained
+    /// directly, requiring references to cyclic types to be boxed.
+    ///
+    /// Rust has no structural object, union, or intersection types, so those
+    /// not declared as components are typed as `serde_json` values.
+    fn ty(&self, db: &dyn crate::Db, ty: Type, boxed: bool) -> String {
+        match ty.kind(db) {
+            TypeKind::Reference(name) if boxed && self.cyclic.contains(&name) => {
+                format!("Box<{}>", type_name(&name))
+            }
+            TypeKind::Reference(name) => type_name(&name),
+            TypeKind::Object(_) => "serde_json::Map<String, serde_json::Value>".to_string(),
+            // NOTE: Keys are kept as strings, as they are in JSON.
+            TypeKind::Map(_, values) => format!(
+                "std::collections::BTreeMap<String, {}>",
+                self.ty(db, values, false)
+            ),
+            TypeKind::Array(item) => format!("Vec<{}>", self.ty(db, item, false)),
+            TypeKind::Tuple(elements) => format!(
+                "({})",
+                elements
+                    .iter()
+                    .map(|ty| format!("{},", self.ty(db, *ty, boxed)))
+                    .join(" ")
+            ),
+            TypeKind::Or(options) => match nullable(db, &options) {
+                Some(inner) => format!("Option<{}>", self.ty(db, inner, boxed)),
+                None if ty.constants(db).is_some() => "String".to_string(),
+                None => "serde_json::Value".to_string(),
+            },
+            TypeKind::And(_) | TypeKind::Custom(_) | TypeKind::Unknown => {
+                "serde_json::Value".to_string()
+            }
+            TypeKind::Generic(name, arguments) => format!(
+                "{}<{}>",
+                type_name(&name),
+                arguments
+                    .iter()
+                    .map(|ty| self.ty(db, *ty, false))
+                    .join(", ")
+            ),
+            TypeKind::Parameter(name) => name,
+            TypeKind::Number => "f64".to_string(),
+            TypeKind::Ident(_) | TypeKind::String => "String".to_string(),
+            TypeKind::Boolean => "bool".to_string(),
+            TypeKind::Null => "()".to_string(),
+        }
+    }
+
+    fn fields(&self, db: &dyn crate::Db, properties: &BTreeMap<String, Property>) -> Vec<String> {
+        properties
+            .iter()
+            .map(|(name, prop)| {
+                let field = field_name(name);
+                let mut attrs = Vec::new();
+                if field.trim_start_matches("r#") != name {
+                    attrs.push(format!("rename = {name:?}"));
+                }
+                let ty = self.ty(db, prop.ty, true);
+                let ty = if prop.optional {
+                    attrs.push(r#"default, skip_serializing_if = "Option::is_none""#.to_string());
+                    format!("Option<{ty}>")
+                } else {
+                    ty
+                };
+                if attrs.is_empty() {
+                    format!("pub {field}: {ty},")
+                } else {
+                    format!("#[serde({})]\npub {field}: {ty},", attrs.join(", "))
+                }
+            })
+            .collect()
+    }
+
+    /// The declaration of a component type, or of a generic type with the
+    /// given type parameters.
+    fn type_declaration(
+        &self,
+        db: &dyn crate::Db,
+        name: &str,
+        parameters: &[&str],
+        ty: Type,
+    ) -> String {
+        let name = match parameters {
+            [] => type_name(name),
+            _ => format!("{}<{}>", type_name(name), parameters.join(", ")),
+        };
+        let body = |lines: Vec<String>| indent(&lines.join("\n"));
+
+        if let Some(values) = ty.constants(db) {
+            let variants = dedupe(values.iter().map(|value| type_name(value)).collect());
+            let variants = values
+                .iter()
+                .zip(variants)
+                .map(|(value, variant)| format!("#[serde(rename = {value:?})]\n{variant},"))
+                .collect();
+            return format!(
+                "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, \
+                 serde::Deserialize)]\npub enum {name} {{\n{}\n}}",
+                body(variants)
+            );
+        }
+
+        match ty.kind(db) {
+            TypeKind::Object(properties) => format!(
+                "{DERIVES}\npub struct {name} {{\n{}\n}}",
+                body(self.fields(db, &properties))
+            ),
+            TypeKind::And(parts)
+                if parts.iter().all(|part| {
+                    matches!(
+                        part.kind(db),
+                        TypeKind::Reference(_) | TypeKind::Object(_) | TypeKind::Map(..)
+                    )
+                }) =>
+            {
+                let mut fields = Vec::new();
+                let mut properties = BTreeMap::new();
+                for part in parts {
+                    match part.kind(db) {
+                        TypeKind::Reference(base) => fields.push(format!(
+                            "#[serde(flatten)]\npub {}: {},",
+                            field_name(&base),
+                            self.ty(db, part, true)
+                        )),
+                        TypeKind::Object(props) => properties.extend(props),
+                        TypeKind::Map(..) => fields.push(format!(
+                            "#[serde(flatten)]\npub additional_properties: {},",
+                            self.ty(db, part, false)
+                        )),
+                        _ => unreachable!(),
+                    }
+                }
+                fields.extend(self.fields(db, &properties));
+                format!("{DERIVES}\npub struct {name} {{\n{}\n}}", body(fields))
+            }
+            TypeKind::Or(options) if nullable(db, &options).is_none() => {
+                let variants = dedupe(
+                    options
+                        .iter()
+                        .map(|option| match option.kind(db) {
+                            TypeKind::Reference(name) => type_name(&name),
+                            TypeKind::Object(_) => "Object".to_string(),
+                            TypeKind::Map(..) => "Map".to_string(),
+                            TypeKind::Array(_) => "Array".to_string(),
+                            TypeKind::Tuple(_) => "Tuple".to_string(),
+                            TypeKind::Number => "Number".to_string(),
+                            TypeKind::String | TypeKind::Ident(_) => "String".to_string(),
+                            TypeKind::Boolean => "Boolean".to_string(),
+                            TypeKind::Null => "Null".to_string(),
+                            _ => "Other".to_string(),
+                        })
+                        .collect(),
+                );
+                let variants = options
+                    .iter()
+                    .zip(variants)
+                    .map(|(option, variant)| match option.kind(db) {
+                        TypeKind::Null => format!("{variant},"),
+                        _ => format!("{variant}({}),", self.ty(db, *option, true)),
+                    })
+                    .collect();
+                format!(
+                    "{DERIVES}\n#[serde(untagged)]\npub enum {name} {{\n{}\n}}",
+                    body(variants)
+                )
+            }
+            _ => format!("pub type {name} = {};", self.ty(db, ty, false)),
+        }
+    }
+
+    fn operation(&self, db: &dyn crate::Db, api: InputApi, op: &Operation) -> String {
+        let name = field_name(&op.name(db, api));
+
+        let body = match op.body {
+            Some(RequestKind::Json(body) | RequestKind::Form(body)) => Some(body),
+            None => None,
+            Some(RequestKind::Custom(_)) => {
+                return format!("// NOTE: `{name}` is left out, as its request body uses a codec");
+            }
+            Some(RequestKind::Multipart(_)) => {
+                return format!("// NOTE: `{name}` is left out, as its request body is multipart");
+            }
+        };
+        let (returns, decode) = match op.response {
+            Some(ResponseKind::Empty) => ("()".to_string(), "Ok(())"),
+            Some(ResponseKind::Plain | ResponseKind::Xml) => {
+                ("String".to_string(), "res.text().await")
+            }
+            Some(ResponseKind::Json(ty)) => (self.ty(db, ty, false), "res.json().await"),
+            Some(ResponseKind::Blob) => ("Vec<u8>".to_string(), "Ok(res.bytes().await?.to_vec())"),
+            Some(ResponseKind::EventStream(_) | ResponseKind::Csv(_)) => {
+                return format!(
+                    "// NOTE: `{name}` is left out, as its response media type isn't supported"
+                );
+            }
+            Some(ResponseKind::Custom(_)) => {
+                return format!("// NOTE: `{name}` is left out, as its response uses a codec");
+            }
+            Some(ResponseKind::Redirect) => {
+                return format!("// NOTE: `{name}` is left out, as it responds with a redirect");
+            }
+            None => unreachable!("operations without a response are left out"),
+        };
+
+        let config = api.config(db);
+        let mut args = vec!["&self".to_string()];
+        args.extend(
+            op.path_params
+                .iter()
+                .map(|(param, ty)| format!("{}: {}", field_name(param), self.ty(db, *ty, false))),
+        );
+        if let Some(body) = body {
+            args.push(format!("body: &{}", self.ty(db, body, false)));
+        }
+        if op.if_match {
+            args.push("if_match: &str".to_string());
+        }
+        for (param, query) in &op.query {
+            let ty = self.ty(db, query.ty, false);
+            if query.optional(&config) {
+                args.push(format!("{}: Option<{ty}>", field_name(param)));
+            } else {
+                args.push(format!("{}: {ty}", field_name(param)));
+            }
+        }
+        // NOTE: Header parameters are optional, as in the TypeScript client.
+        for (param, ty) in &op.headers {
+            args.push(format!(
+                "{}: Option<{}>",
+                field_name(param),
+                self.ty(db, *ty, false)
+            ));
+        }
+
+        let mut path = op.path.replace('{', "{{").replace('}', "}}");
+        let placeholders = op
+            .path_params
+            .keys()
+            .filter_map(|param| Some((op.path.find(&format!("{{{param}}}"))?, param)))
+            .sorted()
+            .collect_vec();
+        let mut path_args = Vec::new();
+        for (_, param) in placeholders {
+            path = path.replace(&format!("{{{{{param}}}}}"), "{}");
+            path_args.push(format!("path_segment(&{})", field_name(param)));
+        }
+
+        let mut request = vec![format!(
+            "let res = self\n    .client\n    .request(\n        reqwest::Method::{},\n        \
+             format!(\"{{}}{path}\", self.base_url{}),\n    )",
+            op.method,
+            path_args.iter().map(|arg| format!(", {arg}")).join("")
+        )];
+        if !op.query.is_empty() {
+            request.push(format!(
+                "    .query(&[{}].concat())",
+                op.query
+                    .keys()
+                    .map(|param| format!("query_pairs({param:?}, &{})", field_name(param)))
+                    .join(", ")
+            ));
+        }
+        match op.body {
+            Some(RequestKind::Form(_)) => request.push("    .form(body)".to_string()),
+            Some(_) => request.push("    .json(body)".to_string()),
+            None => {}
+        }
+        if op.if_match {
+            request.push(r#"    .header("If-Match", if_match)"#.to_string());
+        }
+        if !op.headers.is_empty() {
+            request.push(format!(
+                "    .headers(header_map(&[{}].concat()))",
+                op.headers
+                    .keys()
+                    .map(|param| format!("query_pairs({param:?}, &{})", field_name(param)))
+                    .join(", ")
+            ));
+        }
+        request.push("    .send()\n    .await?\n    .error_for_status()?;".to_string());
+
+        format!(
+            "/// `{} {}`\npub async fn {name}({}) -> Result<{returns}, reqwest::Error> {{\n{}\n}}",
+            op.method,
+            op.path,
+            args.join(", "),
+            indent(&format!("{}\n{decode}", request.join("\n")))
+        )
+    }
+}
+
+/// The other option of a union of a type and `null`.
+fn nullable(db: &dyn crate::Db, options: &[Type]) -> Option<Type> {
+    match options {
+        [a, b] if a.kind(db) == TypeKind::Null => Some(*b),
+        [a, b] if b.kind(db) == TypeKind::Null => Some(*a),
+        _ => None,
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+
+use openapiv3 as oapi;
+
+use crate::{
+    diagnostics, escape_pointer, lower_api, request_media_type_supported,
+    response_media_type_supported, DiagnosticKind, InputApi,
+};
+
+/// How many operations of the document use a media type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaTypeStats {
+    pub media_type: String,
+    /// The number of operations taking a request body of the media type.
+    pub requests: usize,
+    /// The number of operations documenting a response of the media type.
+    pub responses: usize,
+    /// Whether every use of the media type is supported. Operations using an
+    /// unsupported media type are left out of the generated output.
+    pub supported: bool,
+}
+
+/// Counts the uses of every media type in the document, ordered by media type.
+pub fn media_type_stats(db: &dyn crate::Db, api: InputApi) -> Vec<MediaTypeStats> {
+    let config = api.config(db);
+
+    let mut stats = BTreeMap::<String, MediaTypeStats>::new();
+    let mut entry = |media_type: &str| {
+        stats
+            .entry(media_type.to_string())
+            .or_insert_with(|| MediaTypeStats {
+                media_type: media_type.to_string(),
+                requests: 0,
+                responses: 0,
+                supported: true,
+            })
+    };
+
+    let operations = api
+        .api(db)
+        .paths
+        .paths
+        .values()
+        .flat_map(|item| match item {
+            oapi::ReferenceOr::Reference { .. } => Vec::new(),
+            oapi::ReferenceOr::Item(item) => item.iter().map(|(_, op)| op).collect(),
+        });
+    for op in operations {
+        if let Some(oapi::ReferenceOr::Item(body)) = &op.request_body {
+            for media_type in body.content.keys() {
+                let stats = entry(media_type);
+                stats.requests += 1;
+                stats.supported &= request_media_type_supported(&config, media_type);
+            }
+        }
+
+        let mut response_media_types = op
+            .responses
+            .responses
+            .values()
+            .filter_map(|response| response.as_item())
+            .flat_map(|response| response.content.keys())
+            .collect::<Vec<_>>();
+        response_media_types.sort();
+        response_media_types.dedup();
+        for media_type in response_media_types {
+            let stats = entry(media_type);
+            stats.responses += 1;
+            stats.supported &= response_media_type_supported(&config, media_type);
+        }
+    }
+
+    stats.into_values().collect()
+}
+
+/// How much of a document is supported, for judging whether a client can be
+/// generated for an API before adopting it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compatibility {
+    /// The number of operations in the document.
+    pub operations: usize,
+    /// The number of operations in the generated output.
+    pub generated: usize,
+    /// The number of generated operations without any diagnostics about
+    /// them, nor about the schemas written out within them.
+    pub clean: usize,
+    /// The diagnostics grouped by kind and reason, most frequent first, as
+    /// the features which would have to be supported.
+    pub gaps: Vec<FeatureGap>,
+}
+
+impl Compatibility {
+    /// The percentage of the operations generated cleanly.
+    pub fn score(&self) -> f64 {
+        if self.operations == 0 {
+            100.0
+        } else {
+            100.0 * self.clean as f64 / self.operations as f64
+        }
+    }
+}
+
+/// Diagnostics of the same kind and reason, differing only in the names they
+/// mention.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureGap {
+    pub kind: DiagnosticKind,
+    /// The message of the diagnostics with quoted names replaced by `"…"`.
+    pub reason: String,
+    pub count: usize,
+    /// The pointer of the first diagnostic.
+    pub example: String,
+}
+
+/// Scores how much of the document is supported, leaving out constructs
+/// excluded on purpose.
+pub fn compatibility(db: &dyn crate::Db, api: InputApi) -> Compatibility {
+    let diagnostics = diagnostics(db, api)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.kind != DiagnosticKind::Excluded)
+        .collect::<Vec<_>>();
+
+    let operations = api
+        .api(db)
+        .paths
+        .paths
+        .values()
+        .filter_map(|item| item.as_item())
+        .map(|item| item.iter().count())
+        .sum();
+    let generated = lower_api(db, api);
+    let clean = generated
+        .iter()
+        .filter(|op| {
+            let pointer = format!(
+                "/paths/{}/{}",
+                escape_pointer(&op.path),
+                op.method.to_ascii_lowercase()
+            );
+            !diagnostics.iter().any(|diagnostic| {
+                diagnostic.pointer == pointer
+                    || diagnostic.pointer.starts_with(&format!("{pointer}/"))
+            })
+        })
+        .count();
+
+    let mut gaps = Vec::<FeatureGap>::new();
+    for diagnostic in &diagnostics {
+        let reason = generalize(&diagnostic.message);
+        match gaps
+            .iter_mut()
+            .find(|gap| gap.kind == diagnostic.kind && gap.reason == reason)
+        {
+            Some(gap) => gap.count += 1,
+            None => gaps.push(FeatureGap {
+                kind: diagnostic.kind,
+                reason,
+                count: 1,
+                example: diagnostic.pointer.clone(),
+            }),
+        }
+    }
+    gaps.sort_by(|a, b| b.count.cmp(&a.count).then(a.kind.cmp(&b.kind)));
+
+    Compatibility {
+        operations,
+        generated: generated.len(),
+        clean,
+        gaps,
+    }
+}
+
+/// The message with every quoted part replaced by `"…"`.
+fn generalize(message: &str) -> String {
+    let mut generalized = String::new();
+    let mut parts = message.split('"');
+    generalized.push_str(parts.next().unwrap_or_default());
+    while let Some(_quoted) = parts.next() {
+        generalized.push_str("\"…\"");
+        generalized.push_str(parts.next().unwrap_or_default());
+    }
+    generalized
+}
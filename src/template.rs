@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use heck::{ToKebabCase, ToLowerCamelCase, ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+
+use crate::{
+    component_types, lower_api, schema_description, InputApi, Operation, RequestKind, ResponseKind,
+    Shape, Type,
+};
+
+/// The lowered API as passed to templates, built from the same [`Operation`]s
+/// and component types as the other targets, with types given as [`Shape`]s.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TemplateApi {
+    pub title: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub operations: Vec<TemplateOperation>,
+    /// The component types in the order they are declared.
+    pub types: Vec<TemplateType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TemplateOperation {
+    /// The name of the generated method, see [`Operation::name`].
+    pub name: String,
+    pub method: &'static str,
+    pub path: String,
+    pub operation_id: Option<String>,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub deprecated: bool,
+    pub path_params: Vec<TemplateParam>,
+    pub query: Vec<TemplateParam>,
+    pub headers: Vec<TemplateParam>,
+    pub body: Option<TemplateBody>,
+    /// The response the client resolves with, see [`Operation::response`].
+    pub response: Option<TemplateResponse>,
+    /// Every documented response in the order they are documented.
+    pub responses: Vec<TemplateResponse>,
+    pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TemplateParam {
+    pub name: String,
+    pub shape: Shape,
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TemplateBody {
+    /// One of `json`, `custom`, `multipart`, or `form`.
+    pub kind: &'static str,
+    pub media_type: Option<String>,
+    pub shape: Shape,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TemplateResponse {
+    /// The status, such as `200`, `4XX`, or `default`, if known.
+    pub status: Option<String>,
+    /// One of `empty`, `plain`, `json`, `event-stream`, `csv`, `blob`, `xml`,
+    /// `custom`, or `redirect`.
+    pub kind: &'static str,
+    /// The type of the content, or of a single row for `csv`.
+    pub shape: Option<Shape>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TemplateType {
+    pub name: String,
+    pub description: Option<String>,
+    pub shape: Shape,
+}
+
+impl TemplateApi {
+    pub fn new(db: &dyn crate::Db, api: InputApi) -> TemplateApi {
+        let info = &api.api(db).info;
+
+        TemplateApi {
+            title: info.title.clone(),
+            version: info.version.clone(),
+            description: info.description.clone(),
+            operations: lower_api(db, api)
+                .iter()
+                .map(|op| TemplateOperation::new(db, api, op))
+                .collect(),
+            types: component_types(db, api)
+                .into_iter()
+                .map(|(name, ty)| TemplateType {
+                    description: schema_description(db, api, &name),
+                    shape: Shape::new(db, ty),
+                    name,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TemplateOperation {
+    fn new(db: &dyn crate::Db, api: InputApi, op: &Operation) -> TemplateOperation {
+        let config = api.config(db);
+
+        let required = |params: &BTreeMap<String, Type>| -> Vec<TemplateParam> {
+            params
+                .iter()
+                .map(|(name, ty)| TemplateParam {
+                    name: name.clone(),
+                    shape: Shape::new(db, *ty),
+                    optional: false,
+                })
+                .collect()
+        };
+        let response = |status: Option<&String>, response: ResponseKind| {
+            let (kind, ty) = match response {
+                ResponseKind::Empty => ("empty", None),
+                ResponseKind::Plain => ("plain", None),
+                ResponseKind::Json(ty) => ("json", Some(ty)),
+                ResponseKind::EventStream(ty) => ("event-stream", Some(ty)),
+                ResponseKind::Csv(ty) => ("csv", Some(ty)),
+                ResponseKind::Blob => ("blob", None),
+                ResponseKind::Xml => ("xml", None),
+                ResponseKind::Custom(ty) => ("custom", Some(ty)),
+                ResponseKind::Redirect => ("redirect", None),
+            };
+            TemplateResponse {
+                status: status.cloned(),
+                kind,
+                shape: ty.map(|ty| Shape::new(db, ty)),
+            }
+        };
+
+        TemplateOperation {
+            name: op.name(db, api),
+            method: op.method,
+            path: op.path.clone(),
+            operation_id: op.operation_id.clone(),
+            summary: op.summary.clone(),
+            description: op.description.clone(),
+            tags: op.tags.clone(),
+            deprecated: op.deprecated,
+            path_params: required(&op.path_params),
+            query: op
+                .query
+                .iter()
+                .map(|(name, param)| TemplateParam {
+                    name: name.clone(),
+                    shape: Shape::new(db, param.ty),
+                    optional: param.optional(&config),
+                })
+                .collect(),
+            headers: required(&op.headers),
+            body: op.body.map(|body| TemplateBody {
+                kind: match body {
+                    RequestKind::Json(_) => "json",
+                    RequestKind::Custom(_) => "custom",
+                    RequestKind::Multipart(_) => "multipart",
+                    RequestKind::Form(_) => "form",
+                },
+                media_type: op.request_media_type.clone(),
+                shape: Shape::new(db, body.ty()),
+            }),
+            // NOTE: The primary response is among the documented ones, so its
+            // status is looked up rather than left unknown.
+            response: op.response.map(|primary| {
+                let status = op
+                    .responses
+                    .iter()
+                    .find(|(_, res)| *res == primary)
+                    .map(|(status, _)| status);
+                response(status, primary)
+            }),
+            responses: op
+                .responses
+                .iter()
+                .map(|(status, res)| response(Some(status), *res))
+                .collect(),
+            extensions: op.extensions.clone(),
+        }
+    }
+}
+
+/// Renders the templates, keyed by their path relative to the template
+/// directory, with the [`TemplateApi`] as `api`. Each template is written to
+/// its own path with a trailing `.jinja` removed, except for those whose file
+/// name starts with `_`, which are only there to be included or imported by
+/// the others.
+///
+/// Besides the builtin filters of minijinja, names can be converted with
+/// `camel_case`, `pascal_case`, `snake_case`, `shouty_snake_case`, and
+/// `kebab_case`.
+pub fn generate_template(
+    db: &dyn crate::Db,
+    api: InputApi,
+    templates: &BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, minijinja::Error> {
+    let mut env = minijinja::Environment::new();
+    env.set_keep_trailing_newline(true);
+    env.add_filter("camel_case", |name: String| name.to_lower_camel_case());
+    env.add_filter("pascal_case", |name: String| name.to_upper_camel_case());
+    env.add_filter("snake_case", |name: String| name.to_snake_case());
+    env.add_filter("shouty_snake_case", |name: String| {
+        name.to_shouty_snake_case()
+    });
+    env.add_filter("kebab_case", |name: String| name.to_kebab_case());
+    for (name, source) in templates {
+        env.add_template(name, source)?;
+    }
+
+    let ctx = minijinja::context! { api => TemplateApi::new(db, api) };
+
+    templates
+        .keys()
+        .filter(|name| !name.rsplit('/').next().unwrap_or(name).starts_with('_'))
+        .map(|name| {
+            let text = env.get_template(name)?.render(&ctx)?;
+            let path = name.strip_suffix(".jinja").unwrap_or(name);
+            Ok((path.to_string(), text))
+        })
+        .collect()
+}
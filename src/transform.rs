@@ -0,0 +1,1435 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use serde_json::Value;
+
+/// A transformation of the raw OpenAPI document, applied before it is parsed
+/// and lowered.
+#[derive(Debug, Clone)]
+pub enum Transform {
+    /// Remove every path whose template matches the glob pattern.
+    DropPaths(globset::GlobMatcher),
+    /// Replace the `from` prefix of every server URL with `to`. This applies to
+    /// servers declared on the document, path items, and operations alike.
+    RewriteServers { from: String, to: String },
+    /// Add `response` under `status` to every operation which does not already
+    /// document a response for that status.
+    InjectResponse { status: String, response: Value },
+    /// Apply an RFC 6902 JSON Patch.
+    Patch(Vec<PatchOperation>),
+    /// Apply the actions of an OpenAPI Overlay document.
+    Overlay(Overlay),
+    /// Rename the component schemas whose names aren't identifiers, such as
+    /// `Page_SearchResult` becoming `PageSearchResult`, along with every
+    /// reference to them. See [`sanitize_schema_names`]. With `keep_generic`,
+    /// the instances of generic schemas found by [`generic_families`] keep
+    /// their names.
+    SanitizeSchemaNames { keep_generic: bool },
+}
+
+/// A single operation of an RFC 6902 JSON Patch document.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+/// An OpenAPI Overlay document. Only the `actions` are used; everything else
+/// is informational.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct Overlay {
+    pub actions: Vec<OverlayAction>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct OverlayAction {
+    /// A JSONPath expression selecting the nodes the action applies to.
+    ///
+    /// Only the child (`.name`, `['name']`), index (`[0]`), and wildcard
+    /// (`.*`, `[*]`) selectors are supported.
+    pub target: String,
+    /// Merged into every selected node. Objects are merged recursively,
+    /// arrays are appended to, and anything else is replaced.
+    #[serde(default)]
+    pub update: Option<Value>,
+    /// Remove every selected node from its parent.
+    #[serde(default)]
+    pub remove: bool,
+}
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Applies the transforms to the document in order.
+pub fn apply_transforms(doc: &mut Value, transforms: &[Transform]) -> Result<()> {
+    for transform in transforms {
+        tracing::debug!(?transform, "applying transform");
+
+        match transform {
+            Transform::DropPaths(glob) => {
+                if let Some(paths) = doc.get_mut("paths").and_then(Value::as_object_mut) {
+                    paths.retain(|path, _| {
+                        let drop = glob.is_match(path);
+                        if drop {
+                            tracing::info!(?path, "dropping path");
+                        }
+                        !drop
+                    });
+                }
+            }
+            Transform::RewriteServers { from, to } => rewrite_servers(doc, from, to),
+            Transform::InjectResponse { status, response } => {
+                let Some(paths) = doc.get_mut("paths").and_then(Value::as_object_mut) else {
+                    continue;
+                };
+                for item in paths.values_mut() {
+                    for method in METHODS {
+                        let Some(responses) = item
+                            .get_mut(method)
+                            .and_then(|op| op.get_mut("responses"))
+                            .and_then(Value::as_object_mut)
+                        else {
+                            continue;
+                        };
+                        responses
+                            .entry(status.clone())
+                            .or_insert_with(|| response.clone());
+                    }
+                }
+            }
+            Transform::Patch(operations) => {
+                for operation in operations {
+                    apply_patch_operation(doc, operation)?;
+                }
+            }
+            Transform::Overlay(overlay) => {
+                for action in &overlay.actions {
+                    apply_overlay_action(doc, action)?;
+                }
+            }
+            Transform::SanitizeSchemaNames { keep_generic } => {
+                sanitize_schema_names(doc, *keep_generic)
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Rewrites an OpenAPI 3.1 document into the 3.0 form it is parsed as. This
+/// leaves 3.0 documents unchanged, and 3.1 features without a 3.0 equivalent,
+/// such as webhooks, are dropped.
+///
+/// In schemas:
+///
+/// * `type: [T, "null"]` becomes `type: T` with `nullable: true`, and a list
+///   of several other types becomes a `oneOf` of them.
+/// * `{ type: "null" }` options of `oneOf` and `anyOf` are removed, making the
+///   schema `nullable` instead.
+/// * Numeric `exclusiveMinimum` and `exclusiveMaximum` become `minimum` and
+///   `maximum` with the boolean flags set.
+/// * `const: v` becomes `enum: [v]`, and `examples: [...]` becomes `example`.
+/// * Strings with a `contentMediaType` become `format: binary`.
+pub fn convert_openapi_31(doc: &mut Value) {
+    let Some(version) = doc.get("openapi").and_then(Value::as_str) else {
+        return;
+    };
+    if !version.starts_with("3.1") {
+        return;
+    }
+    tracing::debug!(?version, "converting to OpenAPI 3.0");
+
+    doc["openapi"] = Value::String("3.0.3".to_string());
+    if let Some(doc) = doc.as_object_mut() {
+        doc.remove("webhooks");
+        doc.remove("jsonSchemaDialect");
+        doc.entry("paths")
+            .or_insert_with(|| Value::Object(Default::default()));
+    }
+    if let Some(info) = doc.get_mut("info").and_then(Value::as_object_mut) {
+        info.remove("summary");
+        if let Some(license) = info.get_mut("license").and_then(Value::as_object_mut) {
+            license.remove("identifier");
+        }
+    }
+    if let Some(components) = doc.get_mut("components").and_then(Value::as_object_mut) {
+        components.remove("pathItems");
+    }
+
+    convert_schemas(doc);
+}
+
+fn convert_schemas(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for value in map.values_mut() {
+                convert_schemas(value);
+            }
+
+            if let Some(Value::Array(types)) = map.get("type") {
+                let (nulls, types): (Vec<_>, Vec<_>) =
+                    types.iter().cloned().partition(|ty| ty == "null");
+                if !nulls.is_empty() {
+                    map.insert("nullable".to_string(), Value::Bool(true));
+                }
+                match <[Value; 1]>::try_from(types) {
+                    Ok([ty]) => {
+                        map.insert("type".to_string(), ty);
+                    }
+                    Err(types) => {
+                        map.remove("type");
+                        let options = types
+                            .into_iter()
+                            .map(|ty| serde_json::json!({ "type": ty }))
+                            .collect();
+                        map.insert("oneOf".to_string(), Value::Array(options));
+                    }
+                }
+            }
+
+            for key in ["oneOf", "anyOf"] {
+                if let Some(Value::Array(options)) = map.get_mut(key) {
+                    let before = options.len();
+                    options.retain(|option| option.get("type") != Some(&Value::from("null")));
+                    if options.len() < before {
+                        map.insert("nullable".to_string(), Value::Bool(true));
+                    }
+                }
+            }
+
+            for (exclusive, bound) in [
+                ("exclusiveMinimum", "minimum"),
+                ("exclusiveMaximum", "maximum"),
+            ] {
+                if let Some(limit @ Value::Number(_)) = map.get(exclusive).cloned() {
+                    map.insert(bound.to_string(), limit);
+                    map.insert(exclusive.to_string(), Value::Bool(true));
+                }
+            }
+
+            if let Some(value) = map.remove("const") {
+                map.insert("enum".to_string(), Value::Array(vec![value]));
+            }
+            if let Some(Value::Array(examples)) = map.get("examples") {
+                if let Some(example) = examples.first().cloned() {
+                    map.insert("example".to_string(), example);
+                }
+                map.remove("examples");
+            }
+            // NOTE: The keys of maps are lowered from the extension, as
+            // OpenAPI 3.0 has no way of typing them.
+            if let Some(keys) = map.remove("propertyNames") {
+                map.entry("x-key-type").or_insert(keys);
+            }
+            if map.contains_key("contentMediaType") && map.get("type") == Some(&"string".into()) {
+                map.remove("contentMediaType");
+                map.entry("format")
+                    .or_insert_with(|| Value::String("binary".to_string()));
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(convert_schemas),
+        _ => {}
+    }
+}
+
+/// Adds the paths and components of `other` to `doc`, for generating a single
+/// client for several services.
+///
+/// Components present in both documents are kept once if they are identical,
+/// while differing components, or paths present in both, are an error.
+pub fn merge_document(doc: &mut Value, other: Value) -> Result<()> {
+    if let Some(Value::Object(paths)) = other.get("paths") {
+        for (path, item) in paths {
+            insert_unique(object_at(doc, &["paths"])?, path, item.clone(), "path")?;
+        }
+    }
+    if let Some(Value::Object(components)) = other.get("components") {
+        for (kind, entries) in components {
+            let Value::Object(entries) = entries else {
+                continue;
+            };
+            for (name, entry) in entries {
+                insert_unique(
+                    object_at(doc, &["components", kind])?,
+                    name,
+                    entry.clone(),
+                    "component",
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces references to schemas in other documents, such as
+/// `https://example.com/shared.json#/components/schemas/Error`, with
+/// references to copies of them added to the component schemas of `doc`.
+/// Documents are read with `load`, given the part of the reference before `#`
+/// relative to `doc`, and each is only read once.
+///
+/// Copies keep the name of the schema in its own document, so references
+/// between the schemas of another document are followed and copied as well.
+/// A copy identical to a schema already in `doc` is deduplicated into it,
+/// which keeps schemas shared by several services from being emitted more
+/// than once, while a differing schema of the same name is an error.
+/// References are resolved relative to the document containing them, and
+/// documents referencing each other are followed until every reference is
+/// local.
+///
+/// With `resolve_external`, specs split across files can be referenced in
+/// full: any value of another document, such as `./user.yaml#/User`, is
+/// copied into the components under the last segment of its pointer, and a
+/// whole document, such as `./schemas/Pet.yaml`, under its file name. Path
+/// items referencing another document are replaced by it. Without it, only
+/// component schemas may be referenced.
+pub fn bundle_external_refs(
+    doc: &mut Value,
+    resolve_external: bool,
+    mut load: impl FnMut(&str) -> Result<Value>,
+) -> Result<()> {
+    let mut documents = BTreeMap::<String, Value>::new();
+    let mut resolve = |reference: &str| -> Result<Value> {
+        let (location, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+        if !documents.contains_key(location) {
+            tracing::info!(?location, "loading referenced document");
+            let other = load(location).wrap_err_with(|| format!("loading {location:?}"))?;
+            documents.insert(location.to_string(), other);
+        }
+        documents[location]
+            .pointer(pointer)
+            .cloned()
+            .ok_or_else(|| eyre!("referenced value does not exist: {reference:?}"))
+    };
+
+    if resolve_external {
+        if let Some(Value::Object(paths)) = doc.get_mut("paths") {
+            for item in paths.values_mut() {
+                let Some(reference) = item
+                    .get("$ref")
+                    .and_then(Value::as_str)
+                    .filter(|reference| !reference.starts_with('#'))
+                    .map(|reference| join_location("", reference))
+                else {
+                    continue;
+                };
+                let mut resolved = resolve(&reference)?;
+                let location = reference.split('#').next().unwrap_or_default();
+                rebase_refs(&mut resolved, location);
+                *item = resolved;
+            }
+        }
+    }
+
+    let mut bundled = BTreeSet::new();
+    let mut pending = Vec::new();
+    collect_refs(doc, &mut pending);
+    pending.retain(|reference| !reference.starts_with('#'));
+
+    while let Some(written) = pending.pop() {
+        let reference = join_location("", &written);
+        let (kind, name) = external_component(&reference, resolve_external)?;
+        let local = format!("#/components/{kind}/{name}");
+        replace_refs(doc, &written, &local);
+        if !bundled.insert(reference.clone()) {
+            continue;
+        }
+
+        let mut value = resolve(&reference)?;
+        rebase_refs(&mut value, reference.split('#').next().unwrap_or_default());
+        let mut refs = Vec::new();
+        collect_refs(&value, &mut refs);
+        for r in refs {
+            let (kind, name) = external_component(&r, resolve_external)?;
+            replace_refs(&mut value, &r, &format!("#/components/{kind}/{name}"));
+            pending.push(r);
+        }
+
+        insert_unique(
+            object_at(doc, &["components", kind])?,
+            name,
+            value,
+            "component",
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The kind and name of the component a value of another document is copied
+/// into by [`bundle_external_refs`].
+fn external_component(reference: &str, resolve_external: bool) -> Result<(&str, &str)> {
+    let (location, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+    let component = pointer
+        .strip_prefix("/components/")
+        .and_then(|rest| rest.split_once('/'))
+        .filter(|(kind, name)| !name.contains('/') && (resolve_external || *kind == "schemas"));
+    if let Some(component) = component {
+        return Ok(component);
+    }
+    if !resolve_external {
+        bail!("only component schemas can be referenced in other documents: {reference:?}");
+    }
+
+    let name = match pointer.rsplit_once('/') {
+        Some((_, name)) => name,
+        None => {
+            let file = location.rsplit('/').next().unwrap_or(location);
+            file.split('.').next().unwrap_or(file)
+        }
+    };
+    if name.is_empty() || name.contains('~') {
+        bail!("cannot name the component for the reference {reference:?}");
+    }
+    Ok(("schemas", name))
+}
+
+/// Rewrites the references of a value copied from the document at `location`
+/// to be relative to the document it is copied into.
+fn rebase_refs(value: &mut Value, location: &str) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) if reference.starts_with('#') => {
+                        *reference = format!("{location}{reference}")
+                    }
+                    ("$ref", Value::String(reference)) => {
+                        *reference = join_location(location, reference)
+                    }
+                    (_, value) => rebase_refs(value, location),
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| rebase_refs(item, location)),
+        _ => {}
+    }
+}
+
+/// `location`, optionally followed by a `#` fragment, relative to the
+/// document at `base`, with `.` and `..` segments removed. URLs and absolute
+/// paths are kept as they are.
+fn join_location(base: &str, location: &str) -> String {
+    if location.contains("://") || location.starts_with('/') {
+        return location.to_string();
+    }
+    let (location, fragment) = match location.split_once('#') {
+        Some((location, pointer)) => (location, format!("#{pointer}")),
+        None => (location, String::new()),
+    };
+    let dir = base.rfind('/').map_or("", |end| &base[..=end]);
+    let joined = format!("{dir}{location}");
+
+    let mut segments = Vec::<&str>::new();
+    for segment in joined.split('/') {
+        match segment {
+            "." => {}
+            ".." if segments
+                .last()
+                .is_some_and(|last| !["..", ""].contains(last)) =>
+            {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/") + &fragment
+}
+
+/// Renames the component schemas whose names contain anything but ASCII
+/// letters and digits, such as the `Page_SearchResult` generated for generic
+/// types by some frameworks, so they can be declared in every target. The
+/// separators are removed and the letters following them uppercased, giving
+/// `PageSearchResult`. References to them, including discriminator mappings,
+/// are rewritten to the new names.
+///
+/// A name already taken by another schema is numbered, as in
+/// `PageSearchResult2`, and a warning is logged. With `keep_generic`, the
+/// instances of generic schemas found by [`generic_families`] are left as
+/// they are.
+///
+/// Only the CLI applies this by default. Documents passed to
+/// [`crate::InputApi`] as they are keep their names, and schemas containing
+/// `_` are inlined where referenced instead of being declared.
+pub fn sanitize_schema_names(doc: &mut Value, keep_generic: bool) {
+    let Some(schemas) = doc
+        .pointer_mut("/components/schemas")
+        .and_then(Value::as_object_mut)
+    else {
+        return;
+    };
+    let generic = if keep_generic {
+        generic_families(schemas)
+            .into_values()
+            .flat_map(|instances| instances.into_keys())
+            .collect()
+    } else {
+        BTreeSet::new()
+    };
+
+    let mut taken = schemas
+        .keys()
+        .filter(|name| sanitized_name(name) == **name)
+        .cloned()
+        .collect::<BTreeSet<_>>();
+    let mut renames = BTreeMap::new();
+    for name in schemas.keys() {
+        let sanitized = sanitized_name(name);
+        if sanitized == *name || generic.contains(name) {
+            continue;
+        }
+        let mut renamed = sanitized.clone();
+        let mut n = 2;
+        while !taken.insert(renamed.clone()) {
+            renamed = format!("{sanitized}{n}");
+            n += 1;
+        }
+        if renamed != sanitized {
+            tracing::warn!(
+                ?name,
+                ?renamed,
+                "sanitized schema name is taken, numbering it"
+            );
+        }
+        renames.insert(name.clone(), renamed);
+    }
+    if renames.is_empty() {
+        return;
+    }
+
+    let entries = std::mem::take(schemas);
+    for (name, schema) in entries {
+        let name = renames.get(&name).cloned().unwrap_or(name);
+        schemas.insert(name, schema);
+    }
+    let renames = renames
+        .into_iter()
+        .map(|(from, to)| {
+            (
+                format!("#/components/schemas/{}", escape_token(&from)),
+                (from, format!("#/components/schemas/{to}"), to),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+    rename_schema_refs(doc, &renames);
+}
+
+/// The component schemas which look like the instances of a generic type,
+/// as emitted by frameworks such as utoipa: their names are those of the
+/// generic type and of a type argument joined by `_`, such as
+/// `PaginatedResponse_User` and `PaginatedResponse_Post`, and they are the
+/// same except for references to their type argument.
+///
+/// The families are keyed by the name of the generic type, and map the name
+/// of every instance to that of its type argument. Families need at least two
+/// instances, and a generic type named like an existing schema is left out.
+pub fn generic_families(
+    schemas: &serde_json::Map<String, Value>,
+) -> BTreeMap<String, BTreeMap<String, String>> {
+    const PARAMETER: &str = "#/components/schemas/{T}";
+
+    let mut candidates = BTreeMap::<&str, BTreeMap<String, String>>::new();
+    for name in schemas.keys() {
+        let Some((generic, argument)) = name.split_once('_') else {
+            continue;
+        };
+        if generic.is_empty()
+            || argument.contains('_')
+            || schemas.contains_key(generic)
+            || !schemas.contains_key(argument)
+        {
+            continue;
+        }
+        candidates
+            .entry(generic)
+            .or_default()
+            .insert(name.clone(), argument.to_string());
+    }
+
+    candidates
+        .into_iter()
+        .filter(|(_, instances)| instances.len() > 1)
+        .filter(|(_, instances)| {
+            let mut templates = instances.iter().map(|(name, argument)| {
+                let mut template = schemas[name].clone();
+                replace_refs(
+                    &mut template,
+                    &format!("#/components/schemas/{}", escape_token(argument)),
+                    PARAMETER,
+                );
+                (template != schemas[name]).then_some(template)
+            });
+            let Some(Some(first)) = templates.next() else {
+                return false;
+            };
+            templates.all(|template| template.as_ref() == Some(&first))
+        })
+        .map(|(generic, instances)| (generic.to_string(), instances))
+        .collect()
+}
+
+fn sanitized_name(name: &str) -> String {
+    let mut sanitized = String::new();
+    let mut upper = false;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            upper = !sanitized.is_empty();
+        } else if upper {
+            sanitized.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            sanitized.push(c);
+        }
+    }
+    if sanitized.is_empty() || sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert_str(0, "Schema");
+    }
+    sanitized
+}
+
+/// Rewrites references to the renamed schemas, keyed by their old reference
+/// and holding the old name, new reference, and new name. Discriminator
+/// mappings may give either a reference or just the name.
+fn rename_schema_refs(value: &mut Value, renames: &BTreeMap<String, (String, String, String)>) {
+    let rename_ref = |reference: &mut String| {
+        for (from, (_, to, _)) in renames {
+            if let Some(rest) = reference.strip_prefix(from.as_str()) {
+                if rest.is_empty() || rest.starts_with('/') {
+                    *reference = format!("{to}{rest}");
+                    return;
+                }
+            }
+        }
+    };
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => rename_ref(reference),
+                    // NOTE: This may also be a property named `discriminator`,
+                    // so it is searched for references either way.
+                    ("discriminator", value @ Value::Object(_)) => {
+                        let mapping = value.get_mut("mapping").and_then(Value::as_object_mut);
+                        for target in mapping.into_iter().flat_map(|mapping| mapping.values_mut()) {
+                            let Value::String(target) = target else {
+                                continue;
+                            };
+                            match renames.values().find(|(name, _, _)| name == target) {
+                                Some((_, _, to)) => *target = to.clone(),
+                                None => rename_ref(target),
+                            }
+                        }
+                        rename_schema_refs(value, renames);
+                    }
+                    (_, value) => rename_schema_refs(value, renames),
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| rename_schema_refs(item, renames)),
+        _ => {}
+    }
+}
+
+/// The object at the path of keys, created if missing.
+fn object_at<'a>(
+    doc: &'a mut Value,
+    keys: &[&str],
+) -> Result<&'a mut serde_json::Map<String, Value>> {
+    let mut node = doc;
+    for key in keys {
+        node = node
+            .as_object_mut()
+            .ok_or_else(|| eyre!("expected an object containing {key:?}"))?
+            .entry(*key)
+            .or_insert_with(|| Value::Object(Default::default()));
+    }
+    node.as_object_mut()
+        .ok_or_else(|| eyre!("expected an object at {keys:?}"))
+}
+
+fn insert_unique(
+    map: &mut serde_json::Map<String, Value>,
+    key: &str,
+    value: Value,
+    what: &str,
+) -> Result<()> {
+    match map.get(key) {
+        Some(existing) if *existing == value => {
+            tracing::debug!(?key, "deduplicating identical {what}");
+            Ok(())
+        }
+        Some(_) => bail!("{what} {key:?} differs between documents"),
+        None => {
+            map.insert(key.to_string(), value);
+            Ok(())
+        }
+    }
+}
+
+pub(crate) fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => refs.push(reference.clone()),
+                    (_, value) => collect_refs(value, refs),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_refs(item, refs)),
+        _ => {}
+    }
+}
+
+fn replace_refs(value: &mut Value, from: &str, to: &str) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) if reference == from => {
+                        *reference = to.to_string()
+                    }
+                    (_, value) => replace_refs(value, from, to),
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| replace_refs(item, from, to)),
+        _ => {}
+    }
+}
+
+fn rewrite_servers(value: &mut Value, from: &str, to: &str) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match (key.as_str(), value) {
+                    ("servers", Value::Array(servers)) => {
+                        for server in servers {
+                            if let Some(Value::String(url)) = server.get_mut("url") {
+                                if let Some(rest) = url.strip_prefix(from) {
+                                    *url = format!("{to}{rest}");
+                                }
+                            }
+                        }
+                    }
+                    (_, value) => rewrite_servers(value, from, to),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                rewrite_servers(item, from, to);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn apply_patch_operation(doc: &mut Value, operation: &PatchOperation) -> Result<()> {
+    match operation {
+        PatchOperation::Add { path, value } => pointer_add(doc, path, value.clone()),
+        PatchOperation::Remove { path } => pointer_remove(doc, path).map(|_| ()),
+        PatchOperation::Replace { path, value } => {
+            *doc.pointer_mut(path)
+                .ok_or_else(|| eyre!("cannot replace missing value at {path:?}"))? = value.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            let value = pointer_remove(doc, from)?;
+            pointer_add(doc, path, value)
+        }
+        PatchOperation::Copy { from, path } => {
+            let value = doc
+                .pointer(from)
+                .cloned()
+                .ok_or_else(|| eyre!("cannot copy missing value at {from:?}"))?;
+            pointer_add(doc, path, value)
+        }
+        PatchOperation::Test { path, value } => {
+            if doc.pointer(path) != Some(value) {
+                bail!("patch test failed at {path:?}");
+            }
+            Ok(())
+        }
+    }
+}
+
+fn apply_overlay_action(doc: &mut Value, action: &OverlayAction) -> Result<()> {
+    let segments = parse_json_path(&action.target)?;
+    let pointers = select(doc, &segments);
+
+    if pointers.is_empty() {
+        tracing::warn!(json_path = ?action.target, "overlay target matched nothing");
+    }
+
+    if action.remove {
+        // NOTE: Removing in reverse keeps the indices of earlier array elements
+        // valid.
+        for pointer in pointers.iter().rev() {
+            pointer_remove(doc, pointer)?;
+        }
+    } else if let Some(update) = &action.update {
+        for pointer in &pointers {
+            if let Some(node) = doc.pointer_mut(pointer) {
+                merge(node, update);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn merge(target: &mut Value, update: &Value) {
+    match (target, update) {
+        (Value::Object(target), Value::Object(update)) => {
+            for (key, value) in update {
+                match target.get_mut(key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (Value::Array(target), Value::Array(update)) => target.extend(update.iter().cloned()),
+        (Value::Array(target), update) => target.push(update.clone()),
+        (target, update) => *target = update.clone(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+fn parse_json_path(path: &str) -> Result<Vec<PathSegment>> {
+    let mut rest = path
+        .strip_prefix('$')
+        .ok_or_else(|| eyre!("JSONPath must start with '$': {path:?}"))?;
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix(".*") {
+            segments.push(PathSegment::Wildcard);
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix('.') {
+            let end = r.find(['.', '[']).unwrap_or(r.len());
+            if end == 0 {
+                bail!("unsupported JSONPath selector in {path:?}");
+            }
+            segments.push(PathSegment::Key(r[..end].to_string()));
+            rest = &r[end..];
+        } else if let Some(r) = rest.strip_prefix('[') {
+            match r.chars().next() {
+                Some(quote @ ('\'' | '"')) => {
+                    let inner = &r[1..];
+                    let close = inner
+                        .find(quote)
+                        .ok_or_else(|| eyre!("unterminated string in JSONPath {path:?}"))?;
+                    segments.push(PathSegment::Key(inner[..close].to_string()));
+                    rest = inner[close + 1..]
+                        .strip_prefix(']')
+                        .ok_or_else(|| eyre!("expected ']' in JSONPath {path:?}"))?;
+                }
+                _ => {
+                    let close = r
+                        .find(']')
+                        .ok_or_else(|| eyre!("expected ']' in JSONPath {path:?}"))?;
+                    match r[..close].trim() {
+                        "*" => segments.push(PathSegment::Wildcard),
+                        index => segments.push(PathSegment::Index(index.parse().wrap_err_with(
+                            || format!("unsupported JSONPath selector in {path:?}"),
+                        )?)),
+                    }
+                    rest = &r[close + 1..];
+                }
+            }
+        } else {
+            bail!("unsupported JSONPath syntax in {path:?}");
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Returns JSON pointers to every node matched by the path.
+fn select(doc: &Value, segments: &[PathSegment]) -> Vec<String> {
+    let mut matches = vec![(String::new(), doc)];
+
+    for segment in segments {
+        matches = matches
+            .into_iter()
+            .flat_map(|(pointer, value)| {
+                let children: Vec<(String, &Value)> = match (segment, value) {
+                    (PathSegment::Key(key), Value::Object(map)) => map
+                        .get(key)
+                        .map(|child| (escape_token(key), child))
+                        .into_iter()
+                        .collect(),
+                    (PathSegment::Index(index), Value::Array(items)) => items
+                        .get(*index)
+                        .map(|child| (index.to_string(), child))
+                        .into_iter()
+                        .collect(),
+                    (PathSegment::Wildcard, Value::Object(map)) => map
+                        .iter()
+                        .map(|(key, child)| (escape_token(key), child))
+                        .collect(),
+                    (PathSegment::Wildcard, Value::Array(items)) => items
+                        .iter()
+                        .enumerate()
+                        .map(|(index, child)| (index.to_string(), child))
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                children
+                    .into_iter()
+                    .map(move |(token, child)| (format!("{pointer}/{token}"), child))
+            })
+            .collect();
+    }
+
+    matches.into_iter().map(|(pointer, _)| pointer).collect()
+}
+
+fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Splits a JSON pointer into the pointer of its parent and its unescaped last
+/// reference token.
+fn split_pointer(pointer: &str) -> Result<(&str, String)> {
+    let (parent, last) = pointer
+        .rsplit_once('/')
+        .ok_or_else(|| eyre!("invalid JSON pointer: {pointer:?}"))?;
+    Ok((parent, last.replace("~1", "/").replace("~0", "~")))
+}
+
+fn pointer_add(doc: &mut Value, pointer: &str, value: Value) -> Result<()> {
+    if pointer.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+
+    let (parent, key) = split_pointer(pointer)?;
+    match doc.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.insert(key, value);
+        }
+        Some(Value::Array(items)) if key == "-" => items.push(value),
+        Some(Value::Array(items)) => {
+            let index: usize = key.parse()?;
+            if index > items.len() {
+                bail!("index out of bounds in {pointer:?}");
+            }
+            items.insert(index, value);
+        }
+        _ => bail!("cannot add value at {pointer:?}"),
+    }
+    Ok(())
+}
+
+fn pointer_remove(doc: &mut Value, pointer: &str) -> Result<Value> {
+    let (parent, key) = split_pointer(pointer)?;
+    match doc.pointer_mut(parent) {
+        Some(Value::Object(map)) => map
+            .remove(&key)
+            .ok_or_else(|| eyre!("cannot remove missing value at {pointer:?}")),
+        Some(Value::Array(items)) => {
+            let index: usize = key.parse()?;
+            if index >= items.len() {
+                bail!("index out of bounds in {pointer:?}");
+            }
+            Ok(items.remove(index))
+        }
+        _ => bail!("cannot remove value at {pointer:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn patch(doc: &mut Value, operations: Value) -> Result<()> {
+        let operations: Vec<PatchOperation> = serde_json::from_value(operations).unwrap();
+        apply_transforms(doc, &[Transform::Patch(operations)])
+    }
+
+    fn overlay(doc: &mut Value, actions: Value) -> Result<()> {
+        let overlay: Overlay = serde_json::from_value(json!({ "actions": actions })).unwrap();
+        apply_transforms(doc, &[Transform::Overlay(overlay)])
+    }
+
+    #[test]
+    fn json_path_selectors() {
+        assert_eq!(
+            parse_json_path("$.paths['/items'][0].*[*]").unwrap(),
+            vec![
+                PathSegment::Key("paths".to_string()),
+                PathSegment::Key("/items".to_string()),
+                PathSegment::Index(0),
+                PathSegment::Wildcard,
+                PathSegment::Wildcard,
+            ]
+        );
+        assert_eq!(
+            parse_json_path("$[\"a.b\"].c").unwrap(),
+            vec![
+                PathSegment::Key("a.b".to_string()),
+                PathSegment::Key("c".to_string()),
+            ]
+        );
+        assert_eq!(parse_json_path("$").unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn json_path_errors() {
+        for path in ["paths", "$..paths", "$['a'", "$[a]", "$[0", "$.a[?(@.b)]"] {
+            assert!(parse_json_path(path).is_err(), "{path}");
+        }
+    }
+
+    #[test]
+    fn select_escapes_keys() {
+        let doc = json!({ "paths": { "/a~b": { "get": {} }, "/c": { "get": {} } } });
+
+        assert_eq!(
+            select(&doc, &parse_json_path("$.paths.*.get").unwrap()),
+            vec!["/paths/~1a~0b/get", "/paths/~1c/get"]
+        );
+    }
+
+    #[test]
+    fn select_skips_missing_and_mismatched_nodes() {
+        let doc = json!({ "tags": ["a", "b"], "info": { "title": "T" } });
+
+        assert!(select(&doc, &parse_json_path("$.tags[2]").unwrap()).is_empty());
+        assert!(select(&doc, &parse_json_path("$.tags.name").unwrap()).is_empty());
+        assert!(select(&doc, &parse_json_path("$.info[0]").unwrap()).is_empty());
+        assert_eq!(
+            select(&doc, &parse_json_path("$.tags[*]").unwrap()),
+            vec!["/tags/0", "/tags/1"]
+        );
+    }
+
+    #[test]
+    fn patch_adds_to_arrays() {
+        let mut doc = json!({ "tags": ["a", "c"] });
+        patch(
+            &mut doc,
+            json!([
+                { "op": "add", "path": "/tags/1", "value": "b" },
+                { "op": "add", "path": "/tags/-", "value": "d" },
+                { "op": "add", "path": "/tags/4", "value": "e" },
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(doc, json!({ "tags": ["a", "b", "c", "d", "e"] }));
+    }
+
+    #[test]
+    fn patch_rejects_indices_out_of_bounds() {
+        let mut doc = json!({ "tags": ["a"] });
+
+        assert!(patch(
+            &mut doc,
+            json!([{ "op": "add", "path": "/tags/2", "value": "b" }])
+        )
+        .is_err());
+        assert!(patch(&mut doc, json!([{ "op": "remove", "path": "/tags/1" }])).is_err());
+        assert!(patch(&mut doc, json!([{ "op": "remove", "path": "/tags/-" }])).is_err());
+        assert_eq!(doc, json!({ "tags": ["a"] }));
+    }
+
+    #[test]
+    fn patch_unescapes_tokens() {
+        let mut doc = json!({ "paths": { "/a~b": {} } });
+        patch(
+            &mut doc,
+            json!([
+                { "op": "add", "path": "/paths/~1a~0b/get", "value": {} },
+                { "op": "move", "from": "/paths/~1a~0b", "path": "/paths/~1c" },
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(doc, json!({ "paths": { "/c": { "get": {} } } }));
+    }
+
+    #[test]
+    fn patch_replaces_copies_and_tests() {
+        let mut doc = json!({ "info": { "title": "A", "version": "1" } });
+        patch(
+            &mut doc,
+            json!([
+                { "op": "test", "path": "/info/title", "value": "A" },
+                { "op": "replace", "path": "/info/title", "value": "B" },
+                { "op": "copy", "from": "/info/version", "path": "/info/summary" },
+                { "op": "remove", "path": "/info/version" },
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(doc, json!({ "info": { "title": "B", "summary": "1" } }));
+    }
+
+    #[test]
+    fn patch_fails_on_missing_values_and_failed_tests() {
+        let mut doc = json!({ "info": { "title": "A" } });
+
+        assert!(patch(
+            &mut doc,
+            json!([{ "op": "test", "path": "/info/title", "value": "B" }])
+        )
+        .is_err());
+        assert!(patch(
+            &mut doc,
+            json!([{ "op": "replace", "path": "/info/version", "value": "1" }])
+        )
+        .is_err());
+        assert!(patch(
+            &mut doc,
+            json!([{ "op": "remove", "path": "/info/version" }])
+        )
+        .is_err());
+        assert!(patch(
+            &mut doc,
+            json!([{ "op": "copy", "from": "/x", "path": "/y" }])
+        )
+        .is_err());
+        assert!(patch(
+            &mut doc,
+            json!([{ "op": "add", "path": "/x/y", "value": 1 }])
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn overlay_removes_array_elements_in_reverse() {
+        let mut doc = json!({ "servers": [{ "url": "a" }, { "url": "b" }, { "url": "c" }] });
+        overlay(
+            &mut doc,
+            json!([{ "target": "$.servers[*]", "remove": true }]),
+        )
+        .unwrap();
+
+        assert_eq!(doc, json!({ "servers": [] }));
+    }
+
+    #[test]
+    fn overlay_merges_updates() {
+        let mut doc = json!({
+            "info": { "title": "A", "contact": { "name": "N" } },
+            "tags": [{ "name": "a" }],
+        });
+        overlay(
+            &mut doc,
+            json!([
+                { "target": "$.info", "update": { "title": "B", "contact": { "email": "e" } } },
+                { "target": "$.tags", "update": [{ "name": "b" }] },
+                { "target": "$.tags", "update": { "name": "c" } },
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            doc,
+            json!({
+                "info": { "title": "B", "contact": { "name": "N", "email": "e" } },
+                "tags": [{ "name": "a" }, { "name": "b" }, { "name": "c" }],
+            })
+        );
+    }
+
+    #[test]
+    fn overlay_update_of_every_operation() {
+        let mut doc = json!({
+            "paths": { "/a": { "get": {}, "post": {} }, "/b": { "get": {} } },
+        });
+        overlay(
+            &mut doc,
+            json!([{ "target": "$.paths.*.get", "update": { "deprecated": true } }]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            doc,
+            json!({
+                "paths": {
+                    "/a": { "get": { "deprecated": true }, "post": {} },
+                    "/b": { "get": { "deprecated": true } },
+                },
+            })
+        );
+    }
+
+    /// Converts a 3.1 document with the given schema as `S`, returning the
+    /// converted schema.
+    fn convert_schema(schema: Value) -> Value {
+        let mut doc = json!({
+            "openapi": "3.1.0",
+            "info": { "title": "T", "version": "1" },
+            "components": { "schemas": { "S": schema } },
+        });
+        convert_openapi_31(&mut doc);
+        doc["components"]["schemas"]["S"].take()
+    }
+
+    #[test]
+    fn nullable_type_lists() {
+        assert_eq!(
+            convert_schema(json!({ "type": ["string", "null"] })),
+            json!({ "type": "string", "nullable": true })
+        );
+    }
+
+    #[test]
+    fn several_types_become_one_of() {
+        assert_eq!(
+            convert_schema(json!({ "type": ["string", "integer"] })),
+            json!({ "oneOf": [{ "type": "string" }, { "type": "integer" }] })
+        );
+        assert_eq!(
+            convert_schema(json!({ "type": ["string", "integer", "null"] })),
+            json!({
+                "oneOf": [{ "type": "string" }, { "type": "integer" }],
+                "nullable": true,
+            })
+        );
+    }
+
+    #[test]
+    fn const_becomes_enum() {
+        assert_eq!(
+            convert_schema(json!({ "type": "string", "const": "cat" })),
+            json!({ "type": "string", "enum": ["cat"] })
+        );
+    }
+
+    #[test]
+    fn numeric_exclusive_bounds() {
+        assert_eq!(
+            convert_schema(json!({
+                "type": "number",
+                "exclusiveMinimum": 0,
+                "exclusiveMaximum": 10,
+            })),
+            json!({
+                "type": "number",
+                "minimum": 0,
+                "exclusiveMinimum": true,
+                "maximum": 10,
+                "exclusiveMaximum": true,
+            })
+        );
+    }
+
+    #[test]
+    fn null_options_are_dropped() {
+        for key in ["oneOf", "anyOf"] {
+            assert_eq!(
+                convert_schema(json!({
+                    key: [{ "$ref": "#/components/schemas/U" }, { "type": "null" }],
+                })),
+                json!({ key: [{ "$ref": "#/components/schemas/U" }], "nullable": true })
+            );
+        }
+    }
+
+    #[test]
+    fn nested_schemas_are_converted() {
+        assert_eq!(
+            convert_schema(json!({
+                "type": "object",
+                "properties": { "a": { "type": ["integer", "null"] } },
+                "items": { "const": 1 },
+            })),
+            json!({
+                "type": "object",
+                "properties": { "a": { "type": "integer", "nullable": true } },
+                "items": { "enum": [1] },
+            })
+        );
+    }
+
+    #[test]
+    fn documents_other_than_3_1_are_unchanged() {
+        let doc = json!({
+            "openapi": "3.0.3",
+            "components": { "schemas": { "S": { "type": ["string", "null"] } } },
+        });
+        let mut converted = doc.clone();
+        convert_openapi_31(&mut converted);
+
+        assert_eq!(converted, doc);
+    }
+
+    #[test]
+    fn features_without_an_equivalent_are_dropped() {
+        let mut doc = json!({
+            "openapi": "3.1.0",
+            "info": { "title": "T", "version": "1", "summary": "S" },
+            "webhooks": {},
+        });
+        convert_openapi_31(&mut doc);
+
+        assert_eq!(
+            doc,
+            json!({
+                "openapi": "3.0.3",
+                "info": { "title": "T", "version": "1" },
+                "paths": {},
+            })
+        );
+    }
+
+    fn sanitize(schemas: Value, keep_generic: bool) -> Value {
+        let mut doc = json!({ "components": { "schemas": schemas } });
+        sanitize_schema_names(&mut doc, keep_generic);
+        doc
+    }
+
+    #[test]
+    fn sanitized_names() {
+        assert_eq!(sanitized_name("Page_SearchResult"), "PageSearchResult");
+        assert_eq!(sanitized_name("app.models.User"), "appModelsUser");
+        assert_eq!(sanitized_name("_private"), "private");
+        assert_eq!(sanitized_name("2fa"), "Schema2fa");
+        assert_eq!(sanitized_name("__"), "Schema");
+        assert_eq!(sanitized_name("User"), "User");
+    }
+
+    #[test]
+    fn sanitize_renames_schemas_and_references() {
+        let doc = sanitize(
+            json!({
+                "Page_User": {
+                    "type": "object",
+                    "properties": {
+                        "items": { "type": "array", "items": { "$ref": "#/components/schemas/User" } },
+                        "next": { "$ref": "#/components/schemas/Page_User/properties/items" },
+                    },
+                },
+                "User": { "type": "object" },
+                "Feed": { "$ref": "#/components/schemas/Page_User" },
+            }),
+            false,
+        );
+
+        assert_eq!(
+            doc["components"]["schemas"],
+            json!({
+                "PageUser": {
+                    "type": "object",
+                    "properties": {
+                        "items": { "type": "array", "items": { "$ref": "#/components/schemas/User" } },
+                        "next": { "$ref": "#/components/schemas/PageUser/properties/items" },
+                    },
+                },
+                "User": { "type": "object" },
+                "Feed": { "$ref": "#/components/schemas/PageUser" },
+            })
+        );
+    }
+
+    #[test]
+    fn sanitize_numbers_taken_names() {
+        let doc = sanitize(
+            json!({
+                "PageUser": { "type": "string" },
+                "Page_User": { "type": "number" },
+                "Page.User": { "type": "boolean" },
+                "Ref": { "$ref": "#/components/schemas/Page.User" },
+            }),
+            false,
+        );
+        let schemas = &doc["components"]["schemas"];
+
+        assert_eq!(schemas["PageUser"], json!({ "type": "string" }));
+        assert!(schemas["PageUser2"].is_object() && schemas["PageUser3"].is_object());
+        assert_ne!(schemas["PageUser2"], schemas["PageUser3"]);
+        let renamed = schemas["Ref"]["$ref"].as_str().unwrap();
+        let renamed = renamed.strip_prefix("#/components/schemas/").unwrap();
+        assert_eq!(schemas[renamed], json!({ "type": "boolean" }));
+        assert!(schemas.get("Page_User").is_none() && schemas.get("Page.User").is_none());
+    }
+
+    #[test]
+    fn sanitize_rewrites_discriminator_mappings() {
+        let doc = sanitize(
+            json!({
+                "Pet": {
+                    "oneOf": [
+                        { "$ref": "#/components/schemas/Pet_Cat" },
+                        { "$ref": "#/components/schemas/Pet_Dog" },
+                    ],
+                    "discriminator": {
+                        "propertyName": "kind",
+                        "mapping": {
+                            "cat": "#/components/schemas/Pet_Cat",
+                            "dog": "Pet_Dog",
+                        },
+                    },
+                },
+                "Pet_Cat": { "type": "object" },
+                "Pet_Dog": { "type": "object" },
+            }),
+            false,
+        );
+        let pet = &doc["components"]["schemas"]["Pet"];
+
+        assert_eq!(
+            pet["discriminator"]["mapping"],
+            json!({ "cat": "#/components/schemas/PetCat", "dog": "PetDog" })
+        );
+        assert_eq!(
+            pet["oneOf"],
+            json!([
+                { "$ref": "#/components/schemas/PetCat" },
+                { "$ref": "#/components/schemas/PetDog" },
+            ])
+        );
+    }
+
+    #[test]
+    fn sanitize_keeps_generic_instances() {
+        let page = |item: &str| {
+            json!({
+                "type": "array",
+                "items": { "$ref": format!("#/components/schemas/{item}") },
+            })
+        };
+        let schemas = json!({
+            "User": { "type": "object" },
+            "Post": { "type": "object" },
+            "Page_User": page("User"),
+            "Page_Post": page("Post"),
+        });
+
+        let kept = sanitize(schemas.clone(), true);
+        assert_eq!(kept["components"]["schemas"], schemas);
+
+        let renamed = sanitize(schemas, false);
+        assert!(renamed["components"]["schemas"].get("PageUser").is_some());
+    }
+}
@@ -1,14 +1,14 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
-use camino::Utf8PathBuf;
-use heck::{ToLowerCamelCase, ToShoutySnakeCase};
+use heck::{ToLowerCamelCase, ToShoutySnakeCase, ToUpperCamelCase};
 use indent_write::indentable::Indentable;
 use itertools::Itertools;
-use openapiv3 as oapi;
 
 use crate::{
-    operation, schema_by_name, schema_ty, simplify_ty, InputApi, Operation, Property, RequestKind,
-    ResponseKind, Type, TypeKind,
+    component_types, constant_descriptions, constant_labels, generic_declarations, lower_api,
+    lower_schema, schema_description, skipped_operations, Config, EmptyResponse, GroupBy, InputApi,
+    Operation, Property, QueryStyle, RequestKind, ResponseKind, Runtime, SseTransport,
+    TsValidators, Type, TypeImports, TypeKind,
 };
 
 #[salsa::tracked]
@@ -17,100 +17,1831 @@ pub fn generate_ts(db: &dyn crate::Db, api: InputApi) -> String {
 
     let mut buf = String::new();
 
-    writeln!(buf, "{}", include_str!("./preamble.ts")).unwrap();
+    writeln!(buf, "{}", banner(db, api)).unwrap();
+    match &api.config(db).runtime_import {
+        Some(from) => writeln!(
+            buf,
+            "{}export * from {from:?};\n",
+            runtime_import(&api.config(db), from, &runtime_features(db, api))
+        )
+        .unwrap(),
+        None => writeln!(
+            buf,
+            "{}",
+            runtime_source(&api.config(db), &runtime_features(db, api))
+        )
+        .unwrap(),
+    }
+
+    let used = lower_api(db, api)
+        .iter()
+        .flat_map(|op| op.types())
+        .chain(component_types(db, api).into_iter().map(|(_, ty)| ty))
+        .chain(generic_declarations(db, api).into_iter().map(|(_, ty)| ty))
+        .flat_map(|ty| ty.custom_types(db))
+        .collect();
+    let customs = custom_type_imports(api.config(db), &used);
+    if !customs.is_empty() {
+        writeln!(buf, "{customs}").unwrap();
+    }
+
+    let codecs = codec_imports(db, api);
+    if !codecs.is_empty() {
+        writeln!(buf, "{codecs}").unwrap();
+    }
+
+    if validators(&api.config(db)) == Some(TsValidators::Zod) {
+        writeln!(buf, "{}", zod_import(&api.config(db))).unwrap();
+    }
+
+    writeln!(buf, "{}", api_object(db, api)).unwrap();
+    writeln!(buf).unwrap();
+
+    if let Some(results) = status_results(db, api) {
+        writeln!(buf, "{results}").unwrap();
+    }
+
+    if let Some(errors) = error_types(db, api) {
+        writeln!(buf, "{errors}").unwrap();
+    }
+
+    if api.config(db).client_factory {
+        writeln!(buf, "{}", client_factory(db, api)).unwrap();
+        writeln!(buf).unwrap();
+    }
+
+    if let Some(queue) = offline_queue(db, api) {
+        writeln!(buf, "{queue}").unwrap();
+    }
+
+    if let Some(keys) = cache_keys(db, api) {
+        writeln!(buf, "{keys}").unwrap();
+        writeln!(buf).unwrap();
+    }
+
+    if let Some(parsers) = query_parsers(db, api) {
+        writeln!(buf, "{parsers}").unwrap();
+    }
+
+    if let Some(batches) = batches(db, api) {
+        writeln!(buf, "{batches}").unwrap();
+    }
+
+    if let Some(helpers) = poll_helpers(db, api) {
+        writeln!(buf, "{helpers}").unwrap();
+    }
+
+    if let Some(constructors) = union_constructors(db, api) {
+        writeln!(buf, "{constructors}").unwrap();
+    }
+
+    if api.config(db).operation_constants {
+        writeln!(buf, "{}", operation_constants(db, api)).unwrap();
+    }
+
+    if api.config(db).path_types {
+        writeln!(buf, "{}", path_types(db, api)).unwrap();
+    }
+
+    if api.config(db).mock_api {
+        writeln!(buf, "{}", mock_api(db, api)).unwrap();
+    }
+
+    let types = component_types(db, api);
+
+    for (name, ty) in &types {
+        write!(buf, "{}", type_declaration(db, api, name, *ty)).unwrap();
+        if api.config(db).validate {
+            write!(buf, "{}", type_guard(db, api, name, *ty)).unwrap();
+        }
+    }
+
+    // NOTE: Instances are checked by type guards as `unknown`, since a guard
+    // for the generic type would need one for its argument as well.
+    for (name, ty) in generic_declarations(db, api) {
+        writeln!(buf, "export type {name}<T> = {};", ty.ts(db)).unwrap();
+    }
+
+    tracing::info!("wrote {} types", types.len());
+
+    buf
+}
+
+/// The same output as [`generate_ts`], split into modules keyed by their path
+/// relative to the output directory: `runtime.ts` holds the preamble (unless
+/// it is imported from elsewhere), `api.ts` the client, `types/<Name>.ts` one
+/// module per component type, and `index.ts` re-exports all of them.
+///
+/// Each type module is computed by its own query, so regenerating after a
+/// change to the document only produces new text for the affected modules.
+#[salsa::tracked]
+pub fn generate_ts_files(db: &dyn crate::Db, api: InputApi) -> BTreeMap<String, String> {
+    let mut files = BTreeMap::new();
+
+    let runtime = match api.config(db).runtime_import {
+        Some(from) => from,
+        None => {
+            files.insert(
+                "runtime.ts".to_string(),
+                format!(
+                    "{}\n{}",
+                    banner(db, api),
+                    runtime_source(&api.config(db), &runtime_features(db, api))
+                ),
+            );
+            local_module(&api.config(db), "./runtime")
+        }
+    };
+
+    let references = lower_api(db, api)
+        .iter()
+        .flat_map(|op| op.types())
+        .flat_map(|ty| ty.references(db))
+        .collect::<BTreeSet<_>>();
+    let customs = lower_api(db, api)
+        .iter()
+        .flat_map(|op| op.types())
+        .flat_map(|ty| ty.custom_types(db))
+        .collect();
+    let validators = validators(&api.config(db));
+    let mut api_module = format!(
+        "{}{}{}{}{}\n{}\n",
+        type_imports(&api.config(db), &references, "./types", validators),
+        if validators == Some(TsValidators::Zod) {
+            zod_import(&api.config(db))
+        } else {
+            String::new()
+        },
+        custom_type_imports(api.config(db), &customs),
+        runtime_import(&api.config(db), &runtime, &runtime_features(db, api)),
+        codec_imports(db, api),
+        api_object(db, api)
+    );
+    if let Some(results) = status_results(db, api) {
+        api_module.push('\n');
+        api_module.push_str(&results);
+    }
+    if let Some(errors) = error_types(db, api) {
+        api_module.push('\n');
+        api_module.push_str(&errors);
+    }
+    if api.config(db).client_factory {
+        api_module.push('\n');
+        api_module.push_str(&client_factory(db, api));
+        api_module.push('\n');
+    }
+    if let Some(queue) = offline_queue(db, api) {
+        api_module.push('\n');
+        api_module.push_str(&queue);
+        api_module.push('\n');
+    }
+    if let Some(keys) = cache_keys(db, api) {
+        api_module.push('\n');
+        api_module.push_str(&keys);
+        api_module.push('\n');
+    }
+    if let Some(parsers) = query_parsers(db, api) {
+        api_module.push('\n');
+        api_module.push_str(&parsers);
+    }
+    if let Some(batches) = batches(db, api) {
+        api_module.push('\n');
+        api_module.push_str(&batches);
+    }
+    if let Some(helpers) = poll_helpers(db, api) {
+        api_module.push('\n');
+        api_module.push_str(&helpers);
+    }
+    if let Some(constructors) = union_constructors(db, api) {
+        api_module.push('\n');
+        api_module.push_str(&constructors);
+    }
+    if api.config(db).operation_constants {
+        api_module.push('\n');
+        api_module.push_str(&operation_constants(db, api));
+    }
+    if api.config(db).path_types {
+        api_module.push('\n');
+        api_module.push_str(&path_types(db, api));
+    }
+    files.insert("api.ts".to_string(), api_module);
+
+    let types = component_types(db, api);
+    // NOTE: The mock is meant for tests, so it is left out of the index.
+    if api.config(db).mock_api {
+        let names = types.iter().map(|(name, _)| name.clone()).collect();
+        files.insert(
+            "mock.ts".to_string(),
+            format!(
+                "{}import type {{ api }} from {:?};\n\n{}",
+                type_imports(&api.config(db), &names, "./types", None),
+                local_module(&api.config(db), "./api"),
+                mock_api(db, api)
+            ),
+        );
+    }
+    for (name, _) in &types {
+        files.insert(
+            format!("types/{name}.ts"),
+            ts_type_module(db, api, name.clone()),
+        );
+    }
+
+    files.insert(
+        "index.ts".to_string(),
+        [runtime, local_module(&api.config(db), "./api")]
+            .into_iter()
+            .chain(
+                types
+                    .iter()
+                    .map(|(name, _)| local_module(&api.config(db), &format!("./types/{name}"))),
+            )
+            .map(|module| format!("export * from {module:?};\n"))
+            .collect(),
+    );
+
+    files
+}
+
+/// The runtime helpers used by generated clients as a standalone module, for
+/// use with [`Config::runtime_import`]. Only the options of the configuration
+/// affecting the runtime are used.
+pub fn generate_ts_runtime(config: &Config) -> String {
+    format!(
+        "/* abeye runtime {} */\n{}",
+        env!("CARGO_PKG_VERSION"),
+        runtime_source(config, &RuntimeFeature::all(config))
+    )
+}
+
+/// The size of the TypeScript generated for a single type or operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeEntry {
+    /// Either `"type"` or `"operation"`.
+    pub kind: &'static str,
+    pub name: String,
+    pub lines: usize,
+    pub characters: usize,
+    /// The number of distinct component types referenced.
+    pub references: usize,
+}
+
+/// The size of the declaration of every component type and of the method of
+/// every operation in the generated client, largest first.
+pub fn ts_size_report(db: &dyn crate::Db, api: InputApi) -> Vec<SizeEntry> {
+    let entry = |kind, name: String, text: String, references: BTreeSet<String>| SizeEntry {
+        kind,
+        name,
+        lines: text.lines().count(),
+        characters: text.chars().count(),
+        references: references.len(),
+    };
+
+    let types = component_types(db, api).into_iter().map(|(name, ty)| {
+        let text = type_declaration(db, api, &name, ty);
+        let mut references = ty.references(db);
+        references.remove(&name);
+        entry("type", name, text, references)
+    });
+    let operations = lower_api(db, api).into_iter().map(|op| {
+        let (name, fn_impl) = op.ts(db, api);
+        let references = op
+            .types()
+            .into_iter()
+            .flat_map(|ty| ty.references(db))
+            .collect();
+        entry(
+            "operation",
+            name.clone(),
+            format!("{name}: {fn_impl},"),
+            references,
+        )
+    });
+
+    types
+        .chain(operations)
+        .sorted_by(|a, b| b.characters.cmp(&a.characters))
+        .collect()
+}
+
+/// A piece of the runtime, included only if the generated client uses it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum RuntimeFeature {
+    Core,
+    PlatformBrowser,
+    PlatformNode,
+    Json,
+    Empty,
+    EventStream,
+    EventStreamFetch,
+    Telemetry,
+    Trace,
+    Csrf,
+    Dedupe,
+    Offline,
+    IfMatch,
+    CacheKey,
+    Query,
+    Poll,
+    RateLimit,
+    Factory,
+    PathTypes,
+    Validate,
+    Custom,
+    Csv,
+    Blob,
+    Multipart,
+    Form,
+    Result,
+    Errors,
+    Redirect,
+    Headers,
+}
+
+impl RuntimeFeature {
+    /// Every feature applicable with the given configuration.
+    fn all(config: &Config) -> BTreeSet<RuntimeFeature> {
+        BTreeSet::from([
+            RuntimeFeature::Core,
+            RuntimeFeature::platform(config),
+            RuntimeFeature::Json,
+            RuntimeFeature::Empty,
+            RuntimeFeature::event_stream(config),
+            RuntimeFeature::Telemetry,
+            RuntimeFeature::Trace,
+            RuntimeFeature::Csrf,
+            RuntimeFeature::Dedupe,
+            RuntimeFeature::Offline,
+            RuntimeFeature::IfMatch,
+            RuntimeFeature::CacheKey,
+            RuntimeFeature::Query,
+            RuntimeFeature::Poll,
+            RuntimeFeature::RateLimit,
+            RuntimeFeature::Factory,
+            RuntimeFeature::PathTypes,
+            RuntimeFeature::Validate,
+            RuntimeFeature::Custom,
+            RuntimeFeature::Csv,
+            RuntimeFeature::Blob,
+            RuntimeFeature::Multipart,
+            RuntimeFeature::Form,
+            RuntimeFeature::Result,
+            RuntimeFeature::Errors,
+            RuntimeFeature::Redirect,
+            RuntimeFeature::Headers,
+        ])
+    }
+
+    fn platform(config: &Config) -> RuntimeFeature {
+        match config.runtime {
+            Runtime::Browser => RuntimeFeature::PlatformBrowser,
+            Runtime::Node => RuntimeFeature::PlatformNode,
+        }
+    }
+
+    fn event_stream(config: &Config) -> RuntimeFeature {
+        match sse_transport(config) {
+            SseTransport::EventSource => RuntimeFeature::EventStream,
+            SseTransport::Fetch => RuntimeFeature::EventStreamFetch,
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            RuntimeFeature::Core => include_str!("./preamble/core.ts"),
+            RuntimeFeature::PlatformBrowser => include_str!("./preamble/platform-browser.ts"),
+            RuntimeFeature::PlatformNode => include_str!("./preamble/platform-node.ts"),
+            RuntimeFeature::Json => include_str!("./preamble/json.ts"),
+            RuntimeFeature::Empty => include_str!("./preamble/empty.ts"),
+            RuntimeFeature::EventStream => include_str!("./preamble/sse.ts"),
+            RuntimeFeature::EventStreamFetch => include_str!("./preamble/sse-fetch.ts"),
+            RuntimeFeature::Telemetry => include_str!("./preamble/telemetry.ts"),
+            RuntimeFeature::Trace => include_str!("./preamble/trace.ts"),
+            RuntimeFeature::Csrf => include_str!("./preamble/csrf.ts"),
+            RuntimeFeature::Dedupe => include_str!("./preamble/dedupe.ts"),
+            RuntimeFeature::Offline => include_str!("./preamble/offline.ts"),
+            RuntimeFeature::IfMatch => include_str!("./preamble/if-match.ts"),
+            RuntimeFeature::CacheKey => include_str!("./preamble/cache-key.ts"),
+            RuntimeFeature::Query => include_str!("./preamble/query.ts"),
+            RuntimeFeature::Poll => include_str!("./preamble/poll.ts"),
+            RuntimeFeature::RateLimit => include_str!("./preamble/rate-limit.ts"),
+            RuntimeFeature::Factory => include_str!("./preamble/factory.ts"),
+            RuntimeFeature::PathTypes => include_str!("./preamble/path-types.ts"),
+            RuntimeFeature::Validate => include_str!("./preamble/validate.ts"),
+            RuntimeFeature::Custom => include_str!("./preamble/custom.ts"),
+            RuntimeFeature::Csv => include_str!("./preamble/csv.ts"),
+            RuntimeFeature::Blob => include_str!("./preamble/blob.ts"),
+            RuntimeFeature::Multipart => include_str!("./preamble/multipart.ts"),
+            RuntimeFeature::Form => include_str!("./preamble/form.ts"),
+            RuntimeFeature::Result => include_str!("./preamble/result.ts"),
+            RuntimeFeature::Errors => include_str!("./preamble/errors.ts"),
+            RuntimeFeature::Redirect => include_str!("./preamble/redirect.ts"),
+            RuntimeFeature::Headers => include_str!("./preamble/headers.ts"),
+        }
+    }
+
+    /// The exports used by the generated client.
+    fn exports(self) -> &'static [&'static str] {
+        match self {
+            RuntimeFeature::Core => &[
+                "requestPlain",
+                "parseJson",
+                "stringifyJson",
+                "queryString",
+                "type ApiOptions",
+            ],
+            RuntimeFeature::PlatformBrowser | RuntimeFeature::PlatformNode => &[],
+            RuntimeFeature::Json => &["requestJson"],
+            RuntimeFeature::Empty => &["requestEmpty", "requestVoid"],
+            RuntimeFeature::EventStream | RuntimeFeature::EventStreamFetch => &["sse"],
+            RuntimeFeature::Telemetry => &["withMetric"],
+            RuntimeFeature::Trace => &["withTrace"],
+            RuntimeFeature::Csrf => &["withCsrf"],
+            RuntimeFeature::Dedupe => &["dedupe"],
+            RuntimeFeature::Offline => &["createOfflineQueue"],
+            RuntimeFeature::IfMatch => &["withIfMatch"],
+            RuntimeFeature::CacheKey => &["cacheKey"],
+            RuntimeFeature::Query => &["queryArray", "queryCoerce", "queryParam"],
+            RuntimeFeature::Poll => &["poll", "type PollOptions"],
+            RuntimeFeature::RateLimit => &["withRateLimit"],
+            RuntimeFeature::Factory => &["mergeOptions"],
+            RuntimeFeature::PathTypes => &[],
+            RuntimeFeature::Validate => &["guards"],
+            RuntimeFeature::Custom => &["requestCustom"],
+            RuntimeFeature::Csv => &["requestCsv"],
+            RuntimeFeature::Blob => &["requestBlob"],
+            RuntimeFeature::Multipart => &["formData"],
+            RuntimeFeature::Form => &["formUrlEncoded"],
+            RuntimeFeature::Result => &["requestResult"],
+            RuntimeFeature::Errors => &["ApiError", "isApiError", "withErrors"],
+            RuntimeFeature::Redirect => &["requestRedirect"],
+            RuntimeFeature::Headers => &["withHeaders"],
+        }
+    }
+}
+
+/// The pieces of the runtime used by the operations of the API.
+fn runtime_features(db: &dyn crate::Db, api: InputApi) -> BTreeSet<RuntimeFeature> {
+    let config = api.config(db);
+    let mut features = BTreeSet::from([RuntimeFeature::Core, RuntimeFeature::platform(&config)]);
+    if config.telemetry {
+        features.insert(RuntimeFeature::Telemetry);
+    }
+    if config.typed_errors {
+        features.insert(RuntimeFeature::Errors);
+    }
+    if config.client_factory {
+        features.insert(RuntimeFeature::Factory);
+    }
+    if config.path_types {
+        features.insert(RuntimeFeature::PathTypes);
+    }
+    if validators(&config) == Some(TsValidators::Guards) {
+        features.insert(RuntimeFeature::Validate);
+    }
+
+    if !batch_operations(db, api).is_empty() {
+        features.insert(RuntimeFeature::Json);
+    }
+    for op in lower_api(db, api) {
+        if op.traces(db, api) {
+            features.insert(RuntimeFeature::Trace);
+        }
+        if op.csrf_header(db, api).is_some() {
+            features.insert(RuntimeFeature::Csrf);
+        }
+        if op.dedupes(db, api) {
+            features.insert(RuntimeFeature::Dedupe);
+        }
+        if config.offline_queue && op.is_mutation() {
+            features.insert(RuntimeFeature::Offline);
+        }
+        if op.if_match {
+            features.insert(RuntimeFeature::IfMatch);
+        }
+        if !op.headers.is_empty() {
+            features.insert(RuntimeFeature::Headers);
+        }
+        if op.rate_limited {
+            features.insert(RuntimeFeature::RateLimit);
+        }
+        if config.cache_keys && op.method == "GET" {
+            features.insert(RuntimeFeature::CacheKey);
+        }
+        if config.query_parsers && op.method == "GET" && !op.query.is_empty() {
+            features.insert(RuntimeFeature::Query);
+        }
+        if config.poll_helpers && op.is_pollable(db, api) {
+            features.insert(RuntimeFeature::Poll);
+        }
+        match op.body {
+            Some(RequestKind::Multipart(_)) => features.insert(RuntimeFeature::Multipart),
+            Some(RequestKind::Form(_)) => features.insert(RuntimeFeature::Form),
+            _ => false,
+        };
+        if op.uses_codec() {
+            features.insert(RuntimeFeature::Custom);
+            continue;
+        }
+        if op.status_result(db, api).is_some() {
+            features.insert(RuntimeFeature::Result);
+            continue;
+        }
+        match op.response {
+            Some(ResponseKind::Empty) => features.insert(RuntimeFeature::Empty),
+            Some(ResponseKind::Plain | ResponseKind::Xml) | None => false,
+            Some(ResponseKind::Json(_)) => features.insert(RuntimeFeature::Json),
+            Some(ResponseKind::EventStream(_)) => {
+                features.insert(RuntimeFeature::event_stream(&config))
+            }
+            Some(ResponseKind::Csv(_)) => features.insert(RuntimeFeature::Csv),
+            Some(ResponseKind::Blob) => features.insert(RuntimeFeature::Blob),
+            Some(ResponseKind::Redirect) => features.insert(RuntimeFeature::Redirect),
+            Some(ResponseKind::Custom(_)) => unreachable!(),
+        };
+    }
+
+    features
+}
+
+/// The transport used for event streams, which is always `fetch` outside of
+/// browsers.
+fn sse_transport(config: &Config) -> SseTransport {
+    match config.runtime {
+        Runtime::Browser => config.sse_transport,
+        Runtime::Node => SseTransport::Fetch,
+    }
+}
+
+fn runtime_source(config: &Config, features: &BTreeSet<RuntimeFeature>) -> String {
+    format!(
+        "{}{}",
+        json_hooks(config),
+        features.iter().map(|feature| feature.source()).join("\n")
+    )
+}
+
+/// The `JSON_REVIVER` and `JSON_REPLACER` used by the runtime for every JSON
+/// body, imported per [`Config::json_hooks`] or else `undefined`.
+fn json_hooks(config: &Config) -> String {
+    let hooks = config.json_hooks.as_ref();
+    let imported = [
+        (
+            "JSON_REVIVER",
+            hooks.and_then(|hooks| hooks.revive.as_ref()),
+        ),
+        (
+            "JSON_REPLACER",
+            hooks.and_then(|hooks| hooks.serialize.as_ref()),
+        ),
+    ];
+
+    let mut imports = Vec::new();
+    let mut source = String::new();
+    for (alias, name) in imported {
+        match name {
+            Some(name) => imports.push(format!("{name} as {alias}")),
+            None => source.push_str(&format!("const {alias} = undefined;\n")),
+        }
+    }
+    match hooks {
+        Some(hooks) if !imports.is_empty() => format!(
+            "import {{ {} }} from {:?};\n{source}",
+            imports.iter().format(", "),
+            hooks.module
+        ),
+        _ => source,
+    }
+}
+
+fn runtime_import(config: &Config, from: &str, features: &BTreeSet<RuntimeFeature>) -> String {
+    let names = features
+        .iter()
+        .flat_map(|feature| feature.exports())
+        .copied()
+        .collect_vec();
+    let statement = |keyword: &str, names: &[&str]| {
+        format!(
+            "{keyword} {{\n{}\n}} from {from:?};\n",
+            names.iter().map(|name| format!("  {name},")).format("\n")
+        )
+    };
+    match config.type_imports {
+        TypeImports::Inline => statement("import", &names),
+        TypeImports::Separate => {
+            let (types, values): (Vec<_>, Vec<_>) = names
+                .into_iter()
+                .partition(|name| name.starts_with("type "));
+            let types = types
+                .iter()
+                .map(|name| name.trim_start_matches("type "))
+                .collect_vec();
+            [("import type", types), ("import", values)]
+                .into_iter()
+                .filter(|(_, names)| !names.is_empty())
+                .map(|(keyword, names)| statement(keyword, &names))
+                .collect()
+        }
+    }
+}
+
+#[salsa::tracked]
+pub(crate) fn ts_type_module(db: &dyn crate::Db, api: InputApi, name: String) -> String {
+    let ty = lower_schema(db, api, &name).unwrap();
+
+    let mut references = ty.references(db);
+    references.remove(&name);
+
+    let config = api.config(db);
+    let customs = custom_type_imports(config, &ty.custom_types(db));
+    let Some(validators) = validators(config) else {
+        return format!(
+            "{}{customs}{}",
+            type_imports(config, &references, ".", None),
+            type_declaration(db, api, &name, ty)
+        );
+    };
+    if validators == TsValidators::Zod {
+        return format!(
+            "{}{customs}{}{}{}",
+            type_imports(config, &references, ".", Some(validators)),
+            zod_import(config),
+            type_declaration(db, api, &name, ty),
+            type_guard(db, api, &name, ty)
+        );
+    }
+
+    // NOTE: Type modules live a directory below the runtime, so relative
+    // specifiers must be adjusted.
+    let runtime = match &config.runtime_import {
+        Some(from) if from.starts_with("./") => format!("../{}", &from[2..]),
+        Some(from) if from.starts_with("../") => format!("../{from}"),
+        Some(from) => from.clone(),
+        None => local_module(config, "../runtime"),
+    };
+    format!(
+        "{}{customs}import {{ guards }} from {runtime:?};\n{}{}",
+        type_imports(config, &references, ".", Some(validators)),
+        type_declaration(db, api, &name, ty),
+        type_guard(db, api, &name, ty)
+    )
+}
+
+/// Imports of the given component types from their modules in `dir`, along
+/// with their type guards or schemas depending on `validators`.
+fn type_imports(
+    config: &Config,
+    references: &BTreeSet<String>,
+    dir: &str,
+    validators: Option<TsValidators>,
+) -> String {
+    references
+        .iter()
+        .map(|name| {
+            let module = local_module(config, &format!("{dir}/{name}"));
+            let value = match validators {
+                None => None,
+                Some(TsValidators::Guards) => Some(format!("is{name}")),
+                Some(TsValidators::Zod) => Some(format!("{name}Schema")),
+            };
+            match (value, config.type_imports) {
+                (None, _) => format!("import type {{ {name} }} from {module:?};\n"),
+                (Some(value), TypeImports::Inline) => {
+                    format!("import {{ type {name}, {value} }} from {module:?};\n")
+                }
+                (Some(value), TypeImports::Separate) => format!(
+                    "import type {{ {name} }} from {module:?};\n\
+                     import {{ {value} }} from {module:?};\n"
+                ),
+            }
+        })
+        .collect()
+}
+
+fn zod_import(config: &Config) -> String {
+    let module = config.target.zod.module.as_deref().unwrap_or("zod");
+    format!("import {{ z }} from {module:?};\n")
+}
+
+/// The specifier of a module generated alongside the importing one with
+/// [`crate::TsConfig::import_extension`] appended.
+fn local_module(config: &Config, path: &str) -> String {
+    format!("{path}{}", config.target.ts.import_extension)
+}
+
+/// How values are validated at runtime, if at all.
+fn validators(config: &Config) -> Option<TsValidators> {
+    config.validate.then_some(config.ts_validators)
+}
+
+/// Imports of the configured custom types among `used` which name a module
+/// to import them from.
+fn custom_type_imports(config: &Config, used: &BTreeSet<String>) -> String {
+    let mut imports = BTreeMap::<&str, BTreeSet<&str>>::new();
+    for custom in config
+        .schema_types
+        .values()
+        .chain(config.format_types.values())
+    {
+        if let Some(from) = &custom.from {
+            if used.contains(&custom.ts) {
+                imports.entry(from).or_default().insert(&custom.ts);
+            }
+        }
+    }
+
+    imports
+        .into_iter()
+        .map(|(module, names)| {
+            format!(
+                "import type {{ {} }} from {module:?};\n",
+                names.iter().format(", ")
+            )
+        })
+        .collect()
+}
+
+/// The `api` object holding a method per operation.
+///
+/// With [`Config::tag_clients`] the methods are instead defined on an object
+/// per tag, which `api` combines, so bundles using the operations of a single
+/// tag only include those. Operations with several tags are defined on the
+/// object of each.
+fn api_object(db: &dyn crate::Db, api: InputApi) -> String {
+    let operations = lower_api(db, api)
+        .into_iter()
+        .map(|op| {
+            let (name, fn_impl) = op.ts(db, api);
+            let entry = match op.jsdoc(db, api) {
+                Some(doc) => format!("{doc}\n{name}: {fn_impl},"),
+                None => format!("{name}: {fn_impl},"),
+            };
+            (op, entry)
+        })
+        .collect_vec();
+
+    tracing::info!("wrote {} operation", operations.len());
+
+    let placeholders = if api.config(db).keep_going {
+        skipped_operations(db, api)
+            .into_iter()
+            .map(|(operation, diagnostic)| {
+                format!("// NOTE: {operation} was skipped: {}", diagnostic.message)
+            })
+            .collect_vec()
+    } else {
+        Vec::new()
+    };
+
+    let object = |name: &str, entries: &[String]| {
+        format!(
+            "export const {name} = {{\n{}\n}};",
+            entries.iter().format("\n").indented("  ")
+        )
+    };
+
+    if !api.config(db).tag_clients {
+        let mut entries = grouped(db, api, operations);
+        entries.extend(placeholders);
+        return object("api", &entries);
+    }
+
+    let mut clients = BTreeMap::<String, Vec<String>>::new();
+    let mut untagged = Vec::new();
+    for (op, entry) in &operations {
+        if op.tags.is_empty() {
+            untagged.push(entry.clone());
+        }
+        for tag in &op.tags {
+            clients
+                .entry(format!("{}Api", tag.to_lower_camel_case()))
+                .or_default()
+                .push(entry.clone());
+        }
+    }
+
+    // NOTE: Grouped by tag, `api` only has each method under its first tag,
+    // so it is defined on its own rather than combining the tag clients.
+    let combined = match api.config(db).group_by {
+        GroupBy::Tag => grouped(db, api, operations),
+        GroupBy::None => clients
+            .keys()
+            .map(|client| format!("...{client},"))
+            .chain(untagged)
+            .collect_vec(),
+    };
+    let combined = combined.into_iter().chain(placeholders).collect_vec();
+    clients
+        .iter()
+        .map(|(client, entries)| object(client, entries))
+        .chain([object("api", &combined)])
+        .join("\n\n")
+}
+
+/// The entries of the `api` object, or of one mirroring it, nested under an
+/// object per [`Operation::group`] when grouping by tag.
+fn grouped(db: &dyn crate::Db, api: InputApi, entries: Vec<(Operation, String)>) -> Vec<String> {
+    let mut groups = BTreeMap::<String, Vec<String>>::new();
+    let mut ungrouped = Vec::new();
+    for (op, entry) in entries {
+        match op.group(db, api) {
+            Some(group) => groups.entry(group).or_default().push(entry),
+            None => ungrouped.push(entry),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(group, entries)| {
+            format!(
+                "{group}: {{\n{}\n}},",
+                entries.iter().format("\n").indented("  ")
+            )
+        })
+        .chain(ungrouped)
+        .collect()
+}
+
+/// The object literal implementing both `Encoder` and `Decoder` with the
+/// hooks configured for the media type.
+fn codec(config: &Config, media_type: &str) -> String {
+    let codec = &config.media_types[media_type];
+    format!(
+        "{{ mediaType: {media_type:?}, encode: {}, decode: {} }}",
+        codec.encode, codec.decode
+    )
+}
+
+/// Imports of the hooks of every media type codec used by the operations.
+fn codec_imports(db: &dyn crate::Db, api: InputApi) -> String {
+    let config = api.config(db);
+
+    let mut imports = BTreeMap::<&str, BTreeSet<&str>>::new();
+    let operations = lower_api(db, api);
+    let used = operations.iter().flat_map(|op| {
+        let request = match op.body {
+            Some(RequestKind::Custom(_)) => op.request_media_type.as_ref(),
+            _ => None,
+        };
+        let response = match op.response {
+            Some(ResponseKind::Custom(_)) => op.response_media_type.as_ref(),
+            _ => None,
+        };
+        request.into_iter().chain(response)
+    });
+    for media_type in used {
+        let codec = &config.media_types[media_type];
+        imports
+            .entry(&codec.module)
+            .or_default()
+            .extend([codec.encode.as_str(), codec.decode.as_str()]);
+    }
+
+    imports
+        .into_iter()
+        .map(|(module, names)| {
+            format!(
+                "import {{ {} }} from {module:?};\n",
+                names.iter().format(", ")
+            )
+        })
+        .collect()
+}
+
+/// How `requestCsv` converts the value of every column of the row type. Only
+/// numbers and booleans are converted, everything else is kept as a string.
+fn csv_columns(db: &dyn crate::Db, api: InputApi, row: Type) -> String {
+    let columns = match row.resolve(db, api).kind(db) {
+        TypeKind::Object(properties) => properties
+            .iter()
+            .map(|(name, prop)| {
+                let column = match prop.ty.resolve(db, api).kind(db) {
+                    TypeKind::Number => "number",
+                    TypeKind::Boolean => "boolean",
+                    _ => "string",
+                };
+                format!("{name:?}: {column:?}")
+            })
+            .join(", "),
+        _ => String::new(),
+    };
+    format!("{{ {columns} }}")
+}
+
+/// The `createApi` factory, returning an object with the same methods as
+/// `api` which use the given options unless overridden by the call.
+fn client_factory(db: &dyn crate::Db, api: InputApi) -> String {
+    let methods = lower_api(db, api)
+        .into_iter()
+        .map(|op| {
+            let name = op.name(db, api);
+            let args = op.ts_arguments(db, api);
+            let method = format!(
+                "{name}: ({}) =>\n  api.{}({}),",
+                args.iter()
+                    .map(|(arg, ty)| format!("{arg}: {}", ty.ts(db)))
+                    .chain(["options?: ApiOptions".to_string()])
+                    .format(", "),
+                op.api_member(db, api),
+                args.iter()
+                    .map(|(arg, _)| arg.trim_end_matches('?').to_string())
+                    .chain(["mergeOptions(defaults, options)".to_string()])
+                    .format(", ")
+            );
+            (op, method)
+        })
+        .collect_vec();
+    let methods = grouped(db, api, methods);
+
+    format!(
+        "export const createApi = (defaults: ApiOptions) => ({{\n{}\n}});",
+        methods.iter().format("\n").indented("  ")
+    )
+}
+
+/// The `OfflineMutation` union with an entry per mutating operation, and the
+/// `offlineQueue` replaying them through the `api` object. `None` unless
+/// enabled and the API has any mutating operations.
+fn offline_queue(db: &dyn crate::Db, api: InputApi) -> Option<String> {
+    if !api.config(db).offline_queue {
+        return None;
+    }
+
+    let mutations = lower_api(db, api)
+        .into_iter()
+        .filter(|op| op.is_mutation())
+        .map(|op| {
+            (
+                op.name(db, api),
+                op.api_member(db, api),
+                op.ts_arguments(db, api),
+            )
+        })
+        .collect_vec();
+    if mutations.is_empty() {
+        return None;
+    }
+
+    let entries = mutations
+        .iter()
+        .map(|(name, _, args)| {
+            let fields = [format!("operation: {name:?};")]
+                .into_iter()
+                .chain(
+                    args.iter()
+                        .map(|(arg, ty)| format!("{arg}: {};", ty.ts(db))),
+                )
+                .join(" ");
+            format!("| {{ {fields} }}")
+        })
+        .format("\n")
+        .indented("  ");
+    let cases = mutations
+        .iter()
+        .map(|(name, member, args)| {
+            format!(
+                "case {name:?}:\n  return api.{member}({}).data;",
+                args.iter()
+                    .map(|(arg, _)| format!("mutation.{}", arg.trim_end_matches('?')))
+                    .format(", ")
+            )
+        })
+        .format("\n")
+        .indented("    ");
+
+    Some(format!(
+        "export type OfflineMutation =\n{entries};\n\n\
+         export const offlineQueue = createOfflineQueue<OfflineMutation>(\n  \
+         \"abeye-offline-queue\",\n  \
+         (mutation) => {{\n    switch (mutation.operation) {{\n{cases}\n    }}\n  }}\n);\n"
+    ))
+}
+
+/// The operations with an `x-batch` extension, listing the operations whose
+/// requests they accept, by name or as `METHOD /path`, along with those
+/// members. Members not resolving to JSON or an empty response are left out.
+fn batch_operations(db: &dyn crate::Db, api: InputApi) -> Vec<(Operation, Vec<Operation>)> {
+    let operations = lower_api(db, api);
+    operations
+        .iter()
+        .filter_map(|op| {
+            let members = op.extensions.get("x-batch")?.as_array()?;
+            let members = members
+                .iter()
+                .filter_map(|member| {
+                    let member = member.as_str()?;
+                    let found = operations.iter().find(|candidate| {
+                        candidate.name(db, api) == member
+                            || format!("{} {}", candidate.method, candidate.path) == member
+                    });
+                    match found {
+                        Some(found)
+                            if !found.uses_codec()
+                                && matches!(
+                                    found.response,
+                                    Some(ResponseKind::Json(_) | ResponseKind::Empty)
+                                ) =>
+                        {
+                            Some(found.clone())
+                        }
+                        Some(_) => {
+                            tracing::warn!(
+                                batch = op.name(db, api),
+                                member,
+                                "only operations responding with JSON can be batched"
+                            );
+                            None
+                        }
+                        None => {
+                            tracing::warn!(batch = op.name(db, api), member, "unknown operation");
+                            None
+                        }
+                    }
+                })
+                .collect_vec();
+            Some((op.clone(), members))
+        })
+        .collect()
+}
+
+/// A `batch` helper for every batch operation, sending the requests of several
+/// member operations in a single request, and splitting the response into the
+/// typed result of each. With several batch operations, they are named after
+/// the operation, such as `usersBatch`.
+///
+/// The batch operation receives an array of `{ method, url, body }` and must
+/// respond with an array of `{ status, body }` in the same order.
+fn batches(db: &dyn crate::Db, api: InputApi) -> Option<String> {
+    let batches = batch_operations(db, api);
+    if batches.is_empty() {
+        return None;
+    }
+
+    let single = batches.len() == 1;
+    let helpers = batches
+        .iter()
+        .map(|(batch, members)| {
+            let helper = if single {
+                "batch".to_string()
+            } else {
+                format!("{}Batch", batch.name(db, api))
+            };
+            let prefix = helper.to_upper_camel_case();
+
+            let entries = members
+                .iter()
+                .map(|op| {
+                    let args = op.ts_arguments(db, api);
+                    let call = args
+                        .iter()
+                        .map(|(arg, ty)| format!("{arg}: {};", ty.ts(db)))
+                        .join(" ");
+                    let data = match op.response {
+                        Some(ResponseKind::Json(ty)) => ty.ts(db),
+                        _ => "Record<string, never>".to_string(),
+                    };
+                    format!(
+                        "{:?}: {{ call: {{ {call} }}; data: {data} }};",
+                        op.name(db, api)
+                    )
+                })
+                .collect_vec();
+            let cases = members
+                .iter()
+                .map(|op| {
+                    let args = op.ts_arguments(db, api);
+                    let names = args
+                        .iter()
+                        .map(|(arg, _)| arg.trim_end_matches('?'))
+                        .collect_vec();
+                    let destructure = if names.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" const {{ {} }} = call;", names.join(", "))
+                    };
+                    let body = if op.body.is_some() { "body" } else { "undefined" };
+                    format!(
+                        "case {:?}: {{{destructure} return {{ method: {:?}, url: {}, body: {body} }}; }}",
+                        op.name(db, api),
+                        op.method,
+                        op.url(db, api),
+                    )
+                })
+                .collect_vec();
+
+            format!(
+                "export type {prefix}Operations = {{\n{}\n}};\n\
+                 export type {prefix}Call = {{\n  \
+                 [K in keyof {prefix}Operations]: {{ operation: K }} & {prefix}Operations[K][\"call\"];\n\
+                 }}[keyof {prefix}Operations];\n\
+                 export type {prefix}Result<C extends {prefix}Call> =\n  \
+                 | {{ ok: true; data: {prefix}Operations[C[\"operation\"]][\"data\"] }}\n  \
+                 | {{ ok: false; status: number; error: unknown }};\n\
+                 export const {helper} = <const C extends readonly {prefix}Call[]>(\n  \
+                 calls: C,\n  \
+                 options?: ApiOptions\n\
+                 ): {{\n  \
+                 data: Promise<{{ -readonly [K in keyof C]: {prefix}Result<C[K]> }}>;\n  \
+                 cancel: (reason?: string) => void;\n\
+                 }} => {{\n  \
+                 const requests = calls.map((call: {prefix}Call) => {{\n    \
+                 switch (call.operation) {{\n{}\n    }}\n  \
+                 }});\n  \
+                 const {{ data, cancel }} = requestJson<{{ status: number; body: unknown }}[]>(\n    \
+                 {:?},\n    \
+                 `{}`,\n    \
+                 requests,\n    \
+                 options\n  \
+                 );\n  \
+                 return {{\n    \
+                 data: data.then(\n      \
+                 (responses) =>\n        \
+                 responses.map(({{ status, body }}) =>\n          \
+                 status >= 200 && status < 300\n            \
+                 ? {{ ok: true, data: body ?? {{}} }}\n            \
+                 : {{ ok: false, status, error: body }}\n        \
+                 ) as unknown as {{ -readonly [K in keyof C]: {prefix}Result<C[K]> }}\n    \
+                 ),\n    \
+                 cancel,\n  \
+                 }};\n\
+                 }};\n",
+                entries.iter().format("\n").indented("  "),
+                cases.iter().format("\n").indented("      "),
+                batch.method,
+                batch.path,
+            )
+        })
+        .collect_vec();
+
+    Some(helpers.join("\n"))
+}
+
+/// A `pollX` function per GET operation responding with JSON, taking the
+/// arguments of the method along with the `PollOptions` and returning an
+/// async iterator of the responses. `None` unless enabled and the API has any
+/// such operations.
+fn poll_helpers(db: &dyn crate::Db, api: InputApi) -> Option<String> {
+    if !api.config(db).poll_helpers {
+        return None;
+    }
+
+    let helpers = lower_api(db, api)
+        .into_iter()
+        .filter(|op| op.is_pollable(db, api))
+        .map(|op| {
+            let name = op.name(db, api);
+            let Some(ResponseKind::Json(ty)) = op.response else {
+                unreachable!()
+            };
+            let args = op.ts_arguments(db, api);
+            format!(
+                "export const poll{} = (\n{}\n) =>\n  poll(() => api.{}({}), polling);\n",
+                name.to_upper_camel_case(),
+                // NOTE: Optional arguments can't precede the polling options.
+                args.iter()
+                    .map(|(arg, ty)| match arg.strip_suffix('?') {
+                        Some(arg) => format!("{arg}: {} | undefined,", ty.ts(db)),
+                        None => format!("{arg}: {},", ty.ts(db)),
+                    })
+                    .chain([
+                        format!("polling: PollOptions<{}>,", ty.ts(db)),
+                        "options?: ApiOptions".to_string(),
+                    ])
+                    .format("\n")
+                    .indented("  "),
+                op.api_member(db, api),
+                args.iter()
+                    .map(|(arg, _)| arg.trim_end_matches('?'))
+                    .chain(["options"])
+                    .format(", "),
+            )
+        })
+        .collect_vec();
+    if helpers.is_empty() {
+        return None;
+    }
+
+    Some(helpers.join("\n"))
+}
+
+/// A `makeX` object per component schema used as a request body which is a
+/// tagged union, with a function per variant taking its other fields and
+/// filling in the tag. `None` unless enabled and there are any such schemas.
+fn union_constructors(db: &dyn crate::Db, api: InputApi) -> Option<String> {
+    if !api.config(db).union_constructors {
+        return None;
+    }
+
+    let names = lower_api(db, api)
+        .iter()
+        .filter_map(|op| match op.body?.ty().kind(db) {
+            TypeKind::Reference(name) => Some(name),
+            _ => None,
+        })
+        .collect::<BTreeSet<_>>();
+    let constructors = names
+        .into_iter()
+        .filter_map(|name| {
+            let union = Type::new(db, TypeKind::Reference(name.clone())).tagged_union(db, api)?;
+            let property = &union.property;
+            let variants = union
+                .variants
+                .iter()
+                .map(|variant| {
+                    let tag = format!("{property:?}: {:?}", variant.tag);
+                    if variant.fields == 0 {
+                        format!("{}: (): {name} => ({{ {tag} }}),", property_key(&variant.tag))
+                    } else {
+                        format!(
+                            "{}: (fields: Omit<Extract<{name}, {{ {tag} }}>, {property:?}>): {name} =>\n  ({{ ...fields, {tag} }}),",
+                            property_key(&variant.tag)
+                        )
+                    }
+                })
+                .format("\n")
+                .indented("  ");
+            Some(format!("export const make{name} = {{\n{variants}\n}};\n"))
+        })
+        .collect_vec();
+    if constructors.is_empty() {
+        return None;
+    }
+
+    Some(constructors.join("\n"))
+}
+
+/// The `cacheKeys` object with a function per GET operation, serializing its
+/// parameters into a stable key. `None` unless enabled and the API has any
+/// GET operations.
+fn cache_keys(db: &dyn crate::Db, api: InputApi) -> Option<String> {
+    if !api.config(db).cache_keys {
+        return None;
+    }
+
+    let keys = lower_api(db, api)
+        .into_iter()
+        .filter(|op| op.method == "GET")
+        .map(|op| {
+            let args = op
+                .ts_arguments(db, api)
+                .into_iter()
+                .filter(|(arg, _)| matches!(*arg, "params" | "query" | "query?"))
+                .collect_vec();
+            format!(
+                "{}: ({}) =>\n  cacheKey({:?}, {:?}, {}, {}),",
+                op.name(db, api),
+                args.iter()
+                    .map(|(arg, ty)| format!("{arg}: {}", ty.ts(db)))
+                    .format(", "),
+                op.method,
+                op.path,
+                if op.path_params.is_empty() {
+                    "undefined"
+                } else {
+                    "params"
+                },
+                if op.query.is_empty() {
+                    "undefined"
+                } else {
+                    "query"
+                },
+            )
+        })
+        .collect_vec();
+    if keys.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "export const cacheKeys = {{\n{}\n}};",
+        keys.iter().format("\n").indented("  ")
+    ))
+}
+
+/// The `XQuery` type and `parseXQuery` function of every GET operation with
+/// query parameters. `None` unless enabled and the API has any such
+/// operations.
+fn query_parsers(db: &dyn crate::Db, api: InputApi) -> Option<String> {
+    if !api.config(db).query_parsers {
+        return None;
+    }
+
+    let config = api.config(db);
+    let parsers = lower_api(db, api)
+        .into_iter()
+        .filter(|op| op.method == "GET" && !op.query.is_empty())
+        .map(|op| {
+            let query_name = format!("{}Query", op.name(db, api).to_upper_camel_case());
+            let (_, query) = op
+                .ts_arguments(db, api)
+                .into_iter()
+                .find(|(arg, _)| matches!(*arg, "query" | "query?"))
+                .expect("operations with query parameters take a query");
+            let fields = op
+                .query
+                .iter()
+                .map(|(name, param)| {
+                    let parse = match param.ty.resolve(db, api).kind(db) {
+                        TypeKind::Array(item) => format!(
+                            "queryArray(searchParams, {name:?}, {})",
+                            query_coerce(db, api, item)
+                        ),
+                        _ if param.optional(&config) => format!(
+                            "queryParam(searchParams, {name:?}, {}, true)",
+                            query_coerce(db, api, param.ty)
+                        ),
+                        _ => format!(
+                            "queryParam(searchParams, {name:?}, {})",
+                            query_coerce(db, api, param.ty)
+                        ),
+                    };
+                    format!("{name:?}: {parse},")
+                })
+                .format("\n")
+                .indented("  ");
+            format!(
+                "export type {query_name} = {};\n\
+                 export const parse{query_name} = \
+                 (searchParams: URLSearchParams): {query_name} => ({{\n\
+                 {fields}\n}});\n",
+                query.ts(db)
+            )
+        })
+        .collect_vec();
+    if parsers.is_empty() {
+        return None;
+    }
+
+    Some(parsers.join("\n"))
+}
+
+/// The function converting the raw value of a query parameter into `ty`.
+/// Types without a textual representation are expected as JSON.
+fn query_coerce(db: &dyn crate::Db, api: InputApi, ty: Type) -> String {
+    let resolved = ty.resolve(db, api);
+    if let Some(values) = resolved.constants(db) {
+        return format!(
+            "queryCoerce.literal({})",
+            values.iter().map(|value| format!("{value:?}")).join(", ")
+        );
+    }
+    match resolved.kind(db) {
+        TypeKind::String => "queryCoerce.string".to_string(),
+        TypeKind::Number => "queryCoerce.number".to_string(),
+        TypeKind::Boolean => "queryCoerce.boolean".to_string(),
+        _ => format!("queryCoerce.json<{}>", ty.ts(db)),
+    }
+}
 
-    let operations = api
-        .api(db)
-        .paths
-        .paths
+/// A constant per operation describing its method and path.
+fn operation_constants(db: &dyn crate::Db, api: InputApi) -> String {
+    lower_api(db, api)
         .iter()
-        .flat_map(|(path, item)| match item {
-            oapi::ReferenceOr::Reference { reference: _ } => todo!(),
-            oapi::ReferenceOr::Item(path_item) => {
-                let span = tracing::debug_span!("endpoint", path);
-                let _enter = span.enter();
+        .map(|op| {
+            format!(
+                "export const {} = {{ method: {:?}, path: {:?} }} as const;\n",
+                op.name(db, api).to_shouty_snake_case(),
+                op.method,
+                op.path
+            )
+        })
+        .collect()
+}
 
-                if !path_item.parameters.is_empty() {
-                    todo!()
-                }
+/// The `fakes` creating a value of every component type, and
+/// `createMockApi` creating an object like `api` whose methods resolve with
+/// fake data unless overridden.
+fn mock_api(db: &dyn crate::Db, api: InputApi) -> String {
+    let types = component_types(db, api);
+    let names = types.iter().map(|(name, _)| name.as_str()).collect();
+    let branded = api.config(db).branded;
 
-                let gen_op = |method: &'static str, op: &Option<oapi::Operation>| {
-                    op.as_ref()
-                        .map(|op| (method, operation(db, api, path.clone(), op)))
-                };
-                [
-                    gen_op("DELETE", &path_item.delete),
-                    gen_op("GET", &path_item.get),
-                    gen_op("PUT", &path_item.put),
-                    gen_op("POST", &path_item.post),
-                    gen_op("HEAD", &path_item.head),
-                    gen_op("TRACE", &path_item.trace),
-                    gen_op("PATCH", &path_item.patch),
-                ]
-                .into_iter()
-                .flatten()
-                .map(|(method, op)| op.ts(db, api, method))
+    let fakes = types
+        .iter()
+        .map(|(name, ty)| {
+            let fake = ty.fake(db, &names);
+            // NOTE: The fake lacks the brand, so it is asserted to have it.
+            if branded {
+                format!("{name}: (): {name} => ({fake}) as {name},")
+            } else {
+                format!("{name}: (): {name} => ({fake}),")
             }
         })
         .collect_vec();
 
-    writeln!(
-        buf,
-        "export const api = {{\n{}\n}};",
-        operations
-            .iter()
-            .map(|(name, fn_impl)| format!("{name}: {fn_impl},"))
-            .format("\n")
-            .indented("  ")
+    let methods = lower_api(db, api)
+        .into_iter()
+        .map(|op| {
+            let name = op.name(db, api);
+            let result = match op.response {
+                Some(ResponseKind::Json(ty) | ResponseKind::Custom(ty)) => {
+                    format!("async () => {}", ty.fake(db, &names))
+                }
+                Some(ResponseKind::Csv(_)) => "async () => []".to_string(),
+                Some(ResponseKind::Plain | ResponseKind::Xml) => "async () => \"\"".to_string(),
+                Some(ResponseKind::Blob) => "async () => new Blob()".to_string(),
+                Some(ResponseKind::Redirect) => "async () => ({ redirectedTo: \"\" })".to_string(),
+                Some(ResponseKind::Empty) => match api.config(db).empty_response {
+                    EmptyResponse::Record => "async () => ({})".to_string(),
+                    EmptyResponse::Void => "async () => {}".to_string(),
+                },
+                Some(ResponseKind::EventStream(_)) => {
+                    "() => ({ cancel: () => {}, listen: () => {} })".to_string()
+                }
+                None => unreachable!(),
+            };
+            (op, format!("{name}: {result},"))
+        })
+        .collect_vec();
+    let methods = grouped(db, api, methods);
+
+    format!(
+        "export const fakes = {{\n{}\n}};\n\n\
+         export const createMockApi = (\n  overrides: Partial<typeof api> = {{}}\n): typeof api => ({{\n{}\n  ...overrides,\n}});\n",
+        fakes.iter().format("\n").indented("  "),
+        methods.iter().format("\n").indented("  ")
     )
-    .unwrap();
+}
 
-    tracing::info!("wrote {} operation", operations.len());
+/// The `XResult` type of every operation resolving with a status result, per
+/// [`Operation::status_result`]. `None` if there are none.
+fn status_results(db: &dyn crate::Db, api: InputApi) -> Option<String> {
+    let types = lower_api(db, api)
+        .iter()
+        .filter_map(|op| {
+            Some(format!(
+                "export type {}Result = {};\n",
+                op.name(db, api).to_upper_camel_case(),
+                op.status_result(db, api)?
+            ))
+        })
+        .collect_vec();
+    if types.is_empty() {
+        None
+    } else {
+        Some(types.concat())
+    }
+}
 
-    writeln!(buf).unwrap();
+/// The `XError` type of every operation documenting error bodies, per
+/// [`Config::typed_errors`]. `None` if disabled or there are none.
+fn error_types(db: &dyn crate::Db, api: InputApi) -> Option<String> {
+    if !api.config(db).typed_errors {
+        return None;
+    }
+    let types = lower_api(db, api)
+        .iter()
+        .filter_map(|op| {
+            Some(format!(
+                "export type {}Error = {};\n",
+                op.name(db, api).to_upper_camel_case(),
+                op.error_body(db)?
+            ))
+        })
+        .collect_vec();
+    if types.is_empty() {
+        None
+    } else {
+        Some(types.concat())
+    }
+}
 
-    let types = api
-        .api(db)
-        .components
-        .as_ref()
-        .unwrap()
-        .schemas
-        .keys()
-        .filter_map(|name| {
-            if name.contains('_') {
-                tracing::info!(?name, "skipping due to '_'");
-                return None;
-            }
+/// The `Paths` type mapping the name of every operation to its path template,
+/// and `PathOf` for looking one up.
+fn path_types(db: &dyn crate::Db, api: InputApi) -> String {
+    format!(
+        "export type Paths = {{\n{}\n}};\nexport type PathOf<N extends keyof Paths> = Paths[N];\n",
+        lower_api(db, api)
+            .iter()
+            .map(|op| format!("{}: {:?};", op.name(db, api), op.path))
+            .format("\n")
+            .indented("  ")
+    )
+}
 
-            let schema = schema_by_name(db, api, name.to_string())?;
+/// Whether the values of the enumeration named `name` are exported as an
+/// array, per [`Config::constant_schemas`].
+fn exports_constants(config: &Config, name: &str) -> bool {
+    match &config.constant_schemas {
+        Some(globs) => globs
+            .iter()
+            .any(|glob| glob.compile_matcher().is_match(name)),
+        None => true,
+    }
+}
 
-            let ty = simplify_ty(db, schema_ty(db, api, schema));
+/// The exported type alias for a component type, followed by a constant
+/// listing its values if it is an enumeration and
+/// [`Config::constant_schemas`] allows it.
+fn type_declaration(db: &dyn crate::Db, api: InputApi, name: &str, ty: Type) -> String {
+    use std::fmt::Write;
 
-            Some((name, ty))
-        })
-        .collect_vec();
+    let mut buf = String::new();
 
-    for (name, ty) in &types {
-        let ts = ty.ts(db);
+    let branded = api.config(db).branded;
+    let constants = ty.constants(db);
+    let descriptions = constant_descriptions(db, api, name);
+    // NOTE: Each documented value is preceded by its description.
+    let documented = |values: &[String], prefix: &str, suffix: &str| {
+        values
+            .iter()
+            .map(|value| match descriptions.get(value) {
+                Some(description) => {
+                    format!("{}\n{prefix}{value:?}{suffix}", doc_comment(description))
+                }
+                None => format!("{prefix}{value:?}{suffix}"),
+            })
+            .format("\n")
+            .indented("  ")
+            .to_string()
+    };
+    let ts = match &constants {
+        Some(values) if !descriptions.is_empty() => format!("\n{}", documented(values, "| ", "")),
+        _ => ty.ts(db),
+    };
+    if api.config(db).type_hashes {
+        writeln!(buf, "// abeye-hash: {:016x}", ty.content_hash(db)).unwrap();
+    }
+    if let Some(description) = schema_description(db, api, name) {
+        writeln!(buf, "{}", doc_comment(&description)).unwrap();
+    }
+    if branded {
+        writeln!(
+            buf,
+            "export type {name} = ({ts}) & {{ readonly __schema: {name:?} }};"
+        )
+        .unwrap();
+    } else {
         writeln!(buf, "export type {name} = {ts};").unwrap();
-        if let Some(constants) = ty.constants(db) {
-            let const_name =
-                pluralizer::pluralize(name, constants.len() as _, false).to_shouty_snake_case();
+    }
+    if let Some(constants) = constants {
+        let const_name =
+            pluralizer::pluralize(name, constants.len() as _, false).to_shouty_snake_case();
+        // NOTE: The literals lack the brand, so they are asserted to have it.
+        let check = if branded { "as" } else { "satisfies" };
+        let values = if descriptions.is_empty() {
+            format!("[{:?}]", constants.iter().format(", "))
+        } else {
+            format!("[\n{}\n]", documented(&constants, "", ","))
+        };
+        if exports_constants(&api.config(db), name) {
             writeln!(
                 buf,
-                "export const {const_name} = [{:?}] satisfies {name}[];",
-                constants.iter().format(", ")
+                "export const {const_name} = {values} {check} {name}[];"
             )
             .unwrap();
         }
-    }
 
-    tracing::info!("wrote {} types", types.len());
+        // NOTE: Values without a label of their own are labeled by the value.
+        let labels = constant_labels(db, api, name);
+        if !labels.is_empty() {
+            let labels_name = format!("{}Labels", name.to_lower_camel_case());
+            let entries = constants.iter().map(|value| {
+                let label = labels.get(value).unwrap_or(value);
+                format!("{value:?}: {label:?},")
+            });
+            let entries = entries.format("\n").indented("  ");
+            if branded {
+                writeln!(
+                    buf,
+                    "export const {labels_name} = {{\n{entries}\n}} as Record<{name}, string>;"
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    buf,
+                    "export const {labels_name}: Record<{name}, string> = {{\n{entries}\n}};"
+                )
+                .unwrap();
+            }
+        }
+    }
 
     buf
 }
 
+/// The exported type guard for a component type, checking whether a value
+/// matches it.
+fn type_guard(db: &dyn crate::Db, api: InputApi, name: &str, ty: Type) -> String {
+    match api.config(db).ts_validators {
+        TsValidators::Guards => format!(
+            "export const is{name} = (value: unknown): value is {name} =>\n  {}(value);\n",
+            ty.guard(db)
+        ),
+        // NOTE: Schemas are lazy, as they may reference schemas declared
+        // after them, or themselves.
+        TsValidators::Zod => format!(
+            "export const {name}Schema: z.ZodTypeAny = z.lazy(() => {});\n\
+             export const is{name} = (value: unknown): value is {name} =>\n  \
+             {name}Schema.safeParse(value).success;\n",
+            ty.zod(db)
+        ),
+    }
+}
+
+/// A doc comment identifying the API the file was generated from.
+fn banner(db: &dyn crate::Db, api: InputApi) -> String {
+    let info = &api.api(db).info;
+
+    let mut lines = vec![format!("{} ({})", info.title, info.version)];
+    let details = api.info_details(db);
+    if !details.is_empty() {
+        lines.push(String::new());
+        lines.extend(details);
+    }
+
+    jsdoc(&lines)
+}
+
+/// The key of a property in an object type, quoted unless it is a valid
+/// identifier, such as the name of a header.
+fn property_key(name: &str) -> String {
+    let mut chars = name.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        name.to_string()
+    } else {
+        format!("{name:?}")
+    }
+}
+
+/// Renders documentation from the document, such as a description, as a
+/// `/** ... */` block.
+fn doc_comment(text: &str) -> String {
+    jsdoc(&text.trim().lines().map(str::to_string).collect_vec())
+}
+
+/// Renders the given lines as a `/** ... */` block.
+fn jsdoc(lines: &[String]) -> String {
+    let body = lines
+        .iter()
+        .map(|line| {
+            let line = line.replace("*/", "*\\/");
+            if line.is_empty() {
+                " *".to_string()
+            } else {
+                format!(" * {line}")
+            }
+        })
+        .format("\n");
+    format!("/**\n{body}\n */")
+}
+
 impl Type {
+    /// A function checking whether a value matches the type, built from the
+    /// `guards` combinators of the runtime and the guards of component types.
+    fn guard(self, db: &dyn crate::Db) -> String {
+        let all = |types: &[Type]| types.iter().map(|ty| ty.guard(db)).join(", ");
+
+        match self.kind(db) {
+            // NOTE: Referenced guards are called lazily, as they may be
+            // declared after this one.
+            TypeKind::Reference(name) => format!("((value: unknown) => is{name}(value))"),
+            TypeKind::Object(obj) => {
+                let fields = obj
+                    .iter()
+                    .map(|(name, prop)| format!("{name:?}: {}", prop.ty.guard(db)))
+                    .join(", ");
+                let optional = obj
+                    .iter()
+                    .filter(|(_, prop)| prop.optional)
+                    .map(|(name, _)| format!("{name:?}"))
+                    .join(", ");
+                if optional.is_empty() {
+                    format!("guards.object({{ {fields} }})")
+                } else {
+                    format!("guards.object({{ {fields} }}, [{optional}])")
+                }
+            }
+            // NOTE: Keys are strings in JSON, so only the values are checked.
+            TypeKind::Map(_, values) => format!("guards.record({})", values.guard(db)),
+            TypeKind::Array(items) => format!("guards.array({})", items.guard(db)),
+            TypeKind::Tuple(elements) => format!("guards.tuple([{}])", all(&elements)),
+            TypeKind::Or(options) => format!("guards.or([{}])", all(&options)),
+            TypeKind::And(options) => format!("guards.and([{}])", all(&options)),
+            TypeKind::Number => "guards.number".to_string(),
+            TypeKind::String => "guards.string".to_string(),
+            TypeKind::Boolean => "guards.boolean".to_string(),
+            TypeKind::Null => "guards.null".to_string(),
+            TypeKind::Ident(ident) => format!("guards.literal({ident:?})"),
+            // NOTE: Custom types are opaque, so any value is accepted. So are
+            // the values of generic types, which have no guards of their own.
+            TypeKind::Custom(_)
+            | TypeKind::Generic(..)
+            | TypeKind::Parameter(_)
+            | TypeKind::Unknown => "((_: unknown) => true)".to_string(),
+        }
+    }
+
+    /// A Zod schema of the type, referencing the schemas of component types.
+    fn zod(self, db: &dyn crate::Db) -> String {
+        let all = |types: &[Type]| types.iter().map(|ty| ty.zod(db)).join(", ");
+
+        match self.kind(db) {
+            TypeKind::Reference(name) => format!("{name}Schema"),
+            TypeKind::Object(obj) => {
+                let fields = obj
+                    .iter()
+                    .map(|(name, prop)| {
+                        if prop.optional {
+                            format!("{name:?}: {}.optional()", prop.ty.zod(db))
+                        } else {
+                            format!("{name:?}: {}", prop.ty.zod(db))
+                        }
+                    })
+                    .join(", ");
+                format!("z.object({{ {fields} }})")
+            }
+            TypeKind::Map(_, values) => format!("z.record(z.string(), {})", values.zod(db)),
+            TypeKind::Array(items) => format!("z.array({})", items.zod(db)),
+            TypeKind::Tuple(elements) => format!("z.tuple([{}])", all(&elements)),
+            TypeKind::Or(options) => match options.as_slice() {
+                [] => "z.never()".to_string(),
+                [only] => only.zod(db),
+                _ => format!("z.union([{}])", all(&options)),
+            },
+            TypeKind::And(options) => options
+                .iter()
+                .map(|ty| ty.zod(db))
+                .reduce(|a, b| format!("z.intersection({a}, {b})"))
+                .unwrap_or_else(|| "z.unknown()".to_string()),
+            TypeKind::Number => "z.number()".to_string(),
+            TypeKind::String => "z.string()".to_string(),
+            TypeKind::Boolean => "z.boolean()".to_string(),
+            TypeKind::Null => "z.null()".to_string(),
+            TypeKind::Ident(ident) => format!("z.literal({ident:?})"),
+            // NOTE: Custom types are opaque, so any value is accepted. So are
+            // the values of generic types, which have no schemas of their own.
+            TypeKind::Custom(_)
+            | TypeKind::Generic(..)
+            | TypeKind::Parameter(_)
+            | TypeKind::Unknown => "z.unknown()".to_string(),
+        }
+    }
+
+    /// A TypeScript expression creating a fake value of the type, using the
+    /// functions of `fakes` for the component types in `names`.
+    ///
+    /// Optional properties are left out and arrays are empty, which keeps
+    /// recursive types finite.
+    fn fake(self, db: &dyn crate::Db, names: &BTreeSet<&str>) -> String {
+        match self.kind(db) {
+            TypeKind::Reference(name) if names.contains(name.as_str()) => {
+                format!("fakes.{name}()")
+            }
+            TypeKind::Object(obj) => {
+                let fields = obj
+                    .iter()
+                    .filter(|(_, prop)| !prop.optional)
+                    .map(|(name, prop)| format!("{name:?}: {}", prop.ty.fake(db, names)))
+                    .collect_vec();
+                if fields.is_empty() {
+                    "{}".to_string()
+                } else {
+                    format!("{{ {} }}", fields.iter().format(", "))
+                }
+            }
+            TypeKind::Map(..) => "{}".to_string(),
+            TypeKind::Array(_) => "[]".to_string(),
+            TypeKind::Tuple(elements) => format!(
+                "[{}]",
+                elements.iter().map(|ty| ty.fake(db, names)).format(", ")
+            ),
+            TypeKind::Or(options) => match options.first() {
+                Some(first) => first.fake(db, names),
+                None => "undefined as never".to_string(),
+            },
+            TypeKind::And(options) => format!(
+                "{{ {} }} as {}",
+                options
+                    .iter()
+                    .map(|ty| format!("...{}", ty.fake(db, names)))
+                    .format(", "),
+                self.ts(db)
+            ),
+            TypeKind::Number => "0".to_string(),
+            TypeKind::String => "\"\"".to_string(),
+            TypeKind::Boolean => "false".to_string(),
+            TypeKind::Null | TypeKind::Unknown => "null".to_string(),
+            TypeKind::Ident(ident) => format!("{ident:?}"),
+            // NOTE: Custom types and those not among the component types can't
+            // be faked, so tests must override the methods returning them.
+            TypeKind::Reference(_)
+            | TypeKind::Custom(_)
+            | TypeKind::Generic(..)
+            | TypeKind::Parameter(_) => "undefined as never".to_string(),
+        }
+    }
+
+    /// The TypeScript type expression for the type, as it appears in the
+    /// generated output.
+    pub fn to_typescript(self, db: &dyn crate::Db) -> String {
+        self.ts(db)
+    }
+
     pub fn ts(self, db: &dyn crate::Db) -> String {
         match self.kind(db) {
             TypeKind::Reference(name) => name.to_string(),
@@ -122,119 +1853,610 @@ impl Type {
                 let fields = obj
                     .iter()
                     .map(|(name, prop)| {
-                        format!(
-                            "{name}{}: {};",
+                        let field = format!(
+                            "{}{}: {};",
+                            property_key(name),
                             if prop.optional { "?" } else { "" },
                             prop.ty.ts(db)
-                        )
+                        );
+                        match prop.doc {
+                            Some(doc) => format!("{}\n{field}", doc_comment(&doc.text(db))),
+                            None => field,
+                        }
                     })
                     .format("\n")
                     .indented("  ");
                 format!("{{\n{fields}\n}}")
             }
-            TypeKind::Array(array_ty) => format!("{}[]", array_ty.ts(db)),
+            // NOTE: Maps keyed by an enumeration needn't have every key.
+            TypeKind::Map(keys, values) => match keys.kind(db) {
+                TypeKind::String | TypeKind::Number => {
+                    format!("Record<{}, {}>", keys.ts(db), values.ts(db))
+                }
+                _ => format!("Partial<Record<{}, {}>>", keys.ts(db), values.ts(db)),
+            },
+            // NOTE: `[]` binds tighter than `|` and `&`, so `(A | B)[]` needs
+            // its parentheses.
+            TypeKind::Array(array_ty) => match array_ty.kind(db) {
+                TypeKind::Or(options) | TypeKind::And(options) if options.len() > 1 => {
+                    format!("({})[]", array_ty.ts(db))
+                }
+                _ => format!("{}[]", array_ty.ts(db)),
+            },
             TypeKind::Tuple(elements) => {
                 format!("[{}]", elements.iter().map(|ty| ty.ts(db)).join(", "))
             }
+            TypeKind::Or(options) if options.is_empty() => "never".to_string(),
             TypeKind::Or(options) => options.iter().map(|opt| opt.ts(db)).join(" | "),
             TypeKind::And(options) => options.iter().map(|opt| opt.ts(db)).join(" & "),
             TypeKind::Number => "number".to_string(),
             TypeKind::String => "string".to_string(),
             TypeKind::Boolean => "boolean".to_string(),
+            TypeKind::Null => "null".to_string(),
             TypeKind::Ident(ident) => format!("{ident:?}"),
+            TypeKind::Custom(ts) => ts,
+            TypeKind::Generic(name, arguments) => {
+                format!(
+                    "{name}<{}>",
+                    arguments.iter().map(|ty| ty.ts(db)).join(", ")
+                )
+            }
+            TypeKind::Parameter(name) => name,
+            TypeKind::Unknown => "unknown".to_string(),
         }
     }
 }
 
 impl Operation {
-    #[tracing::instrument(skip_all)]
-    fn ts(&self, db: &dyn crate::Db, api: InputApi, method: &str) -> (String, String) {
-        let path = Utf8PathBuf::from(&self.path);
-        let name = if let Some(prefix) = api.config(db).api_prefix {
-            path.strip_prefix(prefix).unwrap()
+    /// Whether the operation can be replayed by the offline queue, which
+    /// covers every POST, PUT, PATCH, and DELETE not streaming events. Files
+    /// can't be persisted, so multipart bodies are left out too.
+    fn is_mutation(&self) -> bool {
+        matches!(self.method, "POST" | "PUT" | "PATCH" | "DELETE")
+            && !matches!(self.response, Some(ResponseKind::EventStream(_)))
+            && !matches!(self.body, Some(RequestKind::Multipart(_)))
+    }
+
+    /// Whether a `pollX` helper is generated for the operation, which is the
+    /// case for GET operations responding with JSON, unless they resolve with
+    /// a status result.
+    fn is_pollable(&self, db: &dyn crate::Db, api: InputApi) -> bool {
+        self.method == "GET"
+            && !self.uses_codec()
+            && matches!(self.response, Some(ResponseKind::Json(_)))
+            && self.status_result(db, api).is_none()
+    }
+
+    /// The union of the JSON bodies of the documented error responses, that is
+    /// those with a 4XX or 5XX status and the `default` response. `None` if
+    /// none have a JSON body.
+    fn error_body(&self, db: &dyn crate::Db) -> Option<String> {
+        let bodies = self
+            .responses
+            .iter()
+            .filter(|(status, _)| {
+                status.starts_with('4') || status.starts_with('5') || status == "default"
+            })
+            .filter_map(|(_, res)| match res {
+                ResponseKind::Json(ty) => Some(ty.ts(db)),
+                _ => None,
+            })
+            .unique()
+            .collect_vec();
+        if bodies.is_empty() {
+            None
+        } else {
+            Some(bodies.join(" | "))
+        }
+    }
+
+    /// The union of the documented responses discriminated by their status,
+    /// such as `{ status: 200; data: User } | { status: 404; data: NotFound }`,
+    /// which the method resolves with under [`Config::status_results`].
+    /// Ranges such as `4XX` and the `default` response have any status.
+    ///
+    /// Operations documenting successful responses with different bodies,
+    /// such as 200 and 201, always resolve with the union of those.
+    ///
+    /// `None` otherwise, and for operations whose successful response isn't
+    /// JSON or empty.
+    fn status_result(&self, db: &dyn crate::Db, api: InputApi) -> Option<String> {
+        if self.uses_codec()
+            || !matches!(
+                self.response,
+                Some(ResponseKind::Json(_) | ResponseKind::Empty)
+            )
+        {
+            return None;
+        }
+
+        let responses = if api.config(db).status_results {
+            self.responses.iter().collect_vec()
         } else {
-            &path
+            let successes = self
+                .responses
+                .iter()
+                .filter(|(status, _)| status.starts_with('2'))
+                .collect_vec();
+            let distinct = successes.iter().map(|(_, res)| res).unique().count();
+            let resolvable = successes
+                .iter()
+                .all(|(_, res)| matches!(res, ResponseKind::Json(_) | ResponseKind::Empty));
+            if distinct < 2 || !resolvable {
+                return None;
+            }
+            successes
+        };
+
+        let members = responses.iter().map(|(status, res)| {
+            let status = match status.parse::<u16>() {
+                Ok(code) => code.to_string(),
+                Err(_) => "number".to_string(),
+            };
+            let data = match res {
+                ResponseKind::Json(ty) => ty.ts(db),
+                ResponseKind::Empty => "undefined".to_string(),
+                ResponseKind::Plain | ResponseKind::Xml => "string".to_string(),
+                _ => "unknown".to_string(),
+            };
+            format!("{{ status: {status}; data: {data} }}")
+        });
+        Some(members.format(" | ").to_string())
+    }
+
+    /// Whether the body or the response is handled by a configured
+    /// [`MediaTypeCodec`](crate::MediaTypeCodec), or the body is multipart or
+    /// form encoded, in which case the request is made with `requestCustom`.
+    fn uses_codec(&self) -> bool {
+        matches!(
+            self.body,
+            Some(RequestKind::Custom(_) | RequestKind::Multipart(_) | RequestKind::Form(_))
+        ) || matches!(self.response, Some(ResponseKind::Custom(_)))
+    }
+
+    /// The `Encoder` of the request body passed to `requestCustom`.
+    fn encoder(&self, db: &dyn crate::Db, api: InputApi) -> String {
+        match (self.body, &self.request_media_type) {
+            (Some(RequestKind::Custom(_)), Some(media_type)) => codec(&api.config(db), media_type),
+            (Some(RequestKind::Multipart(_)), _) => {
+                r#"{ mediaType: "multipart/form-data", encode: formData }"#.to_string()
+            }
+            (Some(RequestKind::Form(_)), _) => {
+                r#"{ mediaType: "application/x-www-form-urlencoded", encode: formUrlEncoded }"#
+                    .to_string()
+            }
+            _ => r#"{ mediaType: "application/json", encode: stringifyJson }"#.to_string(),
+        }
+    }
+
+    /// The type of the response and its `Decoder` passed to `requestCustom`.
+    fn decoder(&self, db: &dyn crate::Db, api: InputApi) -> (String, String) {
+        match (self.response, &self.response_media_type) {
+            (Some(ResponseKind::Custom(ty)), Some(media_type)) => {
+                (ty.ts(db), codec(&api.config(db), media_type))
+            }
+            (Some(ResponseKind::Json(ty)), _) => (
+                ty.ts(db),
+                r#"{ mediaType: "application/json", decode: (res: Response) => res.text().then(parseJson) }"#
+                    .to_string(),
+            ),
+            (Some(ResponseKind::Plain), _) => (
+                "string".to_string(),
+                r#"{ mediaType: "text/plain", decode: (res: Response) => res.text() }"#.to_string(),
+            ),
+            (Some(ResponseKind::Blob), _) => (
+                "Blob".to_string(),
+                r#"{ mediaType: "*/*", decode: (res: Response) => res.blob() }"#.to_string(),
+            ),
+            (Some(ResponseKind::Xml), _) => (
+                "string".to_string(),
+                r#"{ mediaType: "application/xml", decode: (res: Response) => res.text() }"#
+                    .to_string(),
+            ),
+            (Some(ResponseKind::Empty), _) => match api.config(db).empty_response {
+                EmptyResponse::Record => (
+                    "Record<string, never>".to_string(),
+                    r#"{ mediaType: "*/*", decode: async () => ({}) }"#.to_string(),
+                ),
+                EmptyResponse::Void => (
+                    "void".to_string(),
+                    r#"{ mediaType: "*/*", decode: async () => {} }"#.to_string(),
+                ),
+            },
+            // NOTE: Operations combining these are left out by `lower_api`
+            _ => unreachable!(),
         }
-        .components()
-        .join("_")
-        .to_lower_camel_case();
+    }
+
+    /// Whether identical concurrent calls share a single request. This is
+    /// configured globally and per operation with the `x-abeye-dedupe`
+    /// extension, and only applies to GET requests not streaming events.
+    fn dedupes(&self, db: &dyn crate::Db, api: InputApi) -> bool {
+        self.method == "GET"
+            && !matches!(self.response, Some(ResponseKind::EventStream(_)))
+            && self
+                .extensions
+                .get("x-abeye-dedupe")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(api.config(db).dedupe)
+    }
+
+    /// The header the CSRF token is sent in, if enabled and the method of the
+    /// operation is one which may modify state.
+    fn csrf_header(&self, db: &dyn crate::Db, api: InputApi) -> Option<String> {
+        api.config(db)
+            .csrf_header
+            .filter(|_| matches!(self.method, "POST" | "PUT" | "PATCH" | "DELETE"))
+    }
+
+    /// Whether requests carry the trace context. This is configured globally
+    /// and per operation with the `x-abeye-trace` extension.
+    fn traces(&self, db: &dyn crate::Db, api: InputApi) -> bool {
+        self.extensions
+            .get("x-abeye-trace")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(api.config(db).trace_context)
+    }
 
-        fn typify_map(db: &dyn crate::Db, map: &BTreeMap<String, Type>) -> Option<Type> {
+    /// The arguments of the generated method besides `options`, as the name
+    /// of the argument (suffixed with `?` if it is optional) and its type.
+    fn ts_arguments(&self, db: &dyn crate::Db, api: InputApi) -> Vec<(&'static str, Type)> {
+        fn typify_map(db: &dyn crate::Db, map: BTreeMap<String, Property>) -> Option<Type> {
             if map.is_empty() {
                 None
             } else {
-                Some(Type::new(
-                    db,
-                    TypeKind::Object(
-                        map.iter()
-                            .map(|(name, &ty)| {
-                                (
-                                    name.clone(),
-                                    Property {
-                                        ty,
-                                        optional: false,
-                                    },
-                                )
-                            })
-                            .collect(),
-                    ),
-                ))
+                Some(Type::new(db, TypeKind::Object(map)))
             }
         }
 
-        let params = typify_map(db, &self.path_params);
-        let query = typify_map(db, &self.query);
-        let json_body = self.body.map(|body| match body {
-            RequestKind::Json(body) => body,
-        });
+        let config = api.config(db);
+
+        let params = typify_map(
+            db,
+            self.path_params
+                .iter()
+                .map(|(name, &ty)| (name.clone(), Property::required(ty)))
+                .collect(),
+        );
+        let query = typify_map(
+            db,
+            self.query
+                .iter()
+                .map(|(name, param)| {
+                    (
+                        name.clone(),
+                        Property {
+                            ty: param.ty,
+                            optional: param.optional(&config),
+                            doc: None,
+                        },
+                    )
+                })
+                .collect(),
+        );
+        let json_body = self.body.map(RequestKind::ty);
 
-        let props = [
+        let if_match = self.if_match.then(|| Type::new(db, TypeKind::String));
+        // NOTE: A required argument can't follow an optional one, so the query
+        // is only optional if neither a body nor an etag comes after it.
+        let query_name = if !self.query.is_empty()
+            && self.query.values().all(|param| param.optional(&config))
+            && json_body.is_none()
+            && if_match.is_none()
+        {
+            "query?"
+        } else {
+            "query"
+        };
+        let headers = typify_map(
+            db,
+            self.headers
+                .iter()
+                .map(|(name, &ty)| {
+                    (
+                        name.clone(),
+                        Property {
+                            ty,
+                            optional: true,
+                            doc: None,
+                        },
+                    )
+                })
+                .collect(),
+        );
+
+        [
             ("params", params),
-            ("query", query),
+            (query_name, query),
             ("body", json_body),
-            (
-                "options?",
-                Some(Type::new(db, TypeKind::Reference("ApiOptions".to_string()))),
-            ),
+            ("ifMatch", if_match),
+            ("headers?", headers),
         ]
         .into_iter()
         .filter_map(|(name, ty)| Some((name, ty?)))
-        .collect_vec();
+        .collect()
+    }
+
+    /// The expression of the URL requested, with the placeholders of the path
+    /// template substituted by the encoded `params` and the `query` argument
+    /// as the query string.
+    fn url(&self, db: &dyn crate::Db, api: InputApi) -> String {
+        let mut path = self.path.clone();
+        for name in self.path_params.keys() {
+            let value = if property_key(name) == *name {
+                format!("params.{name}")
+            } else {
+                format!("params[{name:?}]")
+            };
+            path = path.replace(
+                &format!("{{{name}}}"),
+                &format!("${{encodeURIComponent(String({value}))}}"),
+            );
+        }
+
+        let query_defaults = if api.config(db).fill_query_defaults {
+            self.query
+                .iter()
+                .filter_map(|(name, param)| Some(format!("{name:?}: {}", param.default.as_ref()?)))
+                .collect_vec()
+        } else {
+            Vec::new()
+        };
+
+        let query_styles = self
+            .query
+            .iter()
+            .filter(|(_, param)| !(param.style == QueryStyle::Form && param.explode))
+            .map(|(name, param)| {
+                format!(
+                    "{name:?}: {{ style: {:?}, explode: {} }}",
+                    param.style.as_str(),
+                    param.explode
+                )
+            })
+            .collect_vec();
 
-        let url = if params.is_some() {
-            format!("`{path}?${{new URLSearchParams(params)}}`")
-        } else if query.is_some() {
-            format!("`{path}?${{new URLSearchParams(query)}}`")
+        if !self.query.is_empty() && !query_styles.is_empty() {
+            let defaults = if query_defaults.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", query_defaults.iter().format(", "))
+            };
+            format!(
+                "`{path}?${{queryString(query, {defaults}, {{ {} }})}}`",
+                query_styles.iter().format(", ")
+            )
+        } else if !self.query.is_empty() && !query_defaults.is_empty() {
+            format!(
+                "`{path}?${{queryString(query, {{ {} }})}}`",
+                query_defaults.iter().format(", ")
+            )
+        } else if !self.query.is_empty() {
+            format!("`{path}?${{queryString(query)}}`")
         } else {
             format!("`{path}`")
+        }
+    }
+
+    /// The object of `api` the method is defined on with [`GroupBy::Tag`],
+    /// named after the first tag of the operation, or `default` if it has
+    /// none. `None` if methods aren't grouped.
+    fn group(&self, db: &dyn crate::Db, api: InputApi) -> Option<String> {
+        match api.config(db).group_by {
+            GroupBy::None => None,
+            GroupBy::Tag => Some(match self.tags.first() {
+                Some(tag) => tag.to_lower_camel_case(),
+                None => "default".to_string(),
+            }),
+        }
+    }
+
+    /// The method as accessed on `api`, such as `search.autosuggest` when
+    /// grouped by tag.
+    fn api_member(&self, db: &dyn crate::Db, api: InputApi) -> String {
+        match self.group(db, api) {
+            Some(group) => format!("{group}.{}", self.name(db, api)),
+            None => self.name(db, api),
+        }
+    }
+
+    /// A doc comment with the summary and description of the operation,
+    /// listing the servers overriding those of the document and marking the
+    /// method as deprecated, if there is anything to document.
+    fn jsdoc(&self, db: &dyn crate::Db, api: InputApi) -> Option<String> {
+        let mut paragraphs = Vec::new();
+        for text in [&self.summary, &self.description].into_iter().flatten() {
+            paragraphs.push(text.trim().lines().map(str::to_string).collect_vec());
+        }
+        if !self.servers.is_empty() {
+            paragraphs.push(
+                ["Served from:".to_string()]
+                    .into_iter()
+                    .chain(self.servers.iter().map(|url| format!("- `{url}`")))
+                    .collect(),
+            );
+        }
+        if self.deprecated {
+            paragraphs.push(vec!["@deprecated".to_string()]);
+        }
+        if api.config(db).typed_errors && self.error_body(db).is_some() {
+            paragraphs.push(vec![format!(
+                "@throws {{ApiError<{}Error>}}",
+                self.name(db, api).to_upper_camel_case()
+            )]);
+        }
+        if paragraphs.is_empty() {
+            return None;
+        }
+        Some(jsdoc(&paragraphs.join(&String::new())))
+    }
+
+    #[tracing::instrument(skip_all)]
+    fn ts(&self, db: &dyn crate::Db, api: InputApi) -> (String, String) {
+        let name = self.name(db, api);
+        let path = &self.path;
+        let method = self.method;
+
+        let props = self
+            .ts_arguments(db, api)
+            .into_iter()
+            .chain([(
+                "options?",
+                Type::new(db, TypeKind::Reference("ApiOptions".to_string())),
+            )])
+            .collect_vec();
+
+        let url = self.url(db, api);
+
+        let validator = match self.response {
+            _ if !api.config(db).validate => None,
+            Some(ResponseKind::Json(ty)) => Some(ty),
+            Some(ResponseKind::EventStream(ty)) => match ty.kind(db) {
+                TypeKind::Array(inner) => Some(inner),
+                _ => Some(ty),
+            },
+            _ => None,
+        }
+        .map(|ty| match api.config(db).ts_validators {
+            TsValidators::Guards => format!(
+                "(value: unknown): value is {} => {}(value)",
+                ty.ts(db),
+                ty.guard(db)
+            ),
+            TsValidators::Zod => format!(
+                "(value: unknown): value is {} => {}.safeParse(value).success",
+                ty.ts(db),
+                ty.zod(db)
+            ),
+        });
+
+        // NOTE: Every request function but the `EventSource` based `sse` takes
+        // the body before the options, so `undefined` holds its place when
+        // there is none. Otherwise the options would be sent as the body.
+        let body_arg = match (self.body, &self.response) {
+            (Some(_), _) => Some("body".to_string()),
+            (None, Some(ResponseKind::EventStream(_)))
+                if sse_transport(&api.config(db)) != SseTransport::Fetch =>
+            {
+                None
+            }
+            (None, _) => Some("undefined".to_string()),
+        };
+
+        let options_arg = if self.headers.is_empty() {
+            "options".to_string()
+        } else {
+            "withHeaders(headers, options)".to_string()
+        };
+        let options_arg = if self.traces(db, api) {
+            format!("withTrace({options_arg})")
+        } else {
+            options_arg
+        };
+        let options_arg = match self.csrf_header(db, api) {
+            Some(header) => format!("withCsrf({header:?}, {options_arg})"),
+            None => options_arg,
+        };
+        let options_arg = if api.config(db).telemetry {
+            format!("withMetric({name:?}, {method:?}, {path:?}, {options_arg})")
+        } else {
+            options_arg
+        };
+        // NOTE: The metric wraps the inner fetch, so it still sees the status
+        // of a failed precondition and of every rate limited attempt.
+        let options_arg = if self.rate_limited {
+            format!("withRateLimit({options_arg})")
+        } else {
+            options_arg
+        };
+        let options_arg = if self.if_match {
+            format!("withIfMatch(ifMatch, {options_arg})")
+        } else {
+            options_arg
+        };
+        // NOTE: Failed preconditions are rejected by `withIfMatch` before
+        // reaching this, and status results resolve with errors instead.
+        let resolves_errors =
+            api.config(db).status_results && self.status_result(db, api).is_some();
+        let options_arg = if api.config(db).typed_errors && !resolves_errors {
+            format!("withErrors({options_arg})")
+        } else {
+            options_arg
         };
 
         let args = [
             Some(format!("{method:?}")),
-            Some(url),
-            json_body.map(|_| "body".to_string()),
-            Some("options".to_string()),
+            Some(url.clone()),
+            body_arg,
+            Some(options_arg),
+            validator,
         ]
         .into_iter()
         .flatten()
         .format(", ");
 
-        let request_impl = match &self.response {
-            Some(res) => match res {
-                ResponseKind::Plain => {
-                    format!("requestPlain({args})",)
-                }
-                ResponseKind::Json(ty) => {
-                    format!("requestJson<{}>({args})", ty.ts(db))
-                }
-                ResponseKind::EventStream(ty) => match ty.kind(db) {
-                    TypeKind::Array(inner) => {
-                        format!("sse<{}>({args})", inner.ts(db))
+        let request_impl = if self.status_result(db, api).is_some() {
+            let body_arg = if self.body.is_some() {
+                "body"
+            } else {
+                "undefined"
+            };
+            // NOTE: Unless every status is resolved, failures are rejected.
+            let reject = if api.config(db).status_results {
+                ""
+            } else {
+                ", true"
+            };
+            format!(
+                "requestResult<{}Result>({method:?}, {url}, {body_arg}, {options_arg}{reject})",
+                name.to_upper_camel_case()
+            )
+        } else if self.uses_codec() {
+            let body_arg = if self.body.is_some() {
+                "body"
+            } else {
+                "undefined"
+            };
+            let (ty, decoder) = self.decoder(db, api);
+            format!(
+                "requestCustom<{ty}>({method:?}, {url}, {body_arg}, {options_arg}, {}, {decoder})",
+                self.encoder(db, api)
+            )
+        } else {
+            match &self.response {
+                Some(res) => match res {
+                    ResponseKind::Empty => match api.config(db).empty_response {
+                        EmptyResponse::Record => format!("requestEmpty({args})"),
+                        EmptyResponse::Void => format!("requestVoid({args})"),
+                    },
+                    ResponseKind::Plain | ResponseKind::Xml => {
+                        format!("requestPlain({args})",)
                     }
-                    _ => format!("sse<{}>({args})", ty.ts(db)),
+                    ResponseKind::Json(ty) => {
+                        format!("requestJson<{}>({args})", ty.ts(db))
+                    }
+                    ResponseKind::EventStream(ty) => match ty.kind(db) {
+                        TypeKind::Array(inner) => {
+                            format!("sse<{}>({args})", inner.ts(db))
+                        }
+                        _ => format!("sse<{}>({args})", ty.ts(db)),
+                    },
+                    ResponseKind::Csv(row) => format!(
+                        "requestCsv<{}>({args}, {})",
+                        row.ts(db),
+                        csv_columns(db, api, *row)
+                    ),
+                    ResponseKind::Blob => format!("requestBlob({args})"),
+                    ResponseKind::Redirect => format!("requestRedirect({args})"),
+                    ResponseKind::Custom(_) => unreachable!(),
                 },
-            },
-            None => todo!(),
+                None => unreachable!("operations without a response are left out"),
+            }
+        };
+        let request_impl = if self.dedupes(db, api) {
+            format!("dedupe({method:?}, {url}, options, () => {request_impl})")
+        } else {
+            request_impl
         };
 
         (
@@ -0,0 +1,166 @@
+mod common;
+
+use abeye::Config;
+use common::{document, generate_ts, list_items};
+
+#[test]
+fn bodyless_operation_passes_metric_options_after_the_body() {
+    let ts = generate_ts(
+        document(list_items(), serde_json::json!({})),
+        Config::builder().telemetry(true).build(),
+    );
+
+    assert!(ts.contains(", undefined, withMetric("), "{ts}");
+}
+
+#[test]
+fn rate_limited_bodyless_operation_passes_options_after_the_body() {
+    let mut paths = list_items();
+    paths["/items"]["get"]["responses"]["429"] = serde_json::json!({ "description": "Slow down" });
+    let ts = generate_ts(document(paths, serde_json::json!({})), Config::default());
+
+    assert!(ts.contains(", undefined, withRateLimit(options)"), "{ts}");
+}
+
+#[test]
+fn client_factory_passes_merged_options_to_bodyless_operations() {
+    let ts = generate_ts(
+        document(list_items(), serde_json::json!({})),
+        Config::builder().client_factory(true).build(),
+    );
+
+    assert!(ts.contains("mergeOptions(defaults, options)"), "{ts}");
+    assert!(ts.contains(", undefined, options)"), "{ts}");
+}
+
+#[test]
+fn bodyless_operation_sends_header_parameters_as_headers() {
+    let mut paths = list_items();
+    paths["/items"]["get"]["parameters"] = serde_json::json!([
+        { "name": "X-Tenant", "in": "header", "required": true, "schema": { "type": "string" } }
+    ]);
+    let ts = generate_ts(document(paths, serde_json::json!({})), Config::default());
+
+    assert!(
+        ts.contains(", undefined, withHeaders(headers, options)"),
+        "{ts}"
+    );
+}
+
+/// `GET /items` with the header parameter `X-Tenant`.
+fn tenant_items() -> serde_json::Value {
+    let mut paths = list_items();
+    paths["/items"]["get"]["parameters"] = serde_json::json!([
+        { "name": "X-Tenant", "in": "header", "required": true, "schema": { "type": "string" } }
+    ]);
+    document(paths, serde_json::json!({}))
+}
+
+#[test]
+fn python_client_sends_header_parameters() {
+    let py = common::with_api(tenant_items(), Config::default(), |db, api| {
+        abeye::generate_py(db, api)
+    });
+
+    assert!(py.contains("x_tenant: Optional[str] = None"), "{py}");
+    assert!(py.contains(r#"headers={"X-Tenant": x_tenant}"#), "{py}");
+}
+
+#[test]
+fn rust_client_sends_header_parameters() {
+    let rs = common::with_api(tenant_items(), Config::default(), |db, api| {
+        abeye::generate_rs(db, api)
+    });
+
+    assert!(rs.contains("x_tenant: Option<String>"), "{rs}");
+    assert!(
+        rs.contains(r#".headers(header_map(&[query_pairs("X-Tenant", &x_tenant)].concat()))"#),
+        "{rs}"
+    );
+}
+
+/// A `POST /items` operation taking a JSON body and the query parameter
+/// `dryRun` with the given schema.
+fn create_item(dry_run: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "/items": {
+            "post": {
+                "operationId": "createItem",
+                "parameters": [{ "name": "dryRun", "in": "query", "schema": dry_run }],
+                "requestBody": {
+                    "required": true,
+                    "content": { "application/json": { "schema": { "type": "string" } } }
+                },
+                "responses": { "204": { "description": "Created" } }
+            }
+        }
+    })
+}
+
+#[test]
+fn defaulted_query_before_a_body_stays_required() {
+    let ts = generate_ts(
+        document(
+            create_item(serde_json::json!({ "type": "boolean", "default": false })),
+            serde_json::json!({}),
+        ),
+        Config::builder().fill_query_defaults(true).build(),
+    );
+
+    assert!(!ts.contains("query?"), "{ts}");
+    assert!(ts.contains("dryRun?: boolean;"), "{ts}");
+}
+
+#[test]
+fn optional_query_before_a_body_stays_required() {
+    let ts = generate_ts(
+        document(
+            create_item(serde_json::json!({ "type": "boolean" })),
+            serde_json::json!({}),
+        ),
+        Config::default(),
+    );
+
+    assert!(!ts.contains("query?"), "{ts}");
+    assert!(ts.contains("dryRun?: boolean;"), "{ts}");
+}
+
+#[test]
+fn optional_query_without_a_body_is_optional() {
+    let mut paths = list_items();
+    paths["/items"]["get"]["parameters"] = serde_json::json!([
+        { "name": "limit", "in": "query", "schema": { "type": "integer" } }
+    ]);
+    let ts = generate_ts(document(paths, serde_json::json!({})), Config::default());
+
+    assert!(ts.contains("query?: {"), "{ts}");
+}
+
+#[test]
+fn plain_text_response_with_an_enum_schema_is_reported() {
+    let paths = serde_json::json!({
+        "/status": {
+            "get": {
+                "responses": {
+                    "200": {
+                        "description": "The status",
+                        "content": {
+                            "text/plain": { "schema": { "type": "string", "enum": ["up", "down"] } }
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    common::with_api(
+        document(paths, serde_json::json!({})),
+        Config::default(),
+        |db, api| {
+            assert!(abeye::generate_ts(db, api).contains("requestPlain("));
+            assert!(abeye::diagnostics(db, api)
+                .iter()
+                .any(|diagnostic| diagnostic.message.starts_with("plain text response")));
+        },
+    );
+}
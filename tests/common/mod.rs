@@ -0,0 +1,50 @@
+#![allow(dead_code)]
+
+use abeye::{Config, Database, InputApi};
+
+/// A document with the given paths and component schemas.
+pub fn document(paths: serde_json::Value, schemas: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "openapi": "3.0.3",
+        "info": { "title": "Test", "version": "1.0.0" },
+        "paths": paths,
+        "components": { "schemas": schemas },
+    })
+}
+
+/// Runs `f` with the document loaded into a fresh database.
+pub fn with_api<T>(
+    doc: serde_json::Value,
+    config: Config,
+    f: impl FnOnce(&Database, InputApi) -> T,
+) -> T {
+    let db = Database::default();
+    let api = InputApi::new(&db, serde_json::from_value(doc).unwrap(), config);
+    f(&db, api)
+}
+
+/// The TypeScript generated for the document as a single file.
+pub fn generate_ts(doc: serde_json::Value, config: Config) -> String {
+    with_api(doc, config, |db, api| abeye::generate_ts(db, api))
+}
+
+/// A `GET /items` operation responding with a JSON array of strings.
+pub fn list_items() -> serde_json::Value {
+    serde_json::json!({
+        "/items": {
+            "get": {
+                "operationId": "listItems",
+                "responses": {
+                    "200": {
+                        "description": "The items",
+                        "content": {
+                            "application/json": {
+                                "schema": { "type": "array", "items": { "type": "string" } }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use abeye::Config;
+use common::{document, with_api};
+
+/// Component schemas with the family `Page_User` and `Page_Post`, used by a
+/// `Feed`.
+fn schemas() -> serde_json::Value {
+    let page = |item: &str| {
+        serde_json::json!({
+            "type": "object",
+            "required": ["items"],
+            "properties": {
+                "items": { "type": "array", "items": { "$ref": format!("#/components/schemas/{item}") } },
+                "next": { "type": "string" },
+            },
+        })
+    };
+    let object = serde_json::json!({
+        "type": "object",
+        "required": ["name"],
+        "properties": { "name": { "type": "string" } },
+    });
+    serde_json::json!({
+        "User": object,
+        "Post": object,
+        "Page_User": page("User"),
+        "Page_Post": page("Post"),
+        "Feed": {
+            "type": "object",
+            "required": ["users", "posts"],
+            "properties": {
+                "users": { "$ref": "#/components/schemas/Page_User" },
+                "posts": { "$ref": "#/components/schemas/Page_Post" },
+            },
+        },
+    })
+}
+
+fn generate<T>(f: impl FnOnce(&abeye::Database, abeye::InputApi) -> T) -> T {
+    with_api(
+        document(serde_json::json!({}), schemas()),
+        Config::builder().generic_schemas(true).build(),
+        f,
+    )
+}
+
+#[test]
+fn typescript_declares_and_instantiates_the_generic_type() {
+    let ts = generate(|db, api| abeye::generate_ts(db, api));
+
+    assert!(ts.contains("export type Page<T> = "), "{ts}");
+    assert!(ts.contains("Page<User>"), "{ts}");
+    assert!(ts.contains("Page<Post>"), "{ts}");
+}
+
+#[test]
+fn python_declares_a_generic_model() {
+    let py = generate(|db, api| abeye::generate_py(db, api));
+
+    assert!(py.contains("T = TypeVar(\"T\")"), "{py}");
+    assert!(py.contains("class Page(BaseModel, Generic[T]):"), "{py}");
+    assert!(py.contains("\"Page[User]\""), "{py}");
+    assert!(!py.contains("Page<"), "{py}");
+}
+
+#[test]
+fn rust_declares_a_generic_struct() {
+    let rs = generate(|db, api| abeye::generate_rs(db, api));
+
+    assert!(rs.contains("pub struct Page<T> {"), "{rs}");
+    assert!(rs.contains("Page<User>"), "{rs}");
+    assert!(rs.contains("Vec<T>"), "{rs}");
+}
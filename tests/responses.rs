@@ -0,0 +1,50 @@
+mod common;
+
+use abeye::Config;
+use common::{document, list_items, with_api};
+
+/// The kind of the response of `GET /items` when typed by the schema.
+fn response_kind(schema: serde_json::Value) -> &'static str {
+    let mut paths = list_items();
+    paths["/items"]["get"]["responses"]["200"]["content"]["application/json"]["schema"] = schema;
+    with_api(
+        document(paths, serde_json::json!({})),
+        Config::default(),
+        |db, api| {
+            let api = abeye::TemplateApi::new(db, api);
+            api.operations[0].response.as_ref().unwrap().kind
+        },
+    )
+}
+
+#[test]
+fn object_without_properties_is_an_empty_response() {
+    assert_eq!(
+        response_kind(serde_json::json!({ "type": "object" })),
+        "empty"
+    );
+}
+
+#[test]
+fn closed_object_without_properties_is_an_empty_response() {
+    let schema = serde_json::json!({ "type": "object", "additionalProperties": false });
+
+    assert_eq!(response_kind(schema), "empty");
+}
+
+#[test]
+fn free_form_object_is_a_json_response() {
+    let schema = serde_json::json!({ "type": "object", "additionalProperties": true });
+
+    assert_eq!(response_kind(schema), "json");
+}
+
+#[test]
+fn map_is_a_json_response() {
+    let schema = serde_json::json!({
+        "type": "object",
+        "additionalProperties": { "type": "number" },
+    });
+
+    assert_eq!(response_kind(schema), "json");
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use abeye::{Config, TsValidators, TypeImports};
+use common::{document, generate_ts, list_items, with_api};
+
+/// A `Team` component type referencing a `User` component type.
+fn schemas() -> serde_json::Value {
+    serde_json::json!({
+        "User": {
+            "type": "object",
+            "required": ["name"],
+            "properties": { "name": { "type": "string" } },
+        },
+        "Team": {
+            "type": "object",
+            "required": ["members"],
+            "properties": {
+                "members": { "type": "array", "items": { "$ref": "#/components/schemas/User" } },
+            },
+        },
+    })
+}
+
+fn single_file(type_imports: TypeImports) -> String {
+    generate_ts(
+        document(list_items(), schemas()),
+        Config::builder()
+            .runtime_import("./runtime".to_string())
+            .type_imports(type_imports)
+            .build(),
+    )
+}
+
+/// The module declaring `Team` when generating a directory of modules.
+fn team_module(type_imports: TypeImports) -> String {
+    let files = with_api(
+        document(list_items(), schemas()),
+        Config::builder()
+            .ts_validators(TsValidators::Guards)
+            .type_imports(type_imports)
+            .build(),
+        |db, api| abeye::generate_ts_files(db, api),
+    );
+    files
+        .into_iter()
+        .find(|(path, _)| path.ends_with("Team.ts"))
+        .map(|(_, source)| source)
+        .unwrap()
+}
+
+#[test]
+fn inline_type_imports_mark_runtime_types_in_place() {
+    let ts = single_file(TypeImports::Inline);
+
+    assert!(ts.contains("  type ApiOptions,"), "{ts}");
+    assert!(!ts.contains("import type {"), "{ts}");
+}
+
+#[test]
+fn separate_type_imports_import_runtime_types_on_their_own() {
+    let ts = single_file(TypeImports::Separate);
+
+    assert!(ts.contains("import type {\n  ApiOptions,"), "{ts}");
+    assert!(!ts.contains("  type ApiOptions,"), "{ts}");
+}
+
+#[test]
+fn inline_type_imports_in_split_modules() {
+    let ts = team_module(TypeImports::Inline);
+
+    assert!(ts.contains("import { type User, isUser } from"), "{ts}");
+    assert!(ts.contains("export type Team = "), "{ts}");
+}
+
+#[test]
+fn separate_type_imports_in_split_modules() {
+    let ts = team_module(TypeImports::Separate);
+
+    assert!(ts.contains("import type { User } from"), "{ts}");
+    assert!(ts.contains("import { isUser } from"), "{ts}");
+    assert!(!ts.contains("type User,"), "{ts}");
+}
@@ -0,0 +1,279 @@
+mod common;
+
+use abeye::Config;
+use common::{document, with_api};
+
+/// The TypeScript for the component schema `Subject` in the given schemas.
+fn subject_ts(schemas: serde_json::Value) -> String {
+    with_api(
+        document(serde_json::json!({}), schemas),
+        Config::default(),
+        |db, api| {
+            abeye::lower_schema(db, api, "Subject")
+                .unwrap()
+                .to_typescript(db)
+        },
+    )
+}
+
+#[test]
+fn array_of_nullable_items_is_parenthesized() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": { "type": "array", "items": { "type": "string", "nullable": true } },
+    }));
+
+    assert_eq!(ts, "(string | null)[]");
+}
+
+#[test]
+fn array_of_a_union_is_parenthesized() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": {
+            "type": "array",
+            "items": { "oneOf": [{ "type": "string" }, { "type": "number" }] },
+        },
+    }));
+
+    assert!(ts.starts_with('(') && ts.ends_with(")[]"), "{ts}");
+}
+
+#[test]
+fn array_of_a_single_type_is_not_parenthesized() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": { "type": "array", "items": { "type": "string" } },
+    }));
+
+    assert_eq!(ts, "string[]");
+}
+
+/// A `Pet` union discriminated by `kind`, with the variant `Cat` declaring
+/// `kind` as a string. `pet_first` decides which is declared first.
+fn pets(pet_first: bool) -> serde_json::Value {
+    let pet = serde_json::json!({
+        "oneOf": [{ "$ref": "#/components/schemas/Subject" }],
+        "discriminator": {
+            "propertyName": "kind",
+            "mapping": { "cat": "#/components/schemas/Subject" },
+        },
+    });
+    let cat = serde_json::json!({
+        "type": "object",
+        "required": ["kind", "name"],
+        "properties": { "kind": { "type": "string" }, "name": { "type": "string" } },
+    });
+    let mut schemas = serde_json::Map::new();
+    if pet_first {
+        schemas.insert("Pet".to_string(), pet);
+        schemas.insert("Subject".to_string(), cat);
+    } else {
+        schemas.insert("Subject".to_string(), cat);
+        schemas.insert("Pet".to_string(), pet);
+    }
+    serde_json::Value::Object(schemas)
+}
+
+#[test]
+fn discriminator_property_of_a_variant_is_narrowed_to_its_tag() {
+    for pet_first in [true, false] {
+        let ts = subject_ts(pets(pet_first));
+
+        assert!(ts.contains("kind: \"cat\""), "{ts}");
+        assert!(!ts.contains("kind: string"), "{ts}");
+        assert!(ts.contains("name: string"), "{ts}");
+    }
+}
+
+#[test]
+fn discriminator_property_which_is_not_a_string_is_reported() {
+    let mut schemas = pets(true);
+    schemas["Subject"]["properties"]["kind"] = serde_json::json!({ "type": "number" });
+    let doc = document(serde_json::json!({}), schemas);
+    let diagnostics = with_api(doc, Config::default(), |db, api| {
+        abeye::diagnostics(db, api)
+    });
+
+    assert!(
+        diagnostics.iter().any(|diagnostic| {
+            diagnostic.kind == abeye::DiagnosticKind::Inconsistent
+                && diagnostic.pointer == "/components/schemas/Subject"
+                && diagnostic
+                    .message
+                    .starts_with("discriminator property \"kind\"")
+        }),
+        "{diagnostics:?}"
+    );
+}
+
+#[test]
+fn discriminator_property_inherited_through_all_of_is_left_alone() {
+    let mut schemas = pets(true);
+    schemas["Base"] = schemas["Subject"].clone();
+    schemas["Subject"] = serde_json::json!({
+        "allOf": [
+            { "$ref": "#/components/schemas/Base" },
+            { "type": "object", "properties": { "age": { "type": "number" } } },
+        ],
+    });
+    let ts = subject_ts(schemas);
+
+    assert!(ts.contains("Base"), "{ts}");
+    assert!(!ts.contains("\"cat\""), "{ts}");
+}
+
+#[test]
+fn literals_subsumed_by_string_are_dropped_from_unions() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": {
+            "oneOf": [{ "type": "string", "enum": ["a", "b"] }, { "type": "string" }],
+        },
+    }));
+
+    assert_eq!(ts, "string");
+}
+
+#[test]
+fn literals_subsumed_by_string_are_reported() {
+    let doc = document(
+        serde_json::json!({}),
+        serde_json::json!({
+            "Subject": {
+                "oneOf": [{ "type": "string", "enum": ["a"] }, { "type": "string" }],
+            },
+        }),
+    );
+    let diagnostics = with_api(doc, Config::default(), |db, api| {
+        abeye::diagnostics(db, api)
+    });
+
+    assert!(
+        diagnostics.iter().any(|diagnostic| {
+            diagnostic.kind == abeye::DiagnosticKind::Inconsistent
+                && diagnostic.pointer == "/components/schemas/Subject"
+                && diagnostic.message.contains("\"a\"")
+        }),
+        "{diagnostics:?}"
+    );
+}
+
+#[test]
+fn union_of_distinct_primitives_is_kept() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": {
+            "oneOf": [{ "type": "string" }, { "type": "number" }, { "type": "boolean" }],
+        },
+    }));
+
+    assert_eq!(ts.matches(" | ").count(), 2, "{ts}");
+    for member in ["string", "number", "boolean"] {
+        assert!(ts.contains(member), "{ts}");
+    }
+}
+
+#[test]
+fn union_of_formatted_strings_collapses_to_string() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": {
+            "oneOf": [
+                { "type": "string", "format": "date-time" },
+                { "type": "string", "format": "uuid" },
+            ],
+        },
+    }));
+
+    assert_eq!(ts, "string");
+}
+
+#[test]
+fn any_of_is_a_union() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": { "anyOf": [{ "type": "string" }, { "type": "number" }] },
+    }));
+
+    assert_eq!(ts.matches(" | ").count(), 1, "{ts}");
+    assert!(ts.contains("string") && ts.contains("number"), "{ts}");
+}
+
+#[test]
+fn single_member_any_of_collapses_to_the_member() {
+    let ts = subject_ts(serde_json::json!({
+        "User": { "type": "object", "properties": { "name": { "type": "string" } } },
+        "Subject": { "anyOf": [{ "$ref": "#/components/schemas/User" }] },
+    }));
+
+    assert_eq!(ts, "User");
+}
+
+#[test]
+fn nullable_any_of_is_a_nullable_union() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": { "anyOf": [{ "type": "string" }, { "type": "number" }], "nullable": true },
+    }));
+
+    assert!(ts.ends_with(" | null"), "{ts}");
+}
+
+#[test]
+fn free_form_object_is_a_record_of_unknown() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": { "type": "object", "additionalProperties": true },
+    }));
+
+    assert_eq!(ts, "Record<string, unknown>");
+}
+
+#[test]
+fn free_form_object_with_properties_keeps_them() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": {
+            "type": "object",
+            "required": ["name"],
+            "properties": { "name": { "type": "string" } },
+            "additionalProperties": true,
+        },
+    }));
+
+    assert!(ts.contains("name: string"), "{ts}");
+    assert!(ts.contains(" & "), "{ts}");
+    assert!(ts.contains("Record<string, unknown>"), "{ts}");
+}
+
+#[test]
+fn closed_object_is_not_a_record() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": { "type": "object", "additionalProperties": false },
+    }));
+
+    assert!(!ts.contains("Record"), "{ts}");
+}
+
+/// An object with the string properties `a` and `b`, requiring those given.
+fn object_requiring(required: &[&str]) -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "required": required,
+        "properties": { "a": { "type": "string" }, "b": { "type": "string" } },
+    })
+}
+
+#[test]
+fn union_member_requiring_more_is_merged_into_the_other() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": { "oneOf": [object_requiring(&["a", "b"]), object_requiring(&["a"])] },
+    }));
+
+    assert!(!ts.contains(" | "), "{ts}");
+    assert!(
+        ts.contains("a: string") && ts.contains("b?: string"),
+        "{ts}"
+    );
+}
+
+#[test]
+fn union_members_with_opposite_optionality_are_kept_apart() {
+    let ts = subject_ts(serde_json::json!({
+        "Subject": { "oneOf": [object_requiring(&["a"]), object_requiring(&["b"])] },
+    }));
+
+    assert_eq!(ts.matches(" | ").count(), 1, "{ts}");
+    assert!(!ts.contains("a?: string; b?: string"), "{ts}");
+}